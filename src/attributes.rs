@@ -1,6 +1,6 @@
 //! Basic font attributes: stretch, weight and style.
 
-use super::internal::{head::Os2, RawFont};
+use super::internal::head::Os2;
 use super::{tag_from_bytes, FontRef, Tag, Setting};
 
 use core::fmt;
@@ -3,19 +3,23 @@ Mapping characters to nominal glyph identifiers.
 */
 
 use super::internal::cmap;
+use super::text::cluster::GlyphMapper;
 use super::{FontRef, GlyphId};
 
+pub use super::internal::cmap::MapVariant;
+
 /// Proxy for rematerializing a character map.
 #[derive(Copy, Clone, Default, Debug)]
-pub struct CharmapProxy(u32, u8, bool);
+pub struct CharmapProxy(u32, u8, u8, u32);
 
 impl CharmapProxy {
     /// Creates character map proxy from the specified font.
     pub fn from_font(font: &FontRef) -> Self {
-        if let Some((offset, format, symbol)) = cmap::subtable(font) {
-            Self(offset, format, symbol)
+        let variations = cmap::variation_subtable(font).unwrap_or(0);
+        if let Some((offset, format, encoding)) = cmap::subtable(font) {
+            Self(offset, format, encoding, variations)
         } else {
-            Self(0, 0, false)
+            Self(0, 0, 0, variations)
         }
     }
 
@@ -27,6 +31,20 @@ impl CharmapProxy {
 }
 
 /// Maps characters to nominal glyph identifiers.
+///
+/// A single subtable is selected in the following order: Microsoft symbol,
+/// Unicode full repertoire (formats 12 and 10), Unicode BMP (formats 4, 6,
+/// 0 and 2), Unicode "last resort" (format 13), Mac OS Roman and finally any
+/// other platform specific encoding.
+///
+/// Codepoints are always Unicode. For symbol fonts, codepoints in the range
+/// U+0000..=U+00FF are also mapped through the private use area at U+F000
+/// and vice versa. For fonts that only provide the Windows Shift-JIS, PRC,
+/// Big5, Wansung or Johab encodings, codepoints are converted to the native
+/// encoding when the `legacy-encodings` feature is enabled. Other platform
+/// specific legacy encodings do not map any Unicode codepoints; their
+/// character codes can be accessed with [`map_code`](Self::map_code) and
+/// [`enumerate_codes`](Self::enumerate_codes).
 #[derive(Copy, Clone)]
 pub struct Charmap<'a> {
     data: &'a [u8],
@@ -50,12 +68,141 @@ impl<'a> Charmap<'a> {
 
     /// Returns a nominal glyph identifier for the specified codepoint.
     pub fn map(&self, codepoint: impl Into<u32>) -> GlyphId {
-        cmap::map(self.data, self.proxy.0, self.proxy.1, codepoint.into()).unwrap_or(0)
+        cmap::map(
+            self.data,
+            self.proxy.0,
+            self.proxy.1,
+            self.proxy.2,
+            codepoint.into(),
+        )
+        .unwrap_or(0)
+    }
+
+    /// Returns true if the selected subtable uses a platform specific legacy
+    /// encoding rather than Unicode.
+    pub fn is_legacy(&self) -> bool {
+        self.proxy.2 >= cmap::LEGACY
+    }
+
+    /// Returns a nominal glyph identifier for the specified character code
+    /// in the native encoding of the selected subtable.
+    pub fn map_code(&self, code: u32) -> GlyphId {
+        cmap::map_code(self.data, self.proxy.0, self.proxy.1, code).unwrap_or(0)
+    }
+
+    /// Invokes the specified closure with all character code/glyph
+    /// identifier pairs in the native encoding of the selected subtable.
+    pub fn enumerate_codes(&self, f: impl FnMut(u32, GlyphId)) {
+        cmap::enumerate_codes(self.data, self.proxy.0, f);
+    }
+
+    /// Returns the result of mapping the variation sequence composed of the
+    /// specified codepoint and variation selector. Returns `None` if the
+    /// font does not support the sequence.
+    pub fn map_variant(
+        &self,
+        codepoint: impl Into<u32>,
+        selector: impl Into<u32>,
+    ) -> Option<MapVariant> {
+        cmap::map_variant(
+            self.data,
+            self.proxy.3,
+            codepoint.into(),
+            selector.into(),
+        )
     }
 
     /// Invokes the specified closure with all codepoint/glyph identifier
     /// pairs in the character map.
     pub fn enumerate(&self, f: impl FnMut(u32, GlyphId)) {
-        cmap::enumerate(self.data, self.proxy.0, f);
+        cmap::enumerate(self.data, self.proxy.0, self.proxy.2, f);
     }
+
+    /// Builds a reverse character map from the glyph identifiers to the
+    /// codepoints that map to them.
+    pub fn reverse(&self) -> ReverseCharmap {
+        let mut pairs = Vec::new();
+        self.enumerate(|ch, glyph_id| pairs.push((glyph_id, ch)));
+        pairs.sort_unstable();
+        pairs.dedup();
+        ReverseCharmap { pairs }
+    }
+
+    /// Returns true if the selected subtable uses the symbol encoding.
+    pub(crate) fn is_symbol(&self) -> bool {
+        self.proxy.2 == cmap::SYMBOL
+    }
+}
+
+impl<'a> GlyphMapper for Charmap<'a> {
+    fn map(&self, ch: char) -> GlyphId {
+        Charmap::map(self, ch)
+    }
+
+    fn map_variant(&self, ch: char, selector: char) -> Option<GlyphId> {
+        match Charmap::map_variant(self, ch, selector)? {
+            MapVariant::Variant(glyph_id) => Some(glyph_id),
+            MapVariant::UseDefault => None,
+        }
+    }
+}
+
+/// Maps glyph identifiers back to the codepoints that nominally map to them.
+///
+/// This is built from a character map with [`Charmap::reverse`] and only
+/// covers the nominal mappings defined by the font. Glyphs produced by
+/// substitutions during shaping, such as ligatures and alternates, are not
+/// included.
+#[derive(Clone, Default, Debug)]
+pub struct ReverseCharmap {
+    pairs: Vec<(GlyphId, u32)>,
+}
+
+impl ReverseCharmap {
+    /// Returns the number of glyph/codepoint pairs in the map.
+    pub fn len(&self) -> usize {
+        self.pairs.len()
+    }
+
+    /// Returns true if the map is empty.
+    pub fn is_empty(&self) -> bool {
+        self.pairs.is_empty()
+    }
+
+    /// Returns the preferred codepoint for the specified glyph identifier.
+    ///
+    /// When multiple codepoints map to the glyph, the lowest codepoint
+    /// outside of the private use areas is preferred.
+    pub fn get(&self, glyph_id: GlyphId) -> Option<char> {
+        let mut first = None;
+        for ch in self.get_all(glyph_id) {
+            if !is_private_use(ch as u32) {
+                return Some(ch);
+            }
+            first = first.or(Some(ch));
+        }
+        first
+    }
+
+    /// Returns an iterator over all codepoints that map to the specified
+    /// glyph identifier in ascending order.
+    pub fn get_all(&self, glyph_id: GlyphId) -> impl Iterator<Item = char> + Clone + '_ {
+        let start = self.pairs.partition_point(|p| p.0 < glyph_id);
+        self.pairs[start..]
+            .iter()
+            .take_while(move |p| p.0 == glyph_id)
+            .filter_map(|p| core::char::from_u32(p.1))
+    }
+
+    /// Returns an iterator over all glyph identifier/codepoint pairs in the
+    /// map, ordered by glyph identifier.
+    pub fn iter(&self) -> impl Iterator<Item = (GlyphId, char)> + Clone + '_ {
+        self.pairs
+            .iter()
+            .filter_map(|p| Some((p.0, core::char::from_u32(p.1)?)))
+    }
+}
+
+fn is_private_use(cp: u32) -> bool {
+    (0xE000..=0xF8FF).contains(&cp) || cp >= 0xF0000
 }
@@ -0,0 +1,276 @@
+/*!
+Compact character coverage sets.
+*/
+
+use super::cache::FontCache;
+use super::text::cluster::CharCluster;
+use super::text::Block;
+use super::{Charmap, FontRef};
+use core::ops::RangeInclusive;
+
+/// Set of characters supported by a font.
+///
+/// The set is stored as a sorted list of disjoint ranges and is intended to
+/// be built once per font and retained alongside other cached font data,
+/// for example in a [`CoverageCache`]. This makes it cheap to test clusters
+/// against many fonts during font fallback.
+#[derive(Clone, PartialEq, Eq, Default, Debug)]
+pub struct Coverage {
+    ranges: Vec<(u32, u32)>,
+}
+
+impl Coverage {
+    /// Creates a new empty coverage set.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a coverage set from the character map of the specified font.
+    pub fn from_font(font: &FontRef) -> Self {
+        Self::from_charmap(&font.charmap())
+    }
+
+    /// Creates a coverage set from the specified character map.
+    ///
+    /// For symbol fonts, characters in the ranges U+0000..=U+00FF and
+    /// U+F000..=U+F0FF are mirrored into each other to match the behavior
+    /// of [`Charmap::map`].
+    pub fn from_charmap(charmap: &Charmap) -> Self {
+        let mut ranges: Vec<(u32, u32)> = Vec::new();
+        let mut sorted = true;
+        let symbol = charmap.is_symbol();
+        charmap.enumerate(|ch, _| {
+            if let Some(last) = ranges.last_mut() {
+                if ch == last.1 + 1 {
+                    last.1 = ch;
+                    return;
+                }
+                sorted &= ch > last.1;
+            }
+            ranges.push((ch, ch));
+        });
+        if symbol {
+            // Mirror the low codes and the private use area in both
+            // directions as is done when mapping.
+            for i in 0..ranges.len() {
+                let (start, end) = ranges[i];
+                let (lo_start, lo_end) = (start, end.min(0xFF));
+                if lo_start <= lo_end {
+                    ranges.push((lo_start + 0xF000, lo_end + 0xF000));
+                }
+                let (hi_start, hi_end) = (start.max(0xF000), end.min(0xF0FF));
+                if hi_start <= hi_end {
+                    ranges.push((hi_start - 0xF000, hi_end - 0xF000));
+                }
+            }
+            sorted = false;
+        }
+        let mut coverage = Self { ranges };
+        if !sorted {
+            coverage.normalize();
+        }
+        coverage
+    }
+
+    /// Creates a coverage set from a sequence of ranges which may be
+    /// unordered and overlapping.
+    pub fn from_ranges<I>(ranges: I) -> Self
+    where
+        I: IntoIterator<Item = RangeInclusive<u32>>,
+    {
+        let mut coverage = Self {
+            ranges: ranges
+                .into_iter()
+                .filter(|r| r.start() <= r.end())
+                .map(|r| (*r.start(), *r.end()))
+                .collect(),
+        };
+        coverage.normalize();
+        coverage
+    }
+
+    /// Returns the number of characters in the set.
+    pub fn len(&self) -> usize {
+        self.ranges
+            .iter()
+            .map(|r| (r.1 - r.0) as usize + 1)
+            .sum()
+    }
+
+    /// Returns true if the set is empty.
+    pub fn is_empty(&self) -> bool {
+        self.ranges.is_empty()
+    }
+
+    /// Returns an iterator over the disjoint ranges of characters in the set
+    /// in ascending order.
+    pub fn ranges(&self) -> impl Iterator<Item = RangeInclusive<u32>> + '_ {
+        self.ranges.iter().map(|r| r.0..=r.1)
+    }
+
+    /// Returns true if the set contains the specified character.
+    pub fn contains(&self, ch: impl Into<u32>) -> bool {
+        let ch = ch.into();
+        use core::cmp::Ordering::*;
+        self.ranges
+            .binary_search_by(|r| {
+                if ch < r.0 {
+                    Greater
+                } else if ch > r.1 {
+                    Less
+                } else {
+                    Equal
+                }
+            })
+            .is_ok()
+    }
+
+    /// Returns true if the set contains all characters in the cluster that
+    /// contribute to shaping. Default ignorable characters, such as
+    /// variation selectors and joiners, are not required to be present.
+    ///
+    /// This tests the cluster in its original form. Unlike
+    /// [`CharCluster::map`], it does not consider the composed or
+    /// decomposed forms of the cluster.
+    pub fn contains_cluster(&self, cluster: &CharCluster) -> bool {
+        cluster
+            .chars()
+            .iter()
+            .filter(|c| c.contributes_to_shaping && !c.ignorable)
+            .all(|c| self.contains(c.ch))
+    }
+
+    /// Returns true if the set shares any characters with the other set.
+    pub fn intersects(&self, other: &Coverage) -> bool {
+        let (mut i, mut j) = (0, 0);
+        while i < self.ranges.len() && j < other.ranges.len() {
+            let (a, b) = (self.ranges[i], other.ranges[j]);
+            if a.1 < b.0 {
+                i += 1;
+            } else if b.1 < a.0 {
+                j += 1;
+            } else {
+                return true;
+            }
+        }
+        false
+    }
+
+    /// Returns a new set containing the characters present in both this set
+    /// and the other set.
+    pub fn intersect(&self, other: &Coverage) -> Coverage {
+        let mut ranges = Vec::new();
+        let (mut i, mut j) = (0, 0);
+        while i < self.ranges.len() && j < other.ranges.len() {
+            let (a, b) = (self.ranges[i], other.ranges[j]);
+            let start = a.0.max(b.0);
+            let end = a.1.min(b.1);
+            if start <= end {
+                ranges.push((start, end));
+            }
+            if a.1 < b.1 {
+                i += 1;
+            } else {
+                j += 1;
+            }
+        }
+        Coverage { ranges }
+    }
+
+    /// Returns a new set containing the characters present in either this
+    /// set or the other set.
+    pub fn union(&self, other: &Coverage) -> Coverage {
+        let mut coverage = Coverage {
+            ranges: self
+                .ranges
+                .iter()
+                .chain(other.ranges.iter())
+                .copied()
+                .collect(),
+        };
+        coverage.normalize();
+        coverage
+    }
+
+    /// Returns the number of characters in the set that belong to the
+    /// specified block.
+    pub fn count_in_block(&self, block: Block) -> usize {
+        let mut count = 0;
+        self.count_by_block(|b, n| {
+            if b == block {
+                count += n;
+            }
+        });
+        count
+    }
+
+    /// Invokes the specified closure with each block represented in the set
+    /// and the number of characters in the set that belong to it. Blocks are
+    /// produced in ascending codepoint order.
+    pub fn count_by_block(&self, mut f: impl FnMut(Block, usize)) {
+        let mut current = None;
+        let mut count = 0;
+        for &(start, end) in &self.ranges {
+            Block::for_each_in_range(start, end, |block, n| {
+                if current != Some(block) {
+                    if let Some(current) = current {
+                        f(current, count);
+                    }
+                    current = Some(block);
+                    count = 0;
+                }
+                count += n;
+            });
+        }
+        if let Some(current) = current {
+            f(current, count);
+        }
+    }
+
+    /// Sorts the ranges and merges those that overlap or are adjacent.
+    fn normalize(&mut self) {
+        self.ranges.sort_unstable();
+        let mut merged: Vec<(u32, u32)> = Vec::with_capacity(self.ranges.len());
+        for &(start, end) in &self.ranges {
+            if let Some(last) = merged.last_mut() {
+                if start <= last.1.saturating_add(1) {
+                    last.1 = last.1.max(end);
+                    continue;
+                }
+            }
+            merged.push((start, end));
+        }
+        self.ranges = merged;
+    }
+}
+
+/// Cache of coverage sets for recently used fonts.
+///
+/// Sets are built from the character map on first use and identified by the
+/// [`CacheKey`](crate::CacheKey) of the font, so repeated fallback queries
+/// against the same fonts avoid enumerating their character maps.
+pub struct CoverageCache {
+    fonts: FontCache<Coverage>,
+}
+
+impl CoverageCache {
+    /// Creates a new coverage cache that holds up to the specified number of
+    /// fonts.
+    pub fn new(max_entries: usize) -> Self {
+        Self {
+            fonts: FontCache::new(max_entries.max(1)),
+        }
+    }
+
+    /// Returns the coverage set for the specified font, building it if
+    /// necessary.
+    pub fn get(&mut self, font: &FontRef) -> &Coverage {
+        self.fonts.get(font, Coverage::from_font).1
+    }
+}
+
+impl Default for CoverageCache {
+    fn default() -> Self {
+        Self::new(32)
+    }
+}
@@ -0,0 +1,567 @@
+/*!
+Indexed collections of fonts with CSS style matching.
+*/
+
+use super::{
+    tag_from_bytes, Attributes, CacheKey, Coverage, FontDataRef, FontRef, Stretch, StringId,
+    Style, Synthesis, Tag, Weight,
+};
+use std::path::Path;
+use std::sync::Arc;
+
+const WDTH: Tag = tag_from_bytes(b"wdth");
+const WGHT: Tag = tag_from_bytes(b"wght");
+const SLNT: Tag = tag_from_bytes(b"slnt");
+const ITAL: Tag = tag_from_bytes(b"ital");
+
+/// Shared font data owned by a database.
+type SharedData = Arc<dyn AsRef<[u8]> + Send + Sync>;
+
+/// Identifier for a font in a database.
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub struct FontId(u32);
+
+impl FontId {
+    /// Returns the index of the font in the database.
+    pub fn to_usize(self) -> usize {
+        self.0 as usize
+    }
+}
+
+/// Collection of fonts indexed by family name, attributes and character
+/// coverage.
+///
+/// Fonts are added from files, directories or byte buffers (including
+/// collections) and can then be queried by family name and attributes
+/// using the font matching algorithm defined by
+/// [CSS Fonts Level 4](https://www.w3.org/TR/css-fonts-4/#font-style-matching),
+/// including support for the ranges provided by variable fonts.
+///
+/// # Example
+/// ```no_run
+/// use swash::{FontDatabase, Weight};
+///
+/// let mut db = FontDatabase::new();
+/// db.add_dir("/usr/share/fonts");
+/// if let Some(font) = db.query(&["Noto Sans", "DejaVu Sans"], Weight::BOLD) {
+///     // Apply font.synthesis() when scaling to cover any mismatch in the
+///     // requested attributes.
+///     let charmap = font.font().charmap();
+/// }
+/// ```
+#[derive(Default)]
+pub struct FontDatabase {
+    sources: Vec<SharedData>,
+    faces: Vec<Face>,
+}
+
+impl FontDatabase {
+    /// Creates a new empty font database.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the number of fonts in the database.
+    pub fn len(&self) -> usize {
+        self.faces.len()
+    }
+
+    /// Returns true if the database is empty.
+    pub fn is_empty(&self) -> bool {
+        self.faces.is_empty()
+    }
+
+    /// Adds all fonts contained in the specified data and returns the
+    /// number of fonts that were added.
+    pub fn add_data(&mut self, data: impl AsRef<[u8]> + Send + Sync + 'static) -> usize {
+        let data: SharedData = Arc::new(data);
+        let source = self.sources.len() as u32;
+        let mut count = 0;
+        let bytes = (*data).as_ref();
+        if let Some(font_data) = FontDataRef::new(bytes) {
+            for font in font_data.fonts() {
+                let start = font.data.as_ptr() as usize - bytes.as_ptr() as usize;
+                let range = (start as u32, (start + font.data.len()) as u32);
+                if let Some(face) = Face::new(&font, source, range) {
+                    self.faces.push(face);
+                    count += 1;
+                }
+            }
+        }
+        if count != 0 {
+            self.sources.push(data);
+        }
+        count
+    }
+
+    /// Adds all fonts contained in the file at the specified path and returns
+    /// the number of fonts that were added.
+    pub fn add_file(&mut self, path: impl AsRef<Path>) -> std::io::Result<usize> {
+        let data = std::fs::read(path)?;
+        Ok(self.add_data(data))
+    }
+
+    /// Recursively adds all font files (with extensions ttf, otf, ttc, otc
+    /// or dfont) in the specified directory and returns the number of fonts that
+    /// were added. Files that cannot be read are ignored.
+    pub fn add_dir(&mut self, path: impl AsRef<Path>) -> usize {
+        let entries = match std::fs::read_dir(path) {
+            Ok(entries) => entries,
+            _ => return 0,
+        };
+        let mut count = 0;
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let file_type = match entry.file_type() {
+                Ok(file_type) => file_type,
+                _ => continue,
+            };
+            if file_type.is_dir() {
+                count += self.add_dir(&path);
+                continue;
+            }
+            let is_font = path
+                .extension()
+                .and_then(|ext| ext.to_str())
+                .map(|ext| {
+                    ["ttf", "otf", "ttc", "otc", "dfont"]
+                        .iter()
+                        .any(|e| ext.eq_ignore_ascii_case(e))
+                })
+                .unwrap_or(false);
+            if is_font {
+                count += self.add_file(&path).unwrap_or(0);
+            }
+        }
+        count
+    }
+
+    /// Returns the font with the specified identifier.
+    pub fn get(&self, id: FontId) -> Option<FontRef<'_>> {
+        let face = self.faces.get(id.to_usize())?;
+        Some(self.font(face))
+    }
+
+    /// Returns the attributes of the font with the specified identifier.
+    pub fn attributes(&self, id: FontId) -> Option<Attributes> {
+        Some(self.faces.get(id.to_usize())?.attributes)
+    }
+
+    /// Returns the character coverage of the font with the specified
+    /// identifier.
+    pub fn coverage(&self, id: FontId) -> Option<&Coverage> {
+        Some(&self.faces.get(id.to_usize())?.coverage)
+    }
+
+    /// Returns the family names of the font with the specified identifier.
+    /// This includes both the legacy and typographic family names in all
+    /// available languages.
+    pub fn family_names(&self, id: FontId) -> impl Iterator<Item = &str> + '_ {
+        self.faces
+            .get(id.to_usize())
+            .into_iter()
+            .flat_map(|face| face.families.iter().map(|s| s.as_str()))
+    }
+
+    /// Returns an iterator over the identifiers of all fonts in the
+    /// database.
+    pub fn fonts(&self) -> impl Iterator<Item = FontId> + Clone {
+        (0..self.faces.len() as u32).map(FontId)
+    }
+
+    /// Returns an iterator over the identifiers of all fonts belonging to
+    /// the specified family. Family names are compared without regard for
+    /// ASCII case.
+    pub fn family<'a>(&'a self, name: &'a str) -> impl Iterator<Item = FontId> + Clone + 'a {
+        self.faces
+            .iter()
+            .enumerate()
+            .filter(move |(_, face)| face.is_family(name))
+            .map(|(i, _)| FontId(i as u32))
+    }
+
+    /// Selects the font from the first available family in the list that
+    /// best matches the requested attributes.
+    pub fn query(
+        &self,
+        families: &[&str],
+        attributes: impl Into<Attributes>,
+    ) -> Option<FontMatch<'_>> {
+        let attributes = attributes.into();
+        families
+            .iter()
+            .find_map(|family| self.best_match(self.family(family), attributes))
+    }
+
+    /// Selects the font that best matches the requested attributes and
+    /// supports the specified character.
+    ///
+    /// Each family in the list is considered in order and the best match in
+    /// the family is selected if it supports the character. If no family
+    /// in the list does, the remaining families in the database are
+    /// considered in the order that they were added.
+    pub fn query_char(
+        &self,
+        families: &[&str],
+        attributes: impl Into<Attributes>,
+        ch: char,
+    ) -> Option<FontMatch<'_>> {
+        let attributes = attributes.into();
+        for family in families {
+            if let Some(m) = self.best_match(self.family(family), attributes) {
+                if self.faces[m.id.to_usize()].coverage.contains(ch) {
+                    return Some(m);
+                }
+            }
+        }
+        // Fall back to any family, visiting each family once.
+        for (i, face) in self.faces.iter().enumerate() {
+            if !face.coverage.contains(ch) {
+                continue;
+            }
+            let name = match face.families.first() {
+                Some(name) => name.as_str(),
+                _ => continue,
+            };
+            if self.faces[..i]
+                .iter()
+                .any(|f| f.is_family(name) && f.coverage.contains(ch))
+            {
+                continue;
+            }
+            let candidates = self
+                .family(name)
+                .filter(|id| self.faces[id.to_usize()].coverage.contains(ch));
+            if let Some(m) = self.best_match(candidates, attributes) {
+                return Some(m);
+            }
+        }
+        None
+    }
+
+    /// Selects the font from the specified candidates that best matches the
+    /// requested attributes.
+    pub fn best_match(
+        &self,
+        candidates: impl Iterator<Item = FontId> + Clone,
+        attributes: impl Into<Attributes>,
+    ) -> Option<FontMatch<'_>> {
+        let attributes = attributes.into();
+        let (stretch, weight, style) = attributes.parts();
+        let faces = candidates.filter_map(|id| Some((id, self.faces.get(id.to_usize())?)));
+        // Narrow the candidates by stretch, then style and finally weight as
+        // described by the CSS font matching algorithm.
+        let best_stretch = faces
+            .clone()
+            .map(|(_, face)| face.stretch_key(stretch))
+            .min()?;
+        let faces = faces.filter(|(_, face)| face.stretch_key(stretch) == best_stretch);
+        let best_style = faces.clone().map(|(_, face)| face.style_key(style)).min()?;
+        let faces = faces.filter(|(_, face)| face.style_key(style) == best_style);
+        let (id, face) = faces.min_by_key(|(_, face)| face.weight_key(weight))?;
+        Some(FontMatch {
+            id,
+            font: self.font(face),
+            synthesis: face.synthesize(attributes),
+        })
+    }
+
+    fn font<'a>(&'a self, face: &Face) -> FontRef<'a> {
+        FontRef {
+            data: (*self.sources[face.source as usize])
+                .as_ref()
+                .get(face.range.0 as usize..face.range.1 as usize)
+                .unwrap_or(&[]),
+            offset: face.offset,
+            key: face.key,
+        }
+    }
+}
+
+/// Result of a font database query.
+#[derive(Copy, Clone)]
+pub struct FontMatch<'a> {
+    id: FontId,
+    font: FontRef<'a>,
+    synthesis: Synthesis,
+}
+
+impl<'a> FontMatch<'a> {
+    /// Returns the identifier of the selected font.
+    pub fn id(&self) -> FontId {
+        self.id
+    }
+
+    /// Returns the selected font.
+    pub fn font(&self) -> FontRef<'a> {
+        self.font
+    }
+
+    /// Returns the synthesis suggestions for matching the requested
+    /// attributes with the selected font.
+    pub fn synthesis(&self) -> Synthesis {
+        self.synthesis
+    }
+}
+
+/// Indexed metadata for a single font.
+struct Face {
+    source: u32,
+    /// Range of the font data in the source. This is the entire source
+    /// except for fonts in resource forks.
+    range: (u32, u32),
+    offset: u32,
+    key: CacheKey,
+    families: Vec<String>,
+    attributes: Attributes,
+    /// Supported range of stretch in percent.
+    stretch: (f32, f32),
+    /// Supported range of weight.
+    weight: (f32, f32),
+    /// Supported styles.
+    style: FaceStyle,
+    coverage: Coverage,
+}
+
+#[derive(Copy, Clone)]
+struct FaceStyle {
+    normal: bool,
+    italic: bool,
+    /// Range of oblique angles in degrees, clockwise.
+    oblique: Option<(f32, f32)>,
+}
+
+impl Face {
+    fn new(font: &FontRef, source: u32, range: (u32, u32)) -> Option<Self> {
+        let mut families: Vec<String> = Vec::new();
+        for string in font.localized_strings() {
+            match string.id() {
+                StringId::Family | StringId::TypographicFamily => {}
+                _ => continue,
+            }
+            if !string.is_decodable() {
+                continue;
+            }
+            let name = string.chars().collect::<String>();
+            if !name.is_empty() && !families.iter().any(|f| f.eq_ignore_ascii_case(&name)) {
+                families.push(name);
+            }
+        }
+        // Prefer the typographic family name for display.
+        if let Some(name) = font
+            .localized_strings()
+            .find_by_id(StringId::TypographicFamily, Some("en"))
+            .or_else(|| font.localized_strings().find_by_id(StringId::Family, Some("en")))
+        {
+            let name = name.chars().collect::<String>();
+            if let Some(pos) = families.iter().position(|f| *f == name) {
+                families[..=pos].rotate_right(1);
+            }
+        }
+        if families.is_empty() {
+            return None;
+        }
+        let attributes = font.attributes();
+        let (stretch, weight, style) = attributes.parts();
+        let stretch = stretch.to_percentage();
+        let weight = weight.0 as f32;
+        let mut ranges = [(stretch, stretch), (weight, weight)];
+        let mut face_style = match style {
+            Style::Normal => FaceStyle {
+                normal: true,
+                italic: false,
+                oblique: None,
+            },
+            Style::Italic => FaceStyle {
+                normal: false,
+                italic: true,
+                oblique: None,
+            },
+            Style::Oblique(angle) => {
+                let angle = angle.to_degrees();
+                FaceStyle {
+                    normal: false,
+                    italic: false,
+                    oblique: Some((angle, angle)),
+                }
+            }
+        };
+        for var in font.variations() {
+            let (min, max) = (var.min_value(), var.max_value());
+            match var.tag() {
+                WDTH => ranges[0] = (min, max),
+                WGHT => ranges[1] = (min, max),
+                SLNT => {
+                    // The slant axis is specified counter-clockwise.
+                    face_style.oblique = Some((-max, -min));
+                    face_style.normal |= min <= 0. && max >= 0.;
+                }
+                ITAL => {
+                    face_style.normal |= min <= 0.;
+                    face_style.italic |= max >= 1.;
+                }
+                _ => {}
+            }
+        }
+        Some(Self {
+            source,
+            range,
+            offset: font.offset,
+            key: CacheKey::new(),
+            families,
+            attributes,
+            stretch: ranges[0],
+            weight: ranges[1],
+            style: face_style,
+            coverage: Coverage::from_font(font),
+        })
+    }
+
+    fn is_family(&self, name: &str) -> bool {
+        self.families.iter().any(|f| f.eq_ignore_ascii_case(name))
+    }
+
+    /// Returns the sort key for the face with respect to the desired
+    /// stretch. Narrower values are preferred for condensed or normal
+    /// requests and wider values for expanded requests.
+    fn stretch_key(&self, stretch: Stretch) -> (u8, u32) {
+        let desired = stretch.to_percentage();
+        axis_key(self.stretch, desired, desired <= 100., None)
+    }
+
+    /// Returns the sort key for the face with respect to the desired
+    /// weight.
+    fn weight_key(&self, weight: Weight) -> (u8, u32) {
+        let desired = weight.0 as f32;
+        if (400. ..=500.).contains(&desired) {
+            axis_key(self.weight, desired, false, Some(500.))
+        } else {
+            axis_key(self.weight, desired, desired < 400., None)
+        }
+    }
+
+    /// Returns the sort key for the face with respect to the desired style.
+    ///
+    /// Normal requests prefer normal faces, then positive oblique angles,
+    /// then italic faces. Italic requests prefer italic faces, then oblique
+    /// faces and then normal faces. Oblique requests prefer angles in the
+    /// same direction, then italic faces and then normal faces. Oblique
+    /// angles in the opposite direction are always considered last.
+    fn style_key(&self, style: Style) -> (u8, u32) {
+        let style_ = self.style;
+        match style {
+            Style::Normal => {
+                if style_.normal {
+                    return (0, 0);
+                }
+                match self.oblique_key(0.) {
+                    Some((tier, d)) if tier < 3 => (1, d),
+                    _ if style_.italic => (2, 0),
+                    Some((_, d)) => (3, d),
+                    _ => (4, 0),
+                }
+            }
+            Style::Italic => {
+                if style_.italic {
+                    return (0, 0);
+                }
+                match self.oblique_key(14.) {
+                    Some((tier, d)) if tier < 3 => (1 + tier, d),
+                    _ if style_.normal => (4, 0),
+                    Some((_, d)) => (5, d),
+                    _ => (6, 0),
+                }
+            }
+            Style::Oblique(angle) => {
+                let angle = angle.to_degrees();
+                match self.oblique_key(angle) {
+                    Some((tier, d)) if tier < 3 => (tier, d),
+                    _ if style_.italic && angle > 0. => (3, 0),
+                    _ if style_.normal => (4, 0),
+                    _ if style_.italic => (5, 0),
+                    Some((_, d)) => (6, d),
+                    _ => (7, 0),
+                }
+            }
+        }
+    }
+
+    /// Returns the tier and distance of the supported oblique angles with
+    /// respect to the desired angle: 0 if the angle is supported, 1 for
+    /// steeper angles in the same direction, 2 for shallower angles in the
+    /// same direction and 3 for angles in the opposite direction.
+    fn oblique_key(&self, angle: f32) -> Option<(u8, u32)> {
+        let (lo, hi) = self.style.oblique?;
+        let distance = |v: f32| ((v - angle).abs() * 64.) as u32;
+        Some(if angle >= lo && angle <= hi {
+            (0, 0)
+        } else if angle >= 0. {
+            if lo > angle {
+                (1, distance(lo))
+            } else if hi > 0. {
+                (2, distance(hi))
+            } else {
+                (3, distance(hi))
+            }
+        } else if hi < angle {
+            (1, distance(hi))
+        } else if lo < 0. {
+            (2, distance(lo))
+        } else {
+            (3, distance(lo))
+        })
+    }
+
+    /// Computes synthesis suggestions for the requested attributes, first
+    /// clamping them to the ranges supported by the variations of the face.
+    fn synthesize(&self, requested: Attributes) -> Synthesis {
+        let (stretch, weight, style) = requested.parts();
+        let mut stretch = stretch.to_percentage();
+        if self.attributes.has_stretch_variation() {
+            stretch = stretch.max(self.stretch.0).min(self.stretch.1);
+        }
+        let mut weight = weight.0 as f32;
+        if self.attributes.has_weight_variation() {
+            weight = weight.max(self.weight.0).min(self.weight.1);
+        }
+        let requested = Attributes::new(
+            Stretch::from_percentage(stretch),
+            Weight(weight as u16),
+            style,
+        );
+        self.attributes.synthesize(requested)
+    }
+}
+
+/// Computes a sort key for a supported range of values with respect to a
+/// desired value. Values inside the range are preferred, followed by
+/// values in the preferred direction, optionally limited by a threshold,
+/// and then values in the opposite direction.
+fn axis_key(range: (f32, f32), desired: f32, prefer_lower: bool, limit: Option<f32>) -> (u8, u32) {
+    let (lo, hi) = range;
+    if desired >= lo && desired <= hi {
+        return (0, 0);
+    }
+    let (value, above) = if lo > desired {
+        (lo, true)
+    } else {
+        (hi, false)
+    };
+    let distance = ((value - desired).abs() * 64.) as u32;
+    if let Some(limit) = limit {
+        // Values above the desired value up to the limit are considered
+        // first, followed by lower values and then those past the limit.
+        return if above && value <= limit {
+            (1, distance)
+        } else if !above {
+            (2, distance)
+        } else {
+            (3, distance)
+        };
+    }
+    if above != prefer_lower {
+        (1, distance)
+    } else {
+        (2, distance)
+    }
+}
@@ -12,7 +12,10 @@ impl<'a> FontDataRef<'a> {
     /// Creates font data from the specified bytes. Returns `None` if the bytes
     /// cannot trivially be determined to represent a font.
     pub fn new(data: &'a [u8]) -> Option<Self> {
-        if !raw_data::is_font(data, 0) && !raw_data::is_collection(data) {
+        if !raw_data::is_font(data, 0)
+            && !raw_data::is_collection(data)
+            && !raw_data::is_resource_fork(data)
+        {
             None
         } else {
             Some(Self {
@@ -22,9 +25,10 @@ impl<'a> FontDataRef<'a> {
         }
     }
 
-    /// Returns true if the data represents a font collection.
+    /// Returns true if the data represents a font collection or a Mac
+    /// resource fork (such as a dfont file).
     pub fn is_collection(&self) -> bool {
-        raw_data::is_collection(self.data)
+        raw_data::is_collection(self.data) || raw_data::is_resource_fork(self.data)
     }
 
     /// Returns the underlying data.
@@ -43,8 +47,12 @@ impl<'a> FontDataRef<'a> {
     }
 
     /// Returns the font at the specified index.
+    ///
+    /// For Mac resource forks, the data of the resulting font is the font
+    /// resource rather than the entire file.
     pub fn get(&self, index: usize) -> Option<FontRef<'a>> {
-        FontRef::from_offset(self.data, raw_data::offset(self.data, index as u32)?)
+        let (data, offset) = raw_data::font_data(self.data, index as u32)?;
+        FontRef::from_offset(data, offset)
     }
 
     /// Returns an iterator over the available fonts.
@@ -65,7 +73,9 @@ impl<'a> FontDataRef<'a> {
 /// selected font (although most fonts are not collections so this is almost always
 /// zero). Note that internal references in the font are stored relative to the
 /// base of the file, so the entire file must be kept in memory and it is an error
-/// to slice the data at the offset.
+/// to slice the data at the offset. The exception is fonts contained in Mac
+/// resource forks (such as dfont files) where the data refers to the font
+/// resource rather than the entire file.
 ///
 /// # Getting started
 /// As a primer, let's write a function to read a font file, construct a font reference
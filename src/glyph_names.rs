@@ -0,0 +1,56 @@
+/*!
+Mapping between glyph identifiers and glyph names.
+*/
+
+use super::internal::{cff::Charset, head::Post};
+use super::{FontRef, GlyphId};
+
+/// Glyph names defined by a font.
+///
+/// Names are sourced from the `post` table when it is version 1.0 or 2.0 and
+/// otherwise from the charset of a name-keyed `CFF` table. CID-keyed and
+/// `CFF2` outlines carry no glyph names, so fonts with those outlines only
+/// provide names through the `post` table.
+#[derive(Copy, Clone)]
+pub struct GlyphNames<'a> {
+    post: Option<Post<'a>>,
+    charset: Option<Charset<'a>>,
+}
+
+impl<'a> GlyphNames<'a> {
+    /// Creates glyph names from the specified font.
+    pub fn from_font(font: &FontRef<'a>) -> Self {
+        let post = font.post().filter(|post| post.has_names());
+        let charset = if post.is_none() {
+            Charset::from_font(font)
+        } else {
+            None
+        };
+        Self { post, charset }
+    }
+
+    /// Returns true if the font does not provide glyph names.
+    pub fn is_empty(&self) -> bool {
+        self.post.is_none() && self.charset.is_none()
+    }
+
+    /// Returns the name of the specified glyph.
+    pub fn name(&self, glyph_id: GlyphId) -> Option<&'a str> {
+        if let Some(post) = &self.post {
+            post.name(glyph_id)
+        } else {
+            self.charset?.name(glyph_id)
+        }
+    }
+
+    /// Returns the identifier of the first glyph with the specified name.
+    ///
+    /// This performs a linear search over the glyph names.
+    pub fn glyph_by_name(&self, name: &str) -> Option<GlyphId> {
+        if let Some(post) = &self.post {
+            post.glyph_by_name(name)
+        } else {
+            self.charset?.glyph_by_name(name)
+        }
+    }
+}
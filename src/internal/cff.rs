@@ -0,0 +1,342 @@
+//! Compact font format table.
+
+use super::{raw_tag, Bytes, RawFont, RawTag};
+
+pub const CFF: RawTag = raw_tag(b"CFF ");
+
+// Top dictionary operators.
+const CHARSET: u16 = 15 << 8;
+const ENCODING: u16 = 16 << 8;
+const CHAR_STRINGS: u16 = 17 << 8;
+const ROS: u16 = 12 << 8 | 30;
+
+/// Glyph names defined by the charset of a name-keyed CFF font, or the
+/// glyph to CID mapping of a CID-keyed font, along with the built-in
+/// encoding.
+#[derive(Copy, Clone)]
+pub struct Charset<'a> {
+    data: Bytes<'a>,
+    strings: u32,
+    charset: u32,
+    encoding: u32,
+    len: u16,
+    is_cid: bool,
+}
+
+impl<'a> Charset<'a> {
+    /// Creates a charset from the specified CFF table data. Returns `None` for
+    /// CID-keyed fonts and fonts using one of the predefined expert charsets.
+    pub fn new(data: &'a [u8]) -> Option<Self> {
+        Self::with_cids(data).filter(|charset| !charset.is_cid)
+    }
+
+    /// Creates a charset from the specified CFF table data, including
+    /// CID-keyed fonts. Returns `None` for fonts using one of the predefined
+    /// expert charsets.
+    pub fn with_cids(data: &'a [u8]) -> Option<Self> {
+        let b = Bytes::new(data);
+        let header_size = b.read::<u8>(2)? as usize;
+        let top_dicts = index_end(&b, header_size)?;
+        let strings = index_end(&b, top_dicts)?;
+        let top_dict = index_get(&b, top_dicts, 0)?;
+        let mut charset = 0;
+        let mut encoding = 0;
+        let mut char_strings = 0;
+        let mut is_cid = false;
+        let mut s = Bytes::new(top_dict).stream_at(0)?;
+        let mut operand = 0i32;
+        while s.remaining() != 0 {
+            let b0 = s.read::<u8>()?;
+            match b0 {
+                0..=21 => {
+                    let op = if b0 == 12 {
+                        (b0 as u16) << 8 | s.read::<u8>()? as u16
+                    } else {
+                        (b0 as u16) << 8
+                    };
+                    match op {
+                        CHARSET => charset = operand.max(0) as u32,
+                        ENCODING => encoding = operand.max(0) as u32,
+                        CHAR_STRINGS => char_strings = operand.max(0) as usize,
+                        ROS => is_cid = true,
+                        _ => {}
+                    }
+                }
+                28 => operand = s.read::<i16>()? as i32,
+                29 => operand = s.read::<i32>()?,
+                30 => loop {
+                    let n = s.read::<u8>()?;
+                    if n & 0xF == 0xF || n >> 4 == 0xF {
+                        break;
+                    }
+                },
+                32..=246 => operand = b0 as i32 - 139,
+                247..=250 => operand = (b0 as i32 - 247) * 256 + s.read::<u8>()? as i32 + 108,
+                251..=254 => operand = -(b0 as i32 - 251) * 256 - s.read::<u8>()? as i32 - 108,
+                _ => return None,
+            }
+        }
+        if char_strings == 0 || charset == 1 || charset == 2 {
+            return None;
+        }
+        let len = b.read::<u16>(char_strings)?;
+        Some(Self {
+            data: b,
+            strings: strings as u32,
+            charset,
+            encoding,
+            len,
+            is_cid,
+        })
+    }
+
+    /// Creates a charset from the specified font.
+    pub fn from_font(font: impl RawFont<'a>) -> Option<Self> {
+        Self::new(font.table_data(CFF)?)
+    }
+
+    /// Returns the number of glyphs in the font.
+    pub fn len(&self) -> u16 {
+        self.len
+    }
+
+    /// Returns true if the font is CID-keyed.
+    pub fn is_cid(&self) -> bool {
+        self.is_cid
+    }
+
+    /// Returns the string identifier for the specified glyph. For CID-keyed
+    /// fonts, this is the CID of the glyph.
+    pub fn sid(&self, glyph_id: u16) -> Option<u16> {
+        if glyph_id >= self.len {
+            return None;
+        }
+        if glyph_id == 0 || self.charset == 0 {
+            // The ISOAdobe charset maps glyphs directly to standard strings.
+            return if self.charset == 0 && glyph_id > 228 {
+                None
+            } else {
+                Some(glyph_id)
+            };
+        }
+        let b = &self.data;
+        let base = self.charset as usize;
+        match b.read::<u8>(base)? {
+            0 => b.read::<u16>(base + 1 + (glyph_id as usize - 1) * 2),
+            format @ 1..=2 => {
+                let mut s = b.stream_at(base + 1)?;
+                let mut gid = 1u16;
+                loop {
+                    let first = s.read::<u16>()?;
+                    let left = if format == 1 {
+                        s.read::<u8>()? as u16
+                    } else {
+                        s.read::<u16>()?
+                    };
+                    if glyph_id <= gid.checked_add(left)? {
+                        return first.checked_add(glyph_id - gid);
+                    }
+                    gid = gid.checked_add(left)?.checked_add(1)?;
+                }
+            }
+            _ => None,
+        }
+    }
+
+    /// Returns the string for the specified string identifier.
+    pub fn string(&self, sid: u16) -> Option<&'a str> {
+        let sid = sid as usize;
+        if sid < STANDARD_STRINGS.len() {
+            return Some(STANDARD_STRINGS[sid]);
+        }
+        let bytes = index_get(&self.data, self.strings as usize, sid - STANDARD_STRINGS.len())?;
+        core::str::from_utf8(bytes).ok()
+    }
+
+    /// Returns the name of the specified glyph.
+    pub fn name(&self, glyph_id: u16) -> Option<&'a str> {
+        if self.is_cid {
+            return None;
+        }
+        self.string(self.sid(glyph_id)?)
+    }
+
+    /// Returns the identifier of the first glyph with the specified string
+    /// identifier, or CID for CID-keyed fonts.
+    pub fn glyph_by_sid(&self, sid: u16) -> Option<u16> {
+        (0..self.len).find(|&gid| self.sid(gid) == Some(sid))
+    }
+
+    /// Returns the glyph mapped to the specified code by the built-in
+    /// encoding. Returns `None` for CID-keyed fonts and fonts using the
+    /// predefined expert encoding.
+    pub fn glyph_by_code(&self, code: u8) -> Option<u16> {
+        if self.is_cid {
+            return None;
+        }
+        match self.encoding {
+            0 => match STANDARD_ENCODING[code as usize] {
+                0 => None,
+                sid => self.glyph_by_sid(sid as u16),
+            },
+            1 => None,
+            base => {
+                let b = &self.data;
+                let base = base as usize;
+                let format = b.read::<u8>(base)?;
+                let count = b.read::<u8>(base + 1)? as usize;
+                let mut gid = 1usize;
+                let supplements = match format & 0x7F {
+                    0 => {
+                        let codes = b.read_bytes(base + 2, count)?;
+                        if let Some(i) = codes.iter().position(|&c| c == code) {
+                            return Some(i as u16 + 1).filter(|&gid| gid < self.len);
+                        }
+                        base + 2 + count
+                    }
+                    1 => {
+                        for i in 0..count {
+                            let first = b.read::<u8>(base + 2 + i * 2)? as usize;
+                            let left = b.read::<u8>(base + 3 + i * 2)? as usize;
+                            let code = code as usize;
+                            if code >= first && code <= first + left {
+                                let gid = gid + code - first;
+                                return Some(gid as u16).filter(|&gid| gid < self.len);
+                            }
+                            gid += left + 1;
+                        }
+                        base + 2 + count * 2
+                    }
+                    _ => return None,
+                };
+                if format & 0x80 == 0 {
+                    return None;
+                }
+                let count = b.read::<u8>(supplements)? as usize;
+                for i in 0..count {
+                    let entry = supplements + 1 + i * 3;
+                    if b.read::<u8>(entry)? == code {
+                        return self.glyph_by_sid(b.read::<u16>(entry + 1)?);
+                    }
+                }
+                None
+            }
+        }
+    }
+
+    /// Returns the identifier of the first glyph with the specified name.
+    pub fn glyph_by_name(&self, name: &str) -> Option<u16> {
+        (0..self.len).find(|&gid| self.name(gid) == Some(name))
+    }
+}
+
+/// Returns the offset immediately following the index at the specified
+/// offset.
+fn index_end(b: &Bytes, offset: usize) -> Option<usize> {
+    let count = b.read::<u16>(offset)? as usize;
+    if count == 0 {
+        return Some(offset + 2);
+    }
+    let offset_size = b.read::<u8>(offset + 2)? as usize;
+    let last = read_offset(b, offset + 3 + count * offset_size, offset_size)?;
+    Some(offset + 2 + (count + 1) * offset_size + last)
+}
+
+/// Returns the data for the item at the specified position in the index at
+/// the specified offset.
+fn index_get<'a>(b: &Bytes<'a>, offset: usize, index: usize) -> Option<&'a [u8]> {
+    let count = b.read::<u16>(offset)? as usize;
+    if index >= count {
+        return None;
+    }
+    let offset_size = b.read::<u8>(offset + 2)? as usize;
+    let offsets = offset + 3;
+    let start = read_offset(b, offsets + index * offset_size, offset_size)?;
+    let end = read_offset(b, offsets + (index + 1) * offset_size, offset_size)?;
+    let data = offsets + (count + 1) * offset_size - 1;
+    b.read_bytes(data + start, end.checked_sub(start)?)
+}
+
+fn read_offset(b: &Bytes, offset: usize, offset_size: usize) -> Option<usize> {
+    let mut value = 0usize;
+    for i in 0..offset_size.min(4) {
+        value = value << 8 | b.read::<u8>(offset + i)? as usize;
+    }
+    Some(value)
+}
+
+/// String identifiers for the standard encoding, indexed by character code.
+#[rustfmt::skip]
+pub const STANDARD_ENCODING: [u8; 256] = [
+    0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+    0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+    1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16,
+    17, 18, 19, 20, 21, 22, 23, 24, 25, 26, 27, 28, 29, 30, 31, 32,
+    33, 34, 35, 36, 37, 38, 39, 40, 41, 42, 43, 44, 45, 46, 47, 48,
+    49, 50, 51, 52, 53, 54, 55, 56, 57, 58, 59, 60, 61, 62, 63, 64,
+    65, 66, 67, 68, 69, 70, 71, 72, 73, 74, 75, 76, 77, 78, 79, 80,
+    81, 82, 83, 84, 85, 86, 87, 88, 89, 90, 91, 92, 93, 94, 95, 0,
+    0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+    0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+    0, 96, 97, 98, 99, 100, 101, 102, 103, 104, 105, 106, 107, 108, 109, 110,
+    0, 111, 112, 113, 114, 0, 115, 116, 117, 118, 119, 120, 121, 122, 0, 123,
+    0, 124, 125, 126, 127, 128, 129, 130, 131, 0, 132, 133, 0, 134, 135, 136,
+    137, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+    0, 138, 0, 139, 0, 0, 0, 0, 140, 141, 142, 143, 0, 0, 0, 0,
+    0, 144, 0, 0, 0, 145, 0, 0, 146, 147, 148, 149, 0, 0, 0, 0,
+];
+
+/// Standard strings predefined by the CFF specification.
+pub const STANDARD_STRINGS: [&str; 391] = [
+    ".notdef", "space", "exclam", "quotedbl", "numbersign", "dollar", "percent", "ampersand",
+    "quoteright", "parenleft", "parenright", "asterisk", "plus", "comma", "hyphen", "period",
+    "slash", "zero", "one", "two", "three", "four", "five", "six", "seven", "eight", "nine",
+    "colon", "semicolon", "less", "equal", "greater", "question", "at", "A", "B", "C", "D", "E",
+    "F", "G", "H", "I", "J", "K", "L", "M", "N", "O", "P", "Q", "R", "S", "T", "U", "V", "W", "X",
+    "Y", "Z", "bracketleft", "backslash", "bracketright", "asciicircum", "underscore", "quoteleft",
+    "a", "b", "c", "d", "e", "f", "g", "h", "i", "j", "k", "l", "m", "n", "o", "p", "q", "r", "s",
+    "t", "u", "v", "w", "x", "y", "z", "braceleft", "bar", "braceright", "asciitilde", "exclamdown",
+    "cent", "sterling", "fraction", "yen", "florin", "section", "currency", "quotesingle",
+    "quotedblleft", "guillemotleft", "guilsinglleft", "guilsinglright", "fi", "fl", "endash",
+    "dagger", "daggerdbl", "periodcentered", "paragraph", "bullet", "quotesinglbase",
+    "quotedblbase", "quotedblright", "guillemotright", "ellipsis", "perthousand", "questiondown",
+    "grave", "acute", "circumflex", "tilde", "macron", "breve", "dotaccent", "dieresis", "ring",
+    "cedilla", "hungarumlaut", "ogonek", "caron", "emdash", "AE", "ordfeminine", "Lslash", "Oslash",
+    "OE", "ordmasculine", "ae", "dotlessi", "lslash", "oslash", "oe", "germandbls", "onesuperior",
+    "logicalnot", "mu", "trademark", "Eth", "onehalf", "plusminus", "Thorn", "onequarter", "divide",
+    "brokenbar", "degree", "thorn", "threequarters", "twosuperior", "registered", "minus", "eth",
+    "multiply", "threesuperior", "copyright", "Aacute", "Acircumflex", "Adieresis", "Agrave",
+    "Aring", "Atilde", "Ccedilla", "Eacute", "Ecircumflex", "Edieresis", "Egrave", "Iacute",
+    "Icircumflex", "Idieresis", "Igrave", "Ntilde", "Oacute", "Ocircumflex", "Odieresis", "Ograve",
+    "Otilde", "Scaron", "Uacute", "Ucircumflex", "Udieresis", "Ugrave", "Yacute", "Ydieresis",
+    "Zcaron", "aacute", "acircumflex", "adieresis", "agrave", "aring", "atilde", "ccedilla",
+    "eacute", "ecircumflex", "edieresis", "egrave", "iacute", "icircumflex", "idieresis", "igrave",
+    "ntilde", "oacute", "ocircumflex", "odieresis", "ograve", "otilde", "scaron", "uacute",
+    "ucircumflex", "udieresis", "ugrave", "yacute", "ydieresis", "zcaron", "exclamsmall",
+    "Hungarumlautsmall", "dollaroldstyle", "dollarsuperior", "ampersandsmall", "Acutesmall",
+    "parenleftsuperior", "parenrightsuperior", "twodotenleader", "onedotenleader", "zerooldstyle",
+    "oneoldstyle", "twooldstyle", "threeoldstyle", "fouroldstyle", "fiveoldstyle", "sixoldstyle",
+    "sevenoldstyle", "eightoldstyle", "nineoldstyle", "commasuperior", "threequartersemdash",
+    "periodsuperior", "questionsmall", "asuperior", "bsuperior", "centsuperior", "dsuperior",
+    "esuperior", "isuperior", "lsuperior", "msuperior", "nsuperior", "osuperior", "rsuperior",
+    "ssuperior", "tsuperior", "ff", "ffi", "ffl", "parenleftinferior", "parenrightinferior",
+    "Circumflexsmall", "hyphensuperior", "Gravesmall", "Asmall", "Bsmall", "Csmall", "Dsmall",
+    "Esmall", "Fsmall", "Gsmall", "Hsmall", "Ismall", "Jsmall", "Ksmall", "Lsmall", "Msmall",
+    "Nsmall", "Osmall", "Psmall", "Qsmall", "Rsmall", "Ssmall", "Tsmall", "Usmall", "Vsmall",
+    "Wsmall", "Xsmall", "Ysmall", "Zsmall", "colonmonetary", "onefitted", "rupiah", "Tildesmall",
+    "exclamdownsmall", "centoldstyle", "Lslashsmall", "Scaronsmall", "Zcaronsmall", "Dieresissmall",
+    "Brevesmall", "Caronsmall", "Dotaccentsmall", "Macronsmall", "figuredash", "hypheninferior",
+    "Ogoneksmall", "Ringsmall", "Cedillasmall", "questiondownsmall", "oneeighth", "threeeighths",
+    "fiveeighths", "seveneighths", "onethird", "twothirds", "zerosuperior", "foursuperior",
+    "fivesuperior", "sixsuperior", "sevensuperior", "eightsuperior", "ninesuperior", "zeroinferior",
+    "oneinferior", "twoinferior", "threeinferior", "fourinferior", "fiveinferior", "sixinferior",
+    "seveninferior", "eightinferior", "nineinferior", "centinferior", "dollarinferior",
+    "periodinferior", "commainferior", "Agravesmall", "Aacutesmall", "Acircumflexsmall",
+    "Atildesmall", "Adieresissmall", "Aringsmall", "AEsmall", "Ccedillasmall", "Egravesmall",
+    "Eacutesmall", "Ecircumflexsmall", "Edieresissmall", "Igravesmall", "Iacutesmall",
+    "Icircumflexsmall", "Idieresissmall", "Ethsmall", "Ntildesmall", "Ogravesmall", "Oacutesmall",
+    "Ocircumflexsmall", "Otildesmall", "Odieresissmall", "OEsmall", "Oslashsmall", "Ugravesmall",
+    "Uacutesmall", "Ucircumflexsmall", "Udieresissmall", "Yacutesmall", "Thornsmall",
+    "Ydieresissmall", "001.000", "001.001", "001.002", "001.003", "Black", "Bold", "Book", "Light",
+    "Medium", "Regular", "Roman", "Semibold",
+];
@@ -1,11 +1,43 @@
 //! Character to glyph mapping table.
 
-use super::{raw_tag, Array, Bytes, RawFont, RawTag, Stream};
+use super::{encoding, raw_tag, Array, Bytes, RawFont, RawTag, Stream, U24};
 
 pub const CMAP: RawTag = raw_tag(b"cmap");
 
-/// Finds a suitable character map subtable for the specified font.
-pub fn subtable<'a>(font: impl RawFont<'a>) -> Option<(u32, u8, bool)> {
+/// Codepoints are Unicode scalar values.
+pub const UNICODE: u8 = 0;
+/// Codepoints are in the Microsoft symbol encoding (usually U+F020..U+F0FF).
+pub const SYMBOL: u8 = 1;
+/// Codepoints are Mac OS Roman character codes.
+pub const MAC_ROMAN: u8 = 2;
+/// Codepoints are in a legacy, platform specific encoding.
+pub const LEGACY: u8 = 3;
+/// Codepoints are Windows Shift-JIS character codes (3, 2).
+pub const SHIFT_JIS: u8 = 4;
+/// Codepoints are Windows PRC (GBK) character codes (3, 3).
+pub const PRC: u8 = 5;
+/// Codepoints are Windows Big5 character codes (3, 4).
+pub const BIG5: u8 = 6;
+/// Codepoints are Windows Wansung (EUC-KR) character codes (3, 5).
+pub const WANSUNG: u8 = 7;
+/// Codepoints are Windows Johab character codes (3, 6).
+pub const JOHAB: u8 = 8;
+
+/// Finds a suitable character map subtable for the specified font and
+/// returns its offset, format and encoding.
+///
+/// Subtables are selected in the following order:
+/// - Microsoft symbol encoding (3, 0)
+/// - Unicode full repertoire (formats 12 and 10)
+/// - Unicode BMP (formats 4, 6, 0 and 2)
+/// - Unicode many-to-one "last resort" mappings (format 13)
+/// - Mac OS Roman (1, 0)
+/// - Any other platform specific encoding, including the Windows Shift-JIS,
+///   PRC, Big5, Wansung and Johab encodings (3, 2..=6)
+///
+/// Within each group, formats are preferred in the order listed and then by
+/// position in the table.
+pub fn subtable<'a>(font: impl RawFont<'a>) -> Option<(u32, u8, u8)> {
     let cmap = font.table_offset(CMAP);
     if cmap == 0 {
         return None;
@@ -14,27 +46,158 @@ pub fn subtable<'a>(font: impl RawFont<'a>) -> Option<(u32, u8, bool)> {
     s.skip(2)?;
     let len = s.read_u16()? as usize;
     let b = Bytes::new(s.data());
-    let mut best = None;
+    let mut best: Option<(u32, u8, u8)> = None;
+    let mut best_rank = 0;
     for _ in 0..len {
         let platform = s.read_u16()?;
         let encoding = s.read_u16()?;
         let offset = s.read_u32()?;
-        let format = b.read_u16(offset as usize)? as u8;
-        if format != 4 && format != 12 {
-            continue;
-        }
-        let offset = cmap.checked_add(offset)?;
-        if is_symbol(platform, encoding) {
-            return Some((offset, format, true));
-        } else if (format == 12 && is_unicode(platform, encoding)) || (best.is_none() && is_unicode(platform, encoding)) {
-            best = Some((offset, format, false));
+        let format = match b.read_u16(offset as usize) {
+            Some(format) => format as u8,
+            _ => continue,
+        };
+        let format_rank = match format {
+            12 | 4 => 6,
+            10 | 6 => 5,
+            0 => 4,
+            2 => 3,
+            13 => 2,
+            _ => continue,
+        };
+        let (group, kind) = if is_symbol(platform, encoding) {
+            (6, SYMBOL)
+        } else if is_unicode(platform, encoding) {
+            match format {
+                12 | 10 => (5, UNICODE),
+                13 => (3, UNICODE),
+                _ => (4, UNICODE),
+            }
+        } else if platform == 1 && encoding == 0 {
+            (2, MAC_ROMAN)
+        } else if platform == 3 && (2..=6).contains(&encoding) {
+            (1, SHIFT_JIS + (encoding - 2) as u8)
+        } else {
+            (1, LEGACY)
+        };
+        let rank = group * 8 + format_rank;
+        if rank > best_rank {
+            best_rank = rank;
+            best = Some((cmap.checked_add(offset)?, format, kind));
         }
     }
     best
 }
 
-/// Maps a codepoint to a glyph identifier.
-pub fn map(data: &[u8], subtable: u32, format: u8, codepoint: u32) -> Option<u16> {
+/// Finds the Unicode variation sequences subtable (format 14) for the
+/// specified font.
+pub fn variation_subtable<'a>(font: impl RawFont<'a>) -> Option<u32> {
+    let cmap = font.table_offset(CMAP);
+    if cmap == 0 {
+        return None;
+    }
+    let mut s = Stream::with_offset(font.data(), cmap as usize)?;
+    s.skip(2)?;
+    let len = s.read_u16()? as usize;
+    let b = Bytes::new(s.data());
+    for _ in 0..len {
+        let platform = s.read_u16()?;
+        let encoding = s.read_u16()?;
+        let offset = s.read_u32()?;
+        if platform == 0 && encoding == 5 && b.read_u16(offset as usize)? == 14 {
+            return cmap.checked_add(offset);
+        }
+    }
+    None
+}
+
+/// Result of mapping a Unicode variation sequence.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum MapVariant {
+    /// The sequence is supported and uses the nominal glyph of the base
+    /// character.
+    UseDefault,
+    /// The sequence is supported and maps to the specified glyph.
+    Variant(u16),
+}
+
+/// Maps a Unicode variation sequence using a format 14 subtable.
+pub fn map_variant(data: &[u8], subtable: u32, codepoint: u32, selector: u32) -> Option<MapVariant> {
+    if subtable == 0 {
+        return None;
+    }
+    let b = Bytes::with_offset(data, subtable as usize)?;
+    let len = b.read::<u32>(6)? as usize;
+    b.ensure_range(10, len * 11)?;
+    let mut l = 0;
+    let mut h = len;
+    while l < h {
+        use core::cmp::Ordering::*;
+        let i = (l + h) / 2;
+        let rec = 10 + i * 11;
+        let vs = b.read::<U24>(rec)?.0;
+        match selector.cmp(&vs) {
+            Less => h = i,
+            Greater => l = i + 1,
+            Equal => {
+                let default_uvs = b.read::<u32>(rec + 3)? as usize;
+                let non_default_uvs = b.read::<u32>(rec + 7)? as usize;
+                if default_uvs != 0 {
+                    let count = b.read::<u32>(default_uvs)? as usize;
+                    let found = search(count, |j| {
+                        let range = default_uvs + 4 + j * 4;
+                        let start = b.read::<U24>(range)?.0;
+                        let end = start + b.read::<u8>(range + 3)? as u32;
+                        Some(if codepoint < start {
+                            Less
+                        } else if codepoint > end {
+                            Greater
+                        } else {
+                            Equal
+                        })
+                    });
+                    if found.is_some() {
+                        return Some(MapVariant::UseDefault);
+                    }
+                }
+                if non_default_uvs != 0 {
+                    let count = b.read::<u32>(non_default_uvs)? as usize;
+                    let found = search(count, |j| {
+                        let value = b.read::<U24>(non_default_uvs + 4 + j * 5)?.0;
+                        Some(codepoint.cmp(&value))
+                    })?;
+                    let glyph_id = b.read::<u16>(non_default_uvs + 4 + found * 5 + 3)?;
+                    return Some(MapVariant::Variant(glyph_id));
+                }
+                return None;
+            }
+        }
+    }
+    None
+}
+
+/// Binary search over `len` sorted records using the specified comparison
+/// of the target against each record.
+fn search(
+    len: usize,
+    mut f: impl FnMut(usize) -> Option<core::cmp::Ordering>,
+) -> Option<usize> {
+    use core::cmp::Ordering::*;
+    let mut l = 0;
+    let mut h = len;
+    while l < h {
+        let i = (l + h) / 2;
+        match f(i)? {
+            Less => h = i,
+            Greater => l = i + 1,
+            Equal => return Some(i),
+        }
+    }
+    None
+}
+
+/// Maps a character code in the native encoding of a subtable to a glyph
+/// identifier.
+fn lookup(data: &[u8], subtable: u32, format: u8, codepoint: u32) -> Option<u16> {
     if subtable == 0 {
         return None;
     }
@@ -97,12 +260,184 @@ pub fn map(data: &[u8], subtable: u32, format: u8, codepoint: u32) -> Option<u16
                 return Some((codepoint - start + delta) as u16);
             }
         }
+    } else if format == 13 {
+        let base = 16;
+        let len = b.read::<u32>(base - 4).unwrap_or(0) as usize;
+        b.ensure_range(base, len * 12)?;
+        let mut l = 0;
+        let mut h = len;
+        while l < h {
+            let i = (l + h) / 2;
+            let rec = base + i * 12;
+            let start = unsafe { b.read_unchecked::<u32>(rec) };
+            if codepoint < start {
+                h = i;
+            } else if codepoint > unsafe { b.read_unchecked::<u32>(rec + 4) } {
+                l = i + 1;
+            } else {
+                return Some(unsafe { b.read_unchecked::<u32>(rec + 8) } as u16);
+            }
+        }
+    } else if format == 0 {
+        if codepoint < 256 {
+            return b.read::<u8>(6 + codepoint as usize).map(|id| id as u16);
+        }
+    } else if format == 6 {
+        let first = b.read::<u16>(6)? as u32;
+        let count = b.read::<u16>(8)? as u32;
+        if codepoint >= first && codepoint - first < count {
+            return b.read::<u16>(10 + (codepoint - first) as usize * 2);
+        }
+    } else if format == 10 {
+        let first = b.read::<u32>(12)?;
+        let count = b.read::<u32>(16)?;
+        if codepoint >= first && codepoint - first < count {
+            return b.read::<u16>(20 + (codepoint - first) as usize * 2);
+        }
+    } else if format == 2 {
+        let (high, low) = if codepoint < 256 {
+            (codepoint, codepoint)
+        } else if codepoint < 0x10000 {
+            (codepoint >> 8, codepoint & 0xFF)
+        } else {
+            return None;
+        };
+        let key = b.read::<u16>(6 + high as usize * 2)? as usize;
+        // Single byte codes must use the first subheader while double byte
+        // codes must begin with a lead byte.
+        if (key == 0) != (codepoint < 256) {
+            return None;
+        }
+        return format2_glyph(&b, key, low);
     }
     None
 }
 
-/// Enumerates all codepoint/glyph pairs in the table.
-pub fn enumerate(data: &[u8], subtable: u32, mut f: impl FnMut(u32, u16)) {
+/// Returns the glyph identifier for the low byte of a character code using
+/// the format 2 subheader at the specified key (offset from the subheader
+/// array).
+fn format2_glyph(b: &Bytes, key: usize, low: u32) -> Option<u16> {
+    let header = 6 + 512 + key;
+    let first = b.read::<u16>(header)? as u32;
+    let count = b.read::<u16>(header + 2)? as u32;
+    if low < first || low - first >= count {
+        return Some(0);
+    }
+    let delta = b.read::<i16>(header + 4)?;
+    let range_offset = b.read::<u16>(header + 6)? as usize;
+    let id = b.read::<u16>(header + 6 + range_offset + (low - first) as usize * 2)?;
+    if id == 0 {
+        Some(0)
+    } else {
+        Some((id as i32 + delta as i32) as u16)
+    }
+}
+
+/// Maps a codepoint to a glyph identifier using a subtable with the
+/// specified format and encoding.
+pub fn map(data: &[u8], subtable: u32, format: u8, encoding: u8, codepoint: u32) -> Option<u16> {
+    match encoding {
+        SYMBOL => {
+            // Symbol fonts usually map their characters into the private use
+            // area at U+F000 while text frequently contains the low codes.
+            let id = lookup(data, subtable, format, codepoint).unwrap_or(0);
+            if id != 0 {
+                Some(id)
+            } else if codepoint <= 0xFF {
+                lookup(data, subtable, format, codepoint + 0xF000)
+            } else if (0xF000..=0xF0FF).contains(&codepoint) {
+                lookup(data, subtable, format, codepoint - 0xF000)
+            } else {
+                Some(0)
+            }
+        }
+        MAC_ROMAN => {
+            let code = encoding::unicode_to_mac_roman(codepoint)?;
+            lookup(data, subtable, format, code as u32)
+        }
+        #[cfg(feature = "legacy-encodings")]
+        SHIFT_JIS..=JOHAB => {
+            let code = unicode_to_legacy(encoding, codepoint)?;
+            lookup(data, subtable, format, code)
+        }
+        // Other legacy encodings are only accessible by character code.
+        LEGACY..=JOHAB => None,
+        _ => lookup(data, subtable, format, codepoint),
+    }
+}
+
+/// Maps a character code in the native encoding of the subtable.
+pub fn map_code(data: &[u8], subtable: u32, format: u8, code: u32) -> Option<u16> {
+    lookup(data, subtable, format, code)
+}
+
+/// Enumerates all codepoint/glyph pairs in the table. Character codes in the
+/// Mac OS Roman encoding and, with the `legacy-encodings` feature, the
+/// Windows legacy encodings are converted to Unicode while subtables with
+/// other legacy encodings produce nothing.
+pub fn enumerate(data: &[u8], subtable: u32, encoding: u8, mut f: impl FnMut(u32, u16)) {
+    match encoding {
+        MAC_ROMAN => enumerate_codes(data, subtable, |code, id| {
+            if code < 256 {
+                f(encoding::mac_roman_to_unicode(code as u8), id);
+            }
+        }),
+        #[cfg(feature = "legacy-encodings")]
+        SHIFT_JIS..=JOHAB => enumerate_codes(data, subtable, |code, id| {
+            // Skip characters that cannot be mapped back, such as the Big5
+            // HKSCS extensions.
+            if let Some(codepoint) = legacy_to_unicode(encoding, code) {
+                if unicode_to_legacy(encoding, codepoint).is_some() {
+                    f(codepoint, id);
+                }
+            }
+        }),
+        LEGACY..=JOHAB => {}
+        _ => enumerate_codes(data, subtable, f),
+    }
+}
+
+/// Converts a Unicode codepoint to a character code in the specified
+/// Windows legacy encoding.
+#[cfg(feature = "legacy-encodings")]
+fn unicode_to_legacy(encoding: u8, codepoint: u32) -> Option<u32> {
+    let ch = core::char::from_u32(codepoint)?;
+    match encoding {
+        SHIFT_JIS => encoding::encode_double_byte(encoding_rs::SHIFT_JIS, ch),
+        PRC => encoding::encode_double_byte(encoding_rs::GBK, ch),
+        BIG5 => encoding::encode_double_byte(encoding_rs::BIG5, ch),
+        WANSUNG => encoding::encode_double_byte(encoding_rs::EUC_KR, ch),
+        JOHAB => encoding::encode_johab(ch),
+        _ => None,
+    }
+}
+
+/// Converts a character code in the specified Windows legacy encoding to a
+/// Unicode codepoint.
+#[cfg(feature = "legacy-encodings")]
+fn legacy_to_unicode(encoding: u8, code: u32) -> Option<u32> {
+    let bytes = [(code >> 8) as u8, code as u8];
+    let bytes = match code {
+        0..=0xFF => &bytes[1..],
+        0x100..=0xFFFF => &bytes[..],
+        _ => return None,
+    };
+    let (ch, len) = match encoding {
+        SHIFT_JIS => encoding::decode_double_byte(encoding_rs::SHIFT_JIS, bytes)?,
+        PRC => encoding::decode_double_byte(encoding_rs::GBK, bytes)?,
+        BIG5 => encoding::decode_double_byte(encoding_rs::BIG5, bytes)?,
+        WANSUNG => encoding::decode_double_byte(encoding_rs::EUC_KR, bytes)?,
+        JOHAB => encoding::decode_johab(bytes)?,
+        _ => return None,
+    };
+    if len != bytes.len() || ch == core::char::REPLACEMENT_CHARACTER {
+        return None;
+    }
+    Some(ch as u32)
+}
+
+/// Enumerates all character code/glyph pairs in the table.
+pub fn enumerate_codes(data: &[u8], subtable: u32, mut f: impl FnMut(u32, u16)) {
     if subtable == 0 {
         return;
     }
@@ -175,13 +510,93 @@ pub fn enumerate(data: &[u8], subtable: u32, mut f: impl FnMut(u32, u16)) {
                     b.read_unchecked::<u32>(rec + 8),
                 )
             };
-            for codepoint in start..=end {
-                let id = (offset + codepoint - start) as u16;
+            for_each_scalar(start, end, |codepoint| {
+                let id = offset.wrapping_add(codepoint - start) as u16;
                 if id != 0 {
                     f(codepoint, id);
                 }
+            });
+        }
+    } else if format == 13 {
+        let base = 16;
+        let len = b.read::<u32>(base - 4).unwrap_or(0) as usize;
+        if !b.check_range(base, len * 12) {
+            return;
+        }
+        for i in 0..len {
+            let rec = base + i * 12;
+            let (start, end, id) = unsafe {
+                (
+                    b.read_unchecked::<u32>(rec),
+                    b.read_unchecked::<u32>(rec + 4),
+                    b.read_unchecked::<u32>(rec + 8) as u16,
+                )
+            };
+            if id != 0 {
+                for_each_scalar(start, end, |codepoint| f(codepoint, id));
+            }
+        }
+    } else if format == 0 {
+        for code in 0..256 {
+            match b.read::<u8>(6 + code) {
+                Some(0) => {}
+                Some(id) => f(code as u32, id as u16),
+                None => break,
             }
         }
+    } else if format == 6 || format == 10 {
+        let (first, count, base) = if format == 6 {
+            (
+                b.read_or_default::<u16>(6) as u32,
+                b.read_or_default::<u16>(8) as u32,
+                10,
+            )
+        } else {
+            (b.read_or_default::<u32>(12), b.read_or_default::<u32>(16), 20)
+        };
+        let ids = b
+            .read_array::<u16>(base, count as usize)
+            .unwrap_or_else(|| Array::new(&[]));
+        for (i, id) in ids.iter().enumerate() {
+            if id != 0 {
+                f(first + i as u32, id);
+            }
+        }
+    } else if format == 2 {
+        for high in 0..256u32 {
+            let key = match b.read::<u16>(6 + high as usize * 2) {
+                Some(key) => key as usize,
+                None => return,
+            };
+            if key == 0 {
+                // Single byte code using the first subheader.
+                if let Some(id) = format2_glyph(&b, 0, high) {
+                    if id != 0 {
+                        f(high, id);
+                    }
+                }
+                continue;
+            }
+            for low in 0..256 {
+                if let Some(id) = format2_glyph(&b, key, low) {
+                    if id != 0 {
+                        f(high << 8 | low, id);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Invokes the closure with each Unicode scalar value in the specified
+/// range of codepoints.
+fn for_each_scalar(start: u32, end: u32, mut f: impl FnMut(u32)) {
+    let end = end.min(0x10FFFF);
+    for codepoint in start..=end.min(0xD7FF) {
+        f(codepoint);
+    }
+    for codepoint in start.max(0xE000)..=end {
+        f(codepoint);
     }
 }
 
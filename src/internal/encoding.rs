@@ -0,0 +1,350 @@
+//! Legacy character encodings.
+
+/// Unicode values for the upper half (0x80..=0xFF) of the Mac OS Roman
+/// encoding.
+#[rustfmt::skip]
+pub const MAC_ROMAN: [u16; 128] = [
+    196, 197, 199, 201, 209, 214, 220, 225, 224, 226, 228, 227, 229, 231, 233,
+    232, 234, 235, 237, 236, 238, 239, 241, 243, 242, 244, 246, 245, 250, 249,
+    251, 252, 8224, 176, 162, 163, 167, 8226, 182, 223, 174, 169, 8482, 180,
+    168, 8800, 198, 216, 8734, 177, 8804, 8805, 165, 181, 8706, 8721, 8719,
+    960, 8747, 170, 186, 937, 230, 248, 191, 161, 172, 8730, 402, 8776, 8710,
+    171, 187, 8230, 160, 192, 195, 213, 338, 339, 8211, 8212, 8220, 8221, 8216,
+    8217, 247, 9674, 255, 376, 8260, 8364, 8249, 8250, 64257, 64258, 8225, 183,
+    8218, 8222, 8240, 194, 202, 193, 203, 200, 205, 206, 207, 204, 211, 212,
+    63743, 210, 218, 219, 217, 305, 710, 732, 175, 728, 729, 730, 184, 733,
+    731, 711,
+];
+
+/// Converts a Mac OS Roman character code to a Unicode codepoint.
+pub fn mac_roman_to_unicode(code: u8) -> u32 {
+    if code < 0x80 {
+        code as u32
+    } else {
+        MAC_ROMAN[code as usize - 0x80] as u32
+    }
+}
+
+/// Converts a Unicode codepoint to a Mac OS Roman character code.
+pub fn unicode_to_mac_roman(codepoint: u32) -> Option<u8> {
+    if codepoint < 0x80 {
+        return Some(codepoint as u8);
+    }
+    MAC_ROMAN
+        .iter()
+        .position(|c| *c as u32 == codepoint)
+        .map(|i| (i + 0x80) as u8)
+}
+
+/// Unicode values for the upper half (0x80..=0xFF) of the Mac OS Cyrillic
+/// encoding.
+#[cfg(feature = "legacy-encodings")]
+#[rustfmt::skip]
+pub const MAC_CYRILLIC: [u16; 128] = [
+    1040, 1041, 1042, 1043, 1044, 1045, 1046, 1047, 1048, 1049, 1050, 1051,
+    1052, 1053, 1054, 1055, 1056, 1057, 1058, 1059, 1060, 1061, 1062, 1063,
+    1064, 1065, 1066, 1067, 1068, 1069, 1070, 1071, 8224, 176, 1168, 163, 167,
+    8226, 182, 1030, 174, 169, 8482, 1026, 1106, 8800, 1027, 1107, 8734, 177,
+    8804, 8805, 1110, 181, 1169, 1032, 1028, 1108, 1031, 1111, 1033, 1113,
+    1034, 1114, 1112, 1029, 172, 8730, 402, 8776, 8710, 171, 187, 8230, 160,
+    1035, 1115, 1036, 1116, 1109, 8211, 8212, 8220, 8221, 8216, 8217, 247,
+    8222, 1038, 1118, 1039, 1119, 8470, 1025, 1105, 1103, 1072, 1073, 1074,
+    1075, 1076, 1077, 1078, 1079, 1080, 1081, 1082, 1083, 1084, 1085, 1086,
+    1087, 1088, 1089, 1090, 1091, 1092, 1093, 1094, 1095, 1096, 1097, 1098,
+    1099, 1100, 1101, 1102, 8364,
+];
+
+/// Unicode values for the upper half (0x80..=0xFF) of the Mac OS Greek
+/// encoding.
+#[cfg(feature = "legacy-encodings")]
+#[rustfmt::skip]
+pub const MAC_GREEK: [u16; 128] = [
+    196, 185, 178, 201, 179, 214, 220, 901, 224, 226, 228, 900, 168, 231, 233,
+    232, 234, 235, 163, 8482, 238, 239, 8226, 189, 8240, 244, 246, 166, 8364,
+    249, 251, 252, 8224, 915, 916, 920, 923, 926, 928, 223, 174, 169, 931,
+    938, 167, 8800, 176, 183, 913, 177, 8804, 8805, 165, 914, 917, 918, 919,
+    921, 922, 924, 934, 939, 936, 937, 940, 925, 172, 927, 929, 8776, 932,
+    171, 187, 8230, 160, 933, 935, 902, 904, 339, 8211, 8213, 8220, 8221,
+    8216, 8217, 247, 905, 906, 908, 910, 941, 942, 943, 972, 911, 973, 945,
+    946, 968, 948, 949, 966, 947, 951, 953, 958, 954, 955, 956, 957, 959, 960,
+    974, 961, 963, 964, 952, 969, 962, 967, 965, 950, 970, 971, 912, 944, 173,
+];
+
+/// Unicode values for the upper half (0x80..=0xFF) of the Mac OS Central European
+/// encoding.
+#[cfg(feature = "legacy-encodings")]
+#[rustfmt::skip]
+pub const MAC_CENTRAL_EUROPEAN: [u16; 128] = [
+    196, 256, 257, 201, 260, 214, 220, 225, 261, 268, 228, 269, 262, 263, 233,
+    377, 378, 270, 237, 271, 274, 275, 278, 243, 279, 244, 246, 245, 250, 282,
+    283, 252, 8224, 176, 280, 163, 167, 8226, 182, 223, 174, 169, 8482, 281,
+    168, 8800, 291, 302, 303, 298, 8804, 8805, 299, 310, 8706, 8721, 322, 315,
+    316, 317, 318, 313, 314, 325, 326, 323, 172, 8730, 324, 327, 8710, 171,
+    187, 8230, 160, 328, 336, 213, 337, 332, 8211, 8212, 8220, 8221, 8216,
+    8217, 247, 9674, 333, 340, 341, 344, 8249, 8250, 345, 342, 343, 352, 8218,
+    8222, 353, 346, 347, 193, 356, 357, 205, 381, 382, 362, 211, 212, 363,
+    366, 218, 367, 368, 369, 370, 371, 221, 253, 311, 379, 321, 380, 290, 711,
+];
+
+/// Unicode values for the upper half (0x80..=0xFF) of the Mac OS Arabic
+/// encoding.
+#[cfg(feature = "legacy-encodings")]
+#[rustfmt::skip]
+pub const MAC_ARABIC: [u16; 128] = [
+    196, 160, 199, 201, 209, 214, 220, 225, 224, 226, 228, 1722, 171, 231,
+    233, 232, 234, 235, 237, 8230, 238, 239, 241, 243, 187, 244, 246, 247,
+    250, 249, 251, 252, 32, 33, 34, 35, 36, 1642, 38, 39, 40, 41, 42, 43,
+    1548, 45, 46, 47, 1632, 1633, 1634, 1635, 1636, 1637, 1638, 1639, 1640,
+    1641, 58, 1563, 60, 61, 62, 1567, 10058, 1569, 1570, 1571, 1572, 1573,
+    1574, 1575, 1576, 1577, 1578, 1579, 1580, 1581, 1582, 1583, 1584, 1585,
+    1586, 1587, 1588, 1589, 1590, 1591, 1592, 1593, 1594, 91, 92, 93, 94, 95,
+    1600, 1601, 1602, 1603, 1604, 1605, 1606, 1607, 1608, 1609, 1610, 1611,
+    1612, 1613, 1614, 1615, 1616, 1617, 1618, 1662, 1657, 1670, 1749, 1700,
+    1711, 1672, 1681, 123, 124, 125, 1688, 1746,
+];
+
+/// Unicode values for the upper half (0x80..=0xFF) of the Mac OS Hebrew
+/// encoding.
+#[cfg(feature = "legacy-encodings")]
+#[rustfmt::skip]
+pub const MAC_HEBREW: [u16; 128] = [
+    196, 64287, 199, 201, 209, 214, 220, 225, 224, 226, 228, 227, 229, 231,
+    233, 232, 234, 235, 237, 236, 238, 239, 241, 243, 242, 244, 246, 245, 250,
+    249, 251, 252, 32, 33, 34, 35, 36, 37, 8362, 39, 40, 41, 42, 43, 44, 45,
+    46, 47, 48, 49, 50, 51, 52, 53, 54, 55, 56, 57, 58, 59, 60, 61, 62, 63,
+    65533, 8222, 63643, 63644, 63645, 63646, 1468, 64331, 64309, 8230, 160,
+    1464, 1463, 1461, 1462, 1460, 8211, 8212, 8220, 8221, 8216, 8217, 64298,
+    64299, 1471, 1456, 1458, 1457, 1467, 1465, 65533, 1459, 1488, 1489, 1490,
+    1491, 1492, 1493, 1494, 1495, 1496, 1497, 1498, 1499, 1500, 1501, 1502,
+    1503, 1504, 1505, 1506, 1507, 1508, 1509, 1510, 1511, 1512, 1513, 1514,
+    125, 93, 123, 91, 124,
+];
+
+/// Decodes the character at the start of the specified bytes in a legacy
+/// double byte encoding (Shift-JIS, GBK, Big5 or EUC-KR), returning the
+/// character and the number of bytes consumed.
+#[cfg(feature = "legacy-encodings")]
+pub fn decode_double_byte(
+    encoding: &'static encoding_rs::Encoding,
+    bytes: &[u8],
+) -> Option<(char, usize)> {
+    let lead = *bytes.first()?;
+    let len = if lead < 0x80 {
+        1
+    } else if encoding == encoding_rs::SHIFT_JIS {
+        match lead {
+            0x81..=0x9F | 0xE0..=0xFC => 2,
+            _ => 1,
+        }
+    } else if lead == 0x80 || lead == 0xFF {
+        1
+    } else if encoding == encoding_rs::GBK
+        && bytes.get(1).map(|b| (0x30..=0x39).contains(b)) == Some(true)
+    {
+        // Four byte GB18030 sequence.
+        4
+    } else {
+        2
+    };
+    let len = len.min(bytes.len());
+    let mut decoder = encoding.new_decoder_without_bom_handling();
+    let mut buf = [0u8; 16];
+    let (_, _, written, _) = decoder.decode_to_utf8(&bytes[..len], &mut buf, true);
+    let mut chars = core::str::from_utf8(&buf[..written]).ok()?.chars();
+    let ch = chars.next().unwrap_or(REPLACEMENT);
+    if ch == REPLACEMENT && chars.next().is_some() {
+        // An invalid trail byte that is reprocessed by the decoder as a
+        // character in its own right.
+        return Some((ch, 1));
+    }
+    Some((ch, len))
+}
+
+/// Decodes the character at the start of the specified bytes in the Johab
+/// encoding, returning the character and the number of bytes consumed.
+#[cfg(feature = "legacy-encodings")]
+pub fn decode_johab(bytes: &[u8]) -> Option<(char, usize)> {
+    let lead = *bytes.first()?;
+    if lead < 0x80 {
+        return Some((lead as char, 1));
+    }
+    let trail = match bytes.get(1) {
+        Some(trail) => *trail,
+        _ => return Some((REPLACEMENT, 1)),
+    };
+    match lead {
+        0x84..=0xD3 => {
+            // Hangul: 5 bits each for the initial, medial and final jamo.
+            let code = (lead as u32) << 8 | trail as u32;
+            let ch = johab_hangul((code >> 10) & 0x1F, (code >> 5) & 0x1F, code & 0x1F);
+            Some((ch.unwrap_or(REPLACEMENT), 2))
+        }
+        0xD9..=0xDE | 0xE0..=0xF9 => {
+            // Symbols and Hanja: convert to a KS X 1001 code and decode
+            // with EUC-KR.
+            let t1 = if lead < 0xE0 {
+                2 * (lead as u32 - 0xD9)
+            } else {
+                2 * lead as u32 - 0x197
+            };
+            let t2 = match trail {
+                0x31..=0x7E => trail as u32 - 0x31,
+                0x91..=0xFE => trail as u32 - 0x43,
+                _ => return Some((REPLACEMENT, 1)),
+            };
+            let (row, col) = if t2 < 0x5E {
+                (t1 + 0x21, t2 + 0x21)
+            } else {
+                (t1 + 0x22, t2 - 0x5E + 0x21)
+            };
+            let euc = [(row | 0x80) as u8, (col | 0x80) as u8];
+            let (ch, _) = decode_double_byte(encoding_rs::EUC_KR, &euc)?;
+            Some((ch, 2))
+        }
+        _ => Some((REPLACEMENT, 2)),
+    }
+}
+
+/// Encodes a character in a legacy double byte encoding (Shift-JIS, GBK,
+/// Big5 or EUC-KR), returning the character code with any lead byte in the
+/// high byte.
+#[cfg(feature = "legacy-encodings")]
+pub fn encode_double_byte(encoding: &'static encoding_rs::Encoding, ch: char) -> Option<u32> {
+    let mut encoder = encoding.new_encoder();
+    let mut utf8 = [0u8; 4];
+    let mut buf = [0u8; 8];
+    let (result, _, written) =
+        encoder.encode_from_utf8_without_replacement(ch.encode_utf8(&mut utf8), &mut buf, true);
+    if result != encoding_rs::EncoderResult::InputEmpty {
+        return None;
+    }
+    match written {
+        1 => Some(buf[0] as u32),
+        2 => Some((buf[0] as u32) << 8 | buf[1] as u32),
+        _ => None,
+    }
+}
+
+/// Encodes a character in the Johab encoding, returning the character code
+/// with any lead byte in the high byte.
+#[cfg(feature = "legacy-encodings")]
+pub fn encode_johab(ch: char) -> Option<u32> {
+    const FILL_INITIAL: u32 = 1;
+    const FILL_MEDIAL: u32 = 2;
+    const FILL_FINAL: u32 = 1;
+    let cp = ch as u32;
+    if cp < 0x80 {
+        return Some(cp);
+    }
+    let hangul = |initial: u32, medial: u32, final_: u32| {
+        Some(0x8000 | initial << 10 | medial << 5 | final_)
+    };
+    // Inverse of the jamo index mappings in johab_hangul.
+    let initial = |i: u32| i + 2;
+    let medial = |m: u32| match m {
+        0..=4 => m + 3,
+        5..=10 => m + 5,
+        11..=16 => m + 7,
+        _ => m + 9,
+    };
+    let final_ = |f: u32| match f {
+        0 => FILL_FINAL,
+        1..=16 => f + 1,
+        _ => f + 2,
+    };
+    match cp {
+        0xAC00..=0xD7A3 => {
+            let s = cp - 0xAC00;
+            return hangul(initial(s / 588), medial(s / 28 % 21), final_(s % 28));
+        }
+        0x3164 => return hangul(FILL_INITIAL, FILL_MEDIAL, FILL_FINAL),
+        0x314F..=0x3163 => return hangul(FILL_INITIAL, medial(cp - 0x314F), FILL_FINAL),
+        _ => {}
+    }
+    if let Some(i) = JOHAB_INITIALS.iter().position(|&j| j as u32 == cp) {
+        return hangul(initial(i as u32), FILL_MEDIAL, FILL_FINAL);
+    }
+    if let Some(f) = JOHAB_FINALS.iter().position(|&j| j as u32 == cp) {
+        return hangul(FILL_INITIAL, FILL_MEDIAL, final_(f as u32 + 1));
+    }
+    // Symbols and Hanja: convert the KS X 1001 code from EUC-KR. Symbol
+    // rows are paired from an even offset and Hanja rows from an odd
+    // offset.
+    let euc = encode_double_byte(encoding_rs::EUC_KR, ch)?;
+    let (row, col) = (euc >> 8, euc & 0xFF);
+    if !(0xA1..=0xFE).contains(&row) || !(0xA1..=0xFE).contains(&col) {
+        return None;
+    }
+    let (row, col) = (row - 0xA1, col - 0xA1);
+    let hanja = match row {
+        0x00..=0x0B => 0,
+        0x29..=0x5C => 1,
+        _ => return None,
+    };
+    let second = (row + hanja) & 1;
+    let t1 = row - second;
+    let t2 = col + second * 0x5E;
+    let lead = if hanja == 0 {
+        0xD9 + t1 / 2
+    } else {
+        (t1 + 0x197) / 2
+    };
+    let trail = if t2 < 0x4E { t2 + 0x31 } else { t2 + 0x43 };
+    Some(lead << 8 | trail)
+}
+
+/// Composes a Hangul syllable or compatibility jamo from the Johab jamo
+/// indices.
+#[cfg(feature = "legacy-encodings")]
+fn johab_hangul(initial: u32, medial: u32, final_: u32) -> Option<char> {
+    const FILL: u32 = 0xFF;
+    let initial = match initial {
+        1 => FILL,
+        2..=20 => initial - 2,
+        _ => return None,
+    };
+    let medial = match medial {
+        2 => FILL,
+        3..=7 => medial - 3,
+        10..=15 => medial - 5,
+        18..=23 => medial - 7,
+        26..=29 => medial - 9,
+        _ => return None,
+    };
+    let final_ = match final_ {
+        1 => 0,
+        2..=17 => final_ - 1,
+        19..=29 => final_ - 2,
+        _ => return None,
+    };
+    let cp = match (initial, medial, final_) {
+        (FILL, FILL, 0) => 0x3164,
+        (FILL, FILL, f) => JOHAB_FINALS[f as usize - 1] as u32,
+        (i, FILL, 0) => JOHAB_INITIALS[i as usize] as u32,
+        (FILL, m, 0) => 0x314F + m,
+        (FILL, _, _) | (_, FILL, _) => return None,
+        (i, m, f) => 0xAC00 + (i * 21 + m) * 28 + f,
+    };
+    core::char::from_u32(cp)
+}
+
+/// Compatibility jamo for the initial consonants.
+#[cfg(feature = "legacy-encodings")]
+#[rustfmt::skip]
+const JOHAB_INITIALS: [u16; 19] = [
+    0x3131, 0x3132, 0x3134, 0x3137, 0x3138, 0x3139, 0x3141, 0x3142, 0x3143,
+    0x3145, 0x3146, 0x3147, 0x3148, 0x3149, 0x314A, 0x314B, 0x314C, 0x314D,
+    0x314E,
+];
+
+/// Compatibility jamo for the final consonants.
+#[cfg(feature = "legacy-encodings")]
+#[rustfmt::skip]
+const JOHAB_FINALS: [u16; 27] = [
+    0x3131, 0x3132, 0x3133, 0x3134, 0x3135, 0x3136, 0x3137, 0x3139, 0x313A,
+    0x313B, 0x313C, 0x313D, 0x313E, 0x313F, 0x3140, 0x3141, 0x3142, 0x3144,
+    0x3145, 0x3146, 0x3147, 0x3148, 0x314A, 0x314B, 0x314C, 0x314D, 0x314E,
+];
+
+#[cfg(feature = "legacy-encodings")]
+const REPLACEMENT: char = core::char::REPLACEMENT_CHARACTER;
@@ -1,6 +1,6 @@
 //! Font header tables.
 
-use super::{raw_tag, RawTag, RawFont, Bytes};
+use super::{fixed::Fixed, raw_tag, Bytes, RawFont, RawTag};
 
 pub const HEAD: RawTag = raw_tag(b"head");
 pub const OS_2: RawTag = raw_tag(b"OS/2");
@@ -17,6 +17,9 @@ impl<'a> Head<'a> {
     /// The expected value of the 'magic' field in the header table.
     pub const MAGIC: u32 = 0x5F0F3CF5;
 
+    /// Number of seconds between the font epoch (1904) and the Unix epoch.
+    const UNIX_EPOCH: i64 = 2_082_844_800;
+
     /// Creates a font header table wrapping the specified data.
     pub fn new(data: &'a [u8]) -> Self {
         Self(Bytes::new(data))
@@ -88,6 +91,18 @@ impl<'a> Head<'a> {
         self.0.read(28).unwrap_or(0)
     }
 
+    /// Returns the creation time as the number of seconds since the Unix
+    /// epoch (January 1st 1970 in UTC).
+    pub fn created_unix_time(&self) -> i64 {
+        self.created() as i64 - Self::UNIX_EPOCH
+    }
+
+    /// Returns the modification time as the number of seconds since the Unix
+    /// epoch (January 1st 1970 in UTC).
+    pub fn modified_unix_time(&self) -> i64 {
+        self.modified() as i64 - Self::UNIX_EPOCH
+    }
+
     /// Returns the union of all glyph bounding boxes.
     pub fn bounds(&self) -> [(i16, i16); 2] {
         [
@@ -111,8 +126,8 @@ impl<'a> Head<'a> {
     /// - 5: Condensed
     /// - 6: Extended
     /// - 7-15: Reserved
-    pub fn mac_style(&self) -> u16 {
-        self.0.read(44).unwrap_or(0)
+    pub fn mac_style(&self) -> MacStyle {
+        MacStyle(self.0.read(44).unwrap_or(0))
     }
 
     /// Returns the smallest readable size in pixels.
@@ -212,6 +227,11 @@ impl<'a> Os2<'a> {
         self.0.read(8).unwrap_or(0)
     }
 
+    /// Returns the embedding permissions and restrictions of the font.
+    pub fn embedding(&self) -> Embedding {
+        Embedding(self.type_flags() as u16)
+    }
+
     /// Returns a rectangle describing suggested subscript positioning.
     pub fn subscript(&self) -> [(i32, i32); 2] {
         [
@@ -259,10 +279,12 @@ impl<'a> Os2<'a> {
 
     /// Returns a 10-byte PANOSE classification number.
     /// <https://monotype.github.io/panose/>
-    pub fn panose(&self) -> &'a [u8] {
-        self.0
-            .read_bytes(32, 10)
-            .unwrap_or(&[0, 0, 0, 0, 0, 0, 0, 0, 0, 0])
+    pub fn panose(&self) -> Panose {
+        let mut bytes = [0; 10];
+        if let Some(data) = self.0.read_bytes(32, 10) {
+            bytes.copy_from_slice(data);
+        }
+        Panose(bytes)
     }
 
     /// Returns a 128-bit value describing the Unicode blocks that are
@@ -299,8 +321,8 @@ impl<'a> Os2<'a> {
     /// - 8: WWS (Weight/Width/Slope names)
     /// - 9: Oblique
     /// - 10-15: Reserved (set to 0)
-    pub fn selection_flags(&self) -> Flags {
-        Flags(self.0.read(62).unwrap_or(0))
+    pub fn selection_flags(&self) -> SelectionFlags {
+        SelectionFlags(self.0.read(62).unwrap_or(0))
     }
 
     /// Returns the minimum and maximum Unicode codepoints supported by the
@@ -417,10 +439,10 @@ impl<'a> Os2<'a> {
 }
 
 /// OS/2 selection flags.
-#[derive(Copy, Clone)]
-pub struct Flags(pub u16);
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct SelectionFlags(pub u16);
 
-impl Flags {
+impl SelectionFlags {
     /// Font contains italic glyphs.
     pub fn italic(self) -> bool {
         self.bit(0)
@@ -476,6 +498,156 @@ impl Flags {
     }
 }
 
+/// Font header style flags.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct MacStyle(pub u16);
+
+impl MacStyle {
+    /// Glyphs are emboldened.
+    pub fn bold(self) -> bool {
+        self.bit(0)
+    }
+
+    /// Font contains italic glyphs.
+    pub fn italic(self) -> bool {
+        self.bit(1)
+    }
+
+    /// Glyphs are underlined.
+    pub fn underline(self) -> bool {
+        self.bit(2)
+    }
+
+    /// Hollow glyphs.
+    pub fn outline(self) -> bool {
+        self.bit(3)
+    }
+
+    /// Glyphs are shadowed.
+    pub fn shadow(self) -> bool {
+        self.bit(4)
+    }
+
+    /// Glyphs are condensed.
+    pub fn condensed(self) -> bool {
+        self.bit(5)
+    }
+
+    /// Glyphs are extended.
+    pub fn extended(self) -> bool {
+        self.bit(6)
+    }
+
+    fn bit(self, bit: u16) -> bool {
+        self.0 & 1 << bit != 0
+    }
+}
+
+/// Usage permissions for embedding a font in documents.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Permissions {
+    /// The font may be embedded and permanently installed on the remote
+    /// system.
+    Installable,
+    /// The font must not be embedded without explicit permission from the
+    /// legal owner.
+    Restricted,
+    /// The font may be embedded in documents that are opened read-only.
+    PreviewAndPrint,
+    /// The font may be embedded in documents that are opened for editing.
+    Editable,
+}
+
+/// OS/2 embedding flags.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct Embedding(pub u16);
+
+impl Embedding {
+    /// Returns the usage permissions. If more than one permission bit is
+    /// set, as allowed by table versions prior to 3, the least restrictive
+    /// permission is returned.
+    pub fn permissions(self) -> Permissions {
+        if self.0 & 8 != 0 {
+            Permissions::Editable
+        } else if self.0 & 4 != 0 {
+            Permissions::PreviewAndPrint
+        } else if self.0 & 2 != 0 {
+            Permissions::Restricted
+        } else {
+            Permissions::Installable
+        }
+    }
+
+    /// The font must not be subsetted prior to embedding.
+    pub fn no_subsetting(self) -> bool {
+        self.0 & 0x100 != 0
+    }
+
+    /// Only bitmaps contained in the font may be embedded.
+    pub fn bitmap_only(self) -> bool {
+        self.0 & 0x200 != 0
+    }
+}
+
+/// PANOSE classification number.
+///
+/// The interpretation of all digits after the first depends on the family
+/// kind. The names of the accessors follow the Latin text classification.
+#[derive(Copy, Clone, PartialEq, Eq, Default, Debug)]
+pub struct Panose(pub [u8; 10]);
+
+impl Panose {
+    /// Returns the family kind.
+    pub fn family_kind(self) -> u8 {
+        self.0[0]
+    }
+
+    /// Returns the serif style.
+    pub fn serif_style(self) -> u8 {
+        self.0[1]
+    }
+
+    /// Returns the weight.
+    pub fn weight(self) -> u8 {
+        self.0[2]
+    }
+
+    /// Returns the proportion.
+    pub fn proportion(self) -> u8 {
+        self.0[3]
+    }
+
+    /// Returns the contrast.
+    pub fn contrast(self) -> u8 {
+        self.0[4]
+    }
+
+    /// Returns the stroke variation.
+    pub fn stroke_variation(self) -> u8 {
+        self.0[5]
+    }
+
+    /// Returns the arm style.
+    pub fn arm_style(self) -> u8 {
+        self.0[6]
+    }
+
+    /// Returns the letterform.
+    pub fn letterform(self) -> u8 {
+        self.0[7]
+    }
+
+    /// Returns the midline.
+    pub fn midline(self) -> u8 {
+        self.0[8]
+    }
+
+    /// Returns the x-height.
+    pub fn x_height(self) -> u8 {
+        self.0[9]
+    }
+}
+
 /// PostScript table.
 #[derive(Copy, Clone)]
 pub struct Post<'a>(Bytes<'a>);
@@ -503,8 +675,8 @@ impl<'a> Post<'a> {
     }
 
     /// Returns the italic angle in counter-clockwise degrees from the vertical.
-    pub fn italic_angle(&self) -> u32 {
-        self.0.read(4).unwrap_or(0)
+    pub fn italic_angle(&self) -> f32 {
+        Fixed(self.0.read(4).unwrap_or(0)).to_f32()
     }
 
     /// Returns the suggested position of the top of the underline stroke from
@@ -522,6 +694,74 @@ impl<'a> Post<'a> {
     pub fn is_fixed_pitch(&self) -> bool {
         self.0.read::<u32>(12).unwrap_or(0) != 0
     }
+
+    /// Returns true if the table contains glyph names.
+    pub fn has_names(&self) -> bool {
+        let v = self.version();
+        v == 0x10000 || v == 0x20000
+    }
+
+    /// Returns the name of the specified glyph.
+    pub fn name(&self, glyph_id: u16) -> Option<&'a str> {
+        match self.version() {
+            0x10000 => MAC_GLYPH_NAMES.get(glyph_id as usize).copied(),
+            0x20000 => {
+                let b = &self.0;
+                let count = b.read::<u16>(32)?;
+                if glyph_id >= count {
+                    return None;
+                }
+                let index = b.read::<u16>(34 + glyph_id as usize * 2)? as usize;
+                if index < 258 {
+                    return MAC_GLYPH_NAMES.get(index).copied();
+                }
+                let mut s = b.stream_at(34 + count as usize * 2)?;
+                for _ in 258..index {
+                    let len = s.read::<u8>()? as usize;
+                    s.skip(len)?;
+                }
+                let len = s.read::<u8>()? as usize;
+                core::str::from_utf8(s.read_bytes(len)?).ok()
+            }
+            _ => None,
+        }
+    }
+
+    /// Returns the identifier of the first glyph with the specified name.
+    pub fn glyph_by_name(&self, name: &str) -> Option<u16> {
+        match self.version() {
+            0x10000 => MAC_GLYPH_NAMES
+                .iter()
+                .position(|n| *n == name)
+                .map(|i| i as u16),
+            0x20000 => {
+                let b = &self.0;
+                let count = b.read::<u16>(32)? as usize;
+                let indices = b.read_array::<u16>(34, count)?;
+                // Resolve the name to a string index first so that the
+                // strings are scanned only once.
+                let index = match MAC_GLYPH_NAMES.iter().position(|n| *n == name) {
+                    Some(index) => index,
+                    None => {
+                        let mut s = b.stream_at(34 + count * 2)?;
+                        let mut index = 258;
+                        loop {
+                            let len = s.read::<u8>()? as usize;
+                            if s.read_bytes(len)? == name.as_bytes() {
+                                break index;
+                            }
+                            index += 1;
+                        }
+                    }
+                };
+                indices
+                    .iter()
+                    .position(|i| i as usize == index)
+                    .map(|i| i as u16)
+            }
+            _ => None,
+        }
+    }
 }
 
 /// Maximum profile table.
@@ -808,3 +1048,36 @@ impl<'a> Vhea<'a> {
         self.0.read(34).unwrap_or(0)
     }
 }
+
+/// Standard Macintosh glyph names used by `post` table versions 1.0 and 2.0.
+pub const MAC_GLYPH_NAMES: [&str; 258] = [
+    ".notdef", ".null", "nonmarkingreturn", "space", "exclam", "quotedbl", "numbersign", "dollar",
+    "percent", "ampersand", "quotesingle", "parenleft", "parenright", "asterisk", "plus", "comma",
+    "hyphen", "period", "slash", "zero", "one", "two", "three", "four", "five", "six", "seven",
+    "eight", "nine", "colon", "semicolon", "less", "equal", "greater", "question", "at", "A", "B",
+    "C", "D", "E", "F", "G", "H", "I", "J", "K", "L", "M", "N", "O", "P", "Q", "R", "S", "T", "U",
+    "V", "W", "X", "Y", "Z", "bracketleft", "backslash", "bracketright", "asciicircum",
+    "underscore", "grave", "a", "b", "c", "d", "e", "f", "g", "h", "i", "j", "k", "l", "m", "n",
+    "o", "p", "q", "r", "s", "t", "u", "v", "w", "x", "y", "z", "braceleft", "bar", "braceright",
+    "asciitilde", "Adieresis", "Aring", "Ccedilla", "Eacute", "Ntilde", "Odieresis", "Udieresis",
+    "aacute", "agrave", "acircumflex", "adieresis", "atilde", "aring", "ccedilla", "eacute",
+    "egrave", "ecircumflex", "edieresis", "iacute", "igrave", "icircumflex", "idieresis", "ntilde",
+    "oacute", "ograve", "ocircumflex", "odieresis", "otilde", "uacute", "ugrave", "ucircumflex",
+    "udieresis", "dagger", "degree", "cent", "sterling", "section", "bullet", "paragraph",
+    "germandbls", "registered", "copyright", "trademark", "acute", "dieresis", "notequal", "AE",
+    "Oslash", "infinity", "plusminus", "lessequal", "greaterequal", "yen", "mu", "partialdiff",
+    "summation", "product", "pi", "integral", "ordfeminine", "ordmasculine", "Omega", "ae",
+    "oslash", "questiondown", "exclamdown", "logicalnot", "radical", "florin", "approxequal",
+    "Delta", "guillemotleft", "guillemotright", "ellipsis", "nonbreakingspace", "Agrave", "Atilde",
+    "Otilde", "OE", "oe", "endash", "emdash", "quotedblleft", "quotedblright", "quoteleft",
+    "quoteright", "divide", "lozenge", "ydieresis", "Ydieresis", "fraction", "currency",
+    "guilsinglleft", "guilsinglright", "fi", "fl", "daggerdbl", "periodcentered", "quotesinglbase",
+    "quotedblbase", "perthousand", "Acircumflex", "Ecircumflex", "Aacute", "Edieresis", "Egrave",
+    "Iacute", "Icircumflex", "Idieresis", "Igrave", "Oacute", "Ocircumflex", "apple", "Ograve",
+    "Uacute", "Ucircumflex", "Ugrave", "dotlessi", "circumflex", "tilde", "macron", "breve",
+    "dotaccent", "ring", "cedilla", "hungarumlaut", "ogonek", "caron", "Lslash", "lslash", "Scaron",
+    "scaron", "Zcaron", "zcaron", "brokenbar", "Eth", "eth", "Yacute", "yacute", "Thorn", "thorn",
+    "minus", "multiply", "onesuperior", "twosuperior", "threesuperior", "onehalf", "onequarter",
+    "threequarters", "franc", "Gbreve", "gbreve", "Idotaccent", "Scedilla", "scedilla", "Cacute",
+    "cacute", "Ccaron", "ccaron", "dcroat",
+];
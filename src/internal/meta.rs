@@ -0,0 +1,22 @@
+//! Metadata table.
+
+use super::{raw_tag, Bytes, RawTag};
+
+pub const META: RawTag = raw_tag(b"meta");
+pub const DLNG: RawTag = raw_tag(b"dlng");
+pub const SLNG: RawTag = raw_tag(b"slng");
+
+/// Returns the data for the metadata entry with the specified tag.
+pub fn entry(data: &[u8], tag: RawTag) -> Option<&[u8]> {
+    let b = Bytes::new(data);
+    let count = b.read::<u32>(12)? as usize;
+    for i in 0..count {
+        let offset = 16 + i * 12;
+        if b.read::<u32>(offset)? == tag {
+            let start = b.read::<u32>(offset + 4)? as usize;
+            let len = b.read::<u32>(offset + 8)? as usize;
+            return b.read_bytes(start, len);
+        }
+    }
+    None
+}
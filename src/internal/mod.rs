@@ -6,9 +6,13 @@ pub mod fixed;
 
 pub mod aat;
 pub mod at;
+pub mod cff;
 pub mod cmap;
+pub mod encoding;
 pub mod glyf;
 pub mod head;
+pub mod meta;
+pub mod stat;
 pub mod var;
 pub mod vorg;
 pub mod xmtx;
@@ -34,6 +38,9 @@ pub mod raw_data {
     const OTTO: RawTag = raw_tag(b"OTTO");
     const TTCF: RawTag = raw_tag(b"ttcf");
     const FONT: RawTag = 0x10000;
+    /// Version used by TrueType fonts for Apple platforms, commonly found in
+    /// resource forks.
+    const TRUE: RawTag = raw_tag(b"true");
 
     /// Returns true if the data represents a font collection.
     pub fn is_collection(data: &[u8]) -> bool {
@@ -43,7 +50,7 @@ pub mod raw_data {
     /// Returns true if the data represents a font at the specified offset.
     pub fn is_font(data: &[u8], offset: u32) -> bool {
         let tag = Bytes::new(data).read_u32(offset as usize).unwrap_or(0);
-        tag == FONT || tag == OTTO
+        tag == FONT || tag == OTTO || tag == TRUE
     }
 
     /// Returns the number of fonts contained in the specified data.
@@ -53,7 +60,7 @@ pub mod raw_data {
         } else if is_font(data, 0) {
             1
         } else {
-            0
+            resource_count(data)
         }
     }
 
@@ -64,9 +71,123 @@ pub mod raw_data {
         }
         if is_font(data, 0) {
             Some(0)
-        } else {
+        } else if is_collection(data) {
             Bytes::new(data).read_u32(12 + index as usize * 4)
+        } else {
+            None
+        }
+    }
+
+    /// Returns the data and the offset to the table directory for the font
+    /// at the specified index in the data.
+    pub fn font_data(data: &[u8], index: u32) -> Option<(&[u8], u32)> {
+        if let Some(offset) = offset(data, index) {
+            return Some((data, offset));
+        }
+        // Table offsets in resources are relative to the resource data.
+        let (start, len) = resource(data, index)?;
+        let data = data.get(start as usize..start as usize + len as usize)?;
+        Some((data, 0))
+    }
+
+    const SFNT: RawTag = raw_tag(b"sfnt");
+    const APPLE_SINGLE: u32 = 0x00051600;
+    const APPLE_DOUBLE: u32 = 0x00051607;
+
+    /// Returns true if the data represents a Mac resource fork (such as a
+    /// dfont file) containing font resources.
+    pub fn is_resource_fork(data: &[u8]) -> bool {
+        resource_count(data) != 0
+    }
+
+    /// Returns the number of sfnt resources in a Mac resource fork.
+    pub fn resource_count(data: &[u8]) -> u32 {
+        resource_list(data).map(|list| list.2).unwrap_or(0)
+    }
+
+    /// Returns the offset and length of the sfnt resource at the specified
+    /// index in a Mac resource fork.
+    pub fn resource(data: &[u8], index: u32) -> Option<(u32, u32)> {
+        let (base, refs, count) = resource_list(data)?;
+        if index >= count {
+            return None;
+        }
+        let b = Bytes::new(data);
+        let fork = base.0 as usize;
+        let data_offset = fork + base.1 as usize;
+        // Reference entry: id (u16), name offset (u16), attributes (u8),
+        // data offset (u24) and a reserved handle (u32).
+        let entry = refs as usize + index as usize * 12;
+        let offset = data_offset + (b.read_u32(entry + 4)? & 0xFFFFFF) as usize;
+        let len = b.read_u32(offset)?;
+        let start = offset.checked_add(4)?;
+        if !b.check_range(start, len as usize) {
+            return None;
         }
+        Some((start as u32, len))
+    }
+
+    /// Returns the offset and length of the resource fork in the data,
+    /// unwrapping AppleSingle and AppleDouble files.
+    fn resource_fork(data: &[u8]) -> Option<(u32, u32)> {
+        let b = Bytes::new(data);
+        let magic = b.read_u32(0)?;
+        if magic != APPLE_SINGLE && magic != APPLE_DOUBLE {
+            return Some((0, data.len() as u32));
+        }
+        let count = b.read_u16(24)? as usize;
+        for i in 0..count {
+            let entry = 26 + i * 12;
+            // Entry 2 contains the resource fork.
+            if b.read_u32(entry)? == 2 {
+                let offset = b.read_u32(entry + 4)?;
+                let len = b.read_u32(entry + 8)?;
+                if !b.check_range(offset as usize, len as usize) {
+                    return None;
+                }
+                return Some((offset, len));
+            }
+        }
+        None
+    }
+
+    /// Returns the base of the resource fork (offset and offset to the
+    /// resource data), the offset of the reference list for sfnt resources
+    /// and the number of sfnt resources.
+    fn resource_list(data: &[u8]) -> Option<((u32, u32), u32, u32)> {
+        let (fork, fork_len) = resource_fork(data)?;
+        let b = Bytes::new(data);
+        let base = fork as usize;
+        let data_offset = b.read_u32(base)?;
+        let map_offset = b.read_u32(base + 4)?;
+        let data_len = b.read_u32(base + 8)?;
+        let map_len = b.read_u32(base + 12)?;
+        // The resource data and map must be disjoint and within the fork.
+        let (data_start, data_end) = (data_offset as u64, data_offset as u64 + data_len as u64);
+        let (map_start, map_end) = (map_offset as u64, map_offset as u64 + map_len as u64);
+        if data_start < 16
+            || map_len < 30
+            || data_end > fork_len as u64
+            || map_end > fork_len as u64
+            || (map_start < data_end && data_start < map_end)
+        {
+            return None;
+        }
+        let map = base + map_offset as usize;
+        let types = map + b.read_u16(map + 24)? as usize;
+        let type_count = b.read_u16(types)?.wrapping_add(1) as usize;
+        for i in 0..type_count {
+            let entry = types + 2 + i * 8;
+            if b.read_u32(entry)? == SFNT {
+                let count = b.read_u16(entry + 4)? as u32 + 1;
+                let refs = types + b.read_u16(entry + 6)? as usize;
+                if !b.check_range(refs, count as usize * 12) {
+                    return None;
+                }
+                return Some(((fork, data_offset), refs as u32, count));
+            }
+        }
+        None
     }
 }
 
@@ -94,6 +215,28 @@ pub trait RawFont<'a>: Sized {
         Some(())
     }
 
+    /// Returns the number of tables in the table directory.
+    fn table_count(&self) -> u16 {
+        let base = self.offset() as usize;
+        Bytes::new(self.data()).read_or_default(base + 4)
+    }
+
+    /// Returns the tag and range for the table record at the specified index
+    /// in the table directory.
+    fn table_at(&self, index: u16) -> Option<(RawTag, (u32, u32))> {
+        if index >= self.table_count() {
+            return None;
+        }
+        let base = self.offset() as usize;
+        let recbase = base.checked_add(12 + index as usize * 16)?;
+        let mut s = Bytes::new(self.data()).stream_at(recbase)?;
+        let tag = s.read_u32()?;
+        s.skip(4)?;
+        let start = s.read_u32()?;
+        let len = s.read_u32()?;
+        Some((tag, (start, start.checked_add(len)?)))
+    }
+
     /// Returns the range for the table with the specified tag.
     fn table_range(&self, tag: RawTag) -> Option<(u32, u32)> {
         let base = self.offset() as usize;
@@ -0,0 +1,172 @@
+//! Style attributes table.
+
+use super::{fixed::Fixed, raw_tag, Bytes, RawFont, RawTag};
+
+pub const STAT: RawTag = raw_tag(b"STAT");
+
+/// The axis value applies to older versions of the font family.
+pub const OLDER_SIBLING_FONT_ATTRIBUTE: u16 = 1;
+/// The axis value name can be omitted when composing style names.
+pub const ELIDABLE_AXIS_VALUE_NAME: u16 = 2;
+
+/// Style attributes table.
+#[derive(Copy, Clone)]
+pub struct Stat<'a> {
+    data: Bytes<'a>,
+    axis_size: u16,
+    axis_count: u16,
+    axis_offset: u32,
+    value_count: u16,
+    value_offset: u32,
+}
+
+impl<'a> Stat<'a> {
+    pub fn new(data: &'a [u8]) -> Self {
+        let b = Bytes::new(data);
+        Self {
+            data: b,
+            axis_size: b.read_or_default(4),
+            axis_count: b.read_or_default(6),
+            axis_offset: b.read_or_default(8),
+            value_count: b.read_or_default(12),
+            value_offset: b.read_or_default(14),
+        }
+    }
+
+    pub fn from_font(font: impl RawFont<'a>) -> Option<Self> {
+        Some(Self::new(font.table_data(STAT)?))
+    }
+
+    /// Returns the name identifier to use when all axis value names are
+    /// elided.
+    pub fn elided_fallback_name_id(&self) -> Option<u16> {
+        if self.data.read_or_default::<u16>(2) >= 1 {
+            self.data.read(18)
+        } else {
+            None
+        }
+    }
+
+    pub fn axis_count(&self) -> u16 {
+        self.axis_count
+    }
+
+    pub fn get_axis(&self, index: u16) -> Option<StatAxis> {
+        if index >= self.axis_count {
+            return None;
+        }
+        let b = &self.data;
+        let offset = self.axis_offset as usize + index as usize * self.axis_size as usize;
+        Some(StatAxis {
+            index,
+            tag: b.read(offset)?,
+            name_id: b.read(offset + 4)?,
+            ordering: b.read(offset + 6)?,
+        })
+    }
+
+    pub fn value_count(&self) -> u16 {
+        self.value_count
+    }
+
+    pub fn get_value(&self, index: u16) -> Option<StatValue<'a>> {
+        if index >= self.value_count {
+            return None;
+        }
+        let b = &self.data;
+        let base = self.value_offset as usize;
+        let offset = base + b.read::<u16>(base + index as usize * 2)? as usize;
+        let format = b.read::<u16>(offset)?;
+        let flags = b.read::<u16>(offset + 4)?;
+        let name_id = b.read::<u16>(offset + 6)?;
+        let mut value = StatValue {
+            index,
+            format,
+            flags,
+            name_id,
+            axis_index: 0,
+            value: Fixed(0),
+            min: Fixed(0),
+            max: Fixed(0),
+            linked_value: None,
+            records: Bytes::new(&[]),
+        };
+        match format {
+            1..=3 => {
+                value.axis_index = b.read(offset + 2)?;
+                value.value = Fixed(b.read(offset + 8)?);
+                value.min = value.value;
+                value.max = value.value;
+                if format == 2 {
+                    value.min = Fixed(b.read(offset + 12)?);
+                    value.max = Fixed(b.read(offset + 16)?);
+                } else if format == 3 {
+                    value.linked_value = Some(Fixed(b.read(offset + 12)?));
+                }
+            }
+            4 => {
+                let count = b.read::<u16>(offset + 2)? as usize;
+                value.records = Bytes::new(b.read_bytes(offset + 8, count * 6)?);
+            }
+            _ => return None,
+        }
+        Some(value)
+    }
+}
+
+/// Design axis in a style attributes table.
+#[derive(Copy, Clone, Default)]
+pub struct StatAxis {
+    pub index: u16,
+    pub tag: RawTag,
+    pub name_id: u16,
+    pub ordering: u16,
+}
+
+/// Axis value in a style attributes table.
+#[derive(Copy, Clone)]
+pub struct StatValue<'a> {
+    pub index: u16,
+    pub format: u16,
+    pub flags: u16,
+    pub name_id: u16,
+    /// Design axis index for formats 1-3.
+    pub axis_index: u16,
+    /// Nominal value for formats 1-3.
+    pub value: Fixed,
+    /// Minimum value of the range for format 2.
+    pub min: Fixed,
+    /// Maximum value of the range for format 2.
+    pub max: Fixed,
+    /// Linked value for format 3.
+    pub linked_value: Option<Fixed>,
+    /// Axis/value records for format 4.
+    pub records: Bytes<'a>,
+}
+
+impl<'a> StatValue<'a> {
+    /// Returns the number of axes covered by the value.
+    pub fn record_count(&self) -> u16 {
+        if self.format == 4 {
+            (self.records.len() / 6) as u16
+        } else {
+            1
+        }
+    }
+
+    /// Returns the design axis index and value for the specified record.
+    pub fn record(&self, index: u16) -> Option<(u16, Fixed)> {
+        if self.format != 4 {
+            return if index == 0 {
+                Some((self.axis_index, self.value))
+            } else {
+                None
+            };
+        }
+        let offset = index as usize * 6;
+        Some((
+            self.records.read(offset)?,
+            Fixed(self.records.read(offset + 2)?),
+        ))
+    }
+}
@@ -37,14 +37,20 @@ mod macros;
 mod attributes;
 mod cache;
 mod charmap;
+mod coverage;
+mod database;
 mod feature;
 mod font;
+mod glyph_names;
 mod internal;
+mod meta;
 mod metrics;
 mod palette;
 mod setting;
+mod stat;
 mod strike;
 mod string;
+mod svg;
 mod tag;
 mod variation;
 
@@ -56,14 +62,19 @@ pub mod text;
 
 pub use attributes::*;
 pub use cache::CacheKey;
-pub use charmap::Charmap;
+pub use charmap::{Charmap, MapVariant, ReverseCharmap};
+pub use coverage::{Coverage, CoverageCache};
+pub use database::{FontDatabase, FontId, FontMatch};
 pub use feature::{Action, Feature, WritingSystem};
 pub use font::{FontDataRef, FontRef};
+pub use glyph_names::GlyphNames;
 pub use metrics::{GlyphMetrics, Metrics};
 pub use palette::{ColorPalette, Usability};
 pub use setting::Setting;
+pub use stat::AxisValue;
 pub use strike::BitmapStrike;
 pub use string::{LocalizedString, StringId};
+pub use svg::SvgDocument;
 pub use tag::{Tag, tag_from_bytes, tag_from_str_lossy};
 pub use variation::{Instance, Variation};
 
@@ -71,12 +82,23 @@ pub use variation::{Instance, Variation};
 pub mod iter {
     pub use super::feature::{Features, WritingSystems};
     pub use super::font::Fonts;
+    pub use super::meta::Languages;
     pub use super::palette::ColorPalettes;
+    pub use super::stat::AxisValues;
     pub use super::strike::BitmapStrikes;
     pub use super::string::{Chars, LocalizedStrings};
+    pub use super::svg::SvgDocuments;
     pub use super::variation::{Instances, Variations};
 }
 
+/// Typed views of the font header and global metadata tables.
+pub mod tables {
+    pub use super::internal::head::{
+        Embedding, Head, Hhea, MacStyle, Maxp, Os2, Panose, Permissions, Post, SelectionFlags,
+        Vhea,
+    };
+}
+
 /// Proxies used to efficiently rematerialize metadata.
 pub mod proxy {
     pub use super::strike::BitmapStrikesProxy;
@@ -87,6 +109,7 @@ pub mod proxy {
 
 use iter::*;
 use proxy::BitmapStrikesProxy;
+use tables::*;
 
 /// Glyph identifier.
 pub type GlyphId = u16;
@@ -105,6 +128,56 @@ impl<'a> FontRef<'a> {
         LocalizedStrings::from_font(self)
     }
 
+    /// Returns the preferred family name for the font and the specified
+    /// language.
+    ///
+    /// This follows the OpenType precedence rules, preferring the
+    /// typographic family name, then the WWS family name and finally the
+    /// legacy family name. Strings in the requested language take
+    /// precedence, followed by English and then any decodable string.
+    pub fn family_name(&self, language: Option<&text::Language>) -> Option<LocalizedString<'a>> {
+        let (family, _) = self.name_ids();
+        self.localized_strings().find_best(&[family], language)
+    }
+
+    /// Returns the preferred subfamily (style) name for the font and the
+    /// specified language.
+    ///
+    /// The subfamily name is selected from the same group of names as the
+    /// [`family_name`](Self::family_name), falling back to the legacy
+    /// subfamily name if the font does not provide the corresponding
+    /// subfamily.
+    pub fn subfamily_name(&self, language: Option<&text::Language>) -> Option<LocalizedString<'a>> {
+        let (_, subfamily) = self.name_ids();
+        self.localized_strings()
+            .find_best(&[subfamily, StringId::SubFamily], language)
+    }
+
+    /// Returns the family and subfamily identifiers of the highest precedence
+    /// group for which the font provides a decodable family name.
+    fn name_ids(&self) -> (StringId, StringId) {
+        const IDS: [(StringId, StringId); 2] = [
+            (StringId::TypographicFamily, StringId::TypographicSubFamily),
+            (StringId::WwsFamily, StringId::WwsSubFamily),
+        ];
+        let strings = self.localized_strings();
+        IDS.iter()
+            .copied()
+            .find(|(family, _)| {
+                strings
+                    .clone()
+                    .any(|s| s.id() == *family && s.is_decodable())
+            })
+            .unwrap_or((StringId::Family, StringId::SubFamily))
+    }
+
+    /// Returns the full name for the font and the specified language,
+    /// falling back to English and then any decodable string.
+    pub fn full_name(&self, language: Option<&text::Language>) -> Option<LocalizedString<'a>> {
+        self.localized_strings()
+            .find_best(&[StringId::Full], language)
+    }
+
     /// Returns an iterator over the variations for the font.
     pub fn variations(&self) -> Variations<'a> {
         Variations::from_font(self)
@@ -115,11 +188,27 @@ impl<'a> FontRef<'a> {
         Instances::from_font(self)
     }
 
+    /// Returns an iterator over the style axis values for the font.
+    pub fn axis_values(&self) -> AxisValues<'a> {
+        AxisValues::from_font(self)
+    }
+
     /// Returns an iterator over writing systems supported by the font.
     pub fn writing_systems(&self) -> WritingSystems<'a> {
         WritingSystems::from_font(self)
     }
 
+    /// Returns an iterator over the languages the font was primarily
+    /// designed for.
+    pub fn design_languages(&self) -> Languages<'a> {
+        Languages::design(self)
+    }
+
+    /// Returns an iterator over the languages the font claims to support.
+    pub fn supported_languages(&self) -> Languages<'a> {
+        Languages::supported(self)
+    }
+
     /// Returns an iterator over the features supported by a font.
     pub fn features(&self) -> Features<'a> {
         Features::from_font(self)
@@ -142,6 +231,11 @@ impl<'a> FontRef<'a> {
         Charmap::from_font(self)
     }
 
+    /// Returns the glyph names for the font.
+    pub fn glyph_names(&self) -> GlyphNames<'a> {
+        GlyphNames::from_font(self)
+    }
+
     /// Returns an iterator over the color palettes for the font.
     pub fn color_palettes(&self) -> ColorPalettes<'a> {
         ColorPalettes::from_font(self)
@@ -157,6 +251,41 @@ impl<'a> FontRef<'a> {
         BitmapStrikesProxy::from_font(self).materialize_color(self)
     }
 
+    /// Returns an iterator over the SVG documents for the font.
+    pub fn svg_documents(&self) -> SvgDocuments<'a> {
+        SvgDocuments::from_font(self)
+    }
+
+    /// Returns the font header table.
+    pub fn head(&self) -> Option<Head<'a>> {
+        Head::from_font(self)
+    }
+
+    /// Returns the OS/2 and Windows metrics table.
+    pub fn os2(&self) -> Option<Os2<'a>> {
+        Os2::from_font(self)
+    }
+
+    /// Returns the PostScript table.
+    pub fn post(&self) -> Option<Post<'a>> {
+        Post::from_font(self)
+    }
+
+    /// Returns the maximum profile table.
+    pub fn maxp(&self) -> Option<Maxp<'a>> {
+        Maxp::from_font(self)
+    }
+
+    /// Returns the horizontal header table.
+    pub fn hhea(&self) -> Option<Hhea<'a>> {
+        Hhea::from_font(self)
+    }
+
+    /// Returns the vertical header table.
+    pub fn vhea(&self) -> Option<Vhea<'a>> {
+        Vhea::from_font(self)
+    }
+
     /// Returns the table data for the specified tag.
     pub fn table(&self, tag: Tag) -> Option<&'a [u8]> {
         use internal::RawFont;
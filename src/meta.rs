@@ -0,0 +1,88 @@
+use super::internal::{meta::*, RawFont};
+use super::{text::Language, FontRef};
+
+/// Iterator over a list of languages declared by a font.
+///
+/// The lists are defined by the `dlng` (design languages) and `slng`
+/// (supported languages) entries of the `meta` table. Entries that consist
+/// of only a script, such as `Jpan`, produce languages with an undetermined
+/// (`und`) language component. Entries that cannot be parsed are skipped.
+#[derive(Copy, Clone)]
+pub struct Languages<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Languages<'a> {
+    fn from_font(font: &FontRef<'a>, tag: u32) -> Self {
+        let data = font
+            .table_data(META)
+            .and_then(|meta| entry(meta, tag))
+            .unwrap_or(&[]);
+        Self { data, pos: 0 }
+    }
+
+    pub(crate) fn design(font: &FontRef<'a>) -> Self {
+        Self::from_font(font, DLNG)
+    }
+
+    pub(crate) fn supported(font: &FontRef<'a>) -> Self {
+        Self::from_font(font, SLNG)
+    }
+
+    /// Returns true if the list claims support for the specified language.
+    ///
+    /// An entry matches when each of its components is either absent (or
+    /// undetermined, for the language) or equal to the corresponding
+    /// component of the requested language. For example, the entries `Jpan`
+    /// and `ja` both match `ja-Jpan`, while `ja-Jpan` does not match `ja`.
+    ///
+    /// ## Iteration behavior
+    /// This function searches the entire list without regard for the
+    /// current state of the iterator.
+    pub fn contains(&self, language: &Language) -> bool {
+        let matches = |entry: &Language| {
+            (entry.language() == "und" || entry.language() == language.language())
+                && (entry.script().is_none() || entry.script() == language.script())
+                && (entry.region().is_none() || entry.region() == language.region())
+        };
+        Self { data: self.data, pos: 0 }.any(|entry| matches(&entry))
+    }
+}
+
+impl<'a> Iterator for Languages<'a> {
+    type Item = Language;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let rest = self.data.get(self.pos..)?;
+            if rest.is_empty() {
+                return None;
+            }
+            let end = rest.iter().position(|b| *b == b',').unwrap_or(rest.len());
+            let item = &rest[..end];
+            self.pos += end + 1;
+            let tag = match core::str::from_utf8(item) {
+                Ok(tag) => tag.trim(),
+                _ => continue,
+            };
+            if let Some(language) = parse_script_lang_tag(tag) {
+                return Some(language);
+            }
+        }
+    }
+}
+
+/// Parses a script/language tag which, unlike a BCP 47 language tag, may
+/// begin with a script subtag.
+fn parse_script_lang_tag(tag: &str) -> Option<Language> {
+    let first = tag.split('-').next()?;
+    if first.len() != 4 {
+        return Language::parse(tag);
+    }
+    let mut buf = [0u8; 32];
+    let len = tag.len() + 4;
+    buf.get_mut(..4)?.copy_from_slice(b"und-");
+    buf.get_mut(4..len)?.copy_from_slice(tag.as_bytes());
+    Language::parse(core::str::from_utf8(&buf[..len]).ok()?)
+}
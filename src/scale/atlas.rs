@@ -0,0 +1,666 @@
+/*!
+Cache of rendered glyphs packed into texture atlases.
+
+The [`GlyphCache`] wraps [`Render`] for renderers that draw glyphs from
+textures. Images are keyed by font, glyph identifier, size, normalized
+variation coordinates, hinting, quantized subpixel offset and the list of
+sources, and are packed into one or more atlases using a shelf allocator.
+When the atlases are full, the least recently used glyphs are evicted to
+make room, and an atlas may be replaced by one with a different number of
+channels when no atlas of the required kind exists.
+
+Changes to the atlases are recorded as [`AtlasEvent`]s so that textures can
+be updated incrementally:
+```
+# use swash::{scale::{*, atlas::*}, FontRef};
+# fn draw(context: &mut ScaleContext, cache: &mut GlyphCache, font: FontRef) {
+let mut scaler = context.builder(font).size(16.).build();
+let render = Render::new(&[Source::ColorBitmap(StrikeWith::BestFit), Source::Outline]);
+let glyph = cache.get(&mut scaler, &render, 42, 10.25, 20.);
+for event in cache.drain_events() {
+    match event {
+        AtlasEvent::Created(index) => {
+            // Allocate a texture for cache.atlases()[index]
+        }
+        AtlasEvent::Added(index, rect) => {
+            // Upload the rectangle from cache.atlases()[index].data()
+        }
+        AtlasEvent::Evicted(..) => {}
+    }
+}
+# }
+```
+*/
+
+use super::image::{Content, Image};
+use super::{Hinting, Render, Scaler, Source};
+use crate::{GlyphId, NormalizedCoord};
+use core::hash::Hash;
+use std::collections::HashMap;
+use zeno::{Placement, Vector};
+
+/// Maximum number of cached glyphs without an image in an atlas, such as
+/// spaces and glyphs that failed to render.
+const MAX_EMPTY_ENTRIES: usize = 4096;
+
+/// Rectangle within an atlas.
+#[derive(Copy, Clone, PartialEq, Eq, Default, Debug)]
+pub struct AtlasRect {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+/// Change to an atlas that should be reflected in the corresponding texture.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum AtlasEvent {
+    /// An atlas was created at the specified index. This replaces any
+    /// previous atlas at the same index, which may have had a different
+    /// number of channels.
+    Created(usize),
+    /// Image data was written to a rectangle of an atlas.
+    Added(usize, AtlasRect),
+    /// A rectangle of an atlas is no longer in use.
+    Evicted(usize, AtlasRect),
+}
+
+/// Glyph image stored in an atlas.
+#[derive(Copy, Clone, Debug)]
+pub struct CachedGlyph {
+    /// Source of the image.
+    pub source: Source,
+    /// Content of the image.
+    pub content: Content,
+    /// Offset and size of the image relative to the integral glyph origin.
+    pub placement: Placement,
+    /// Index of the atlas containing the image.
+    pub atlas: usize,
+    /// Rectangle of the image within the atlas. Empty for glyphs without
+    /// an image, such as spaces.
+    pub rect: AtlasRect,
+}
+
+/// Texture atlas containing glyph images.
+pub struct Atlas {
+    width: u32,
+    height: u32,
+    channels: u32,
+    data: Vec<u8>,
+    shelves: Vec<Shelf>,
+    bottom: u32,
+}
+
+impl Atlas {
+    fn new(width: u32, height: u32, channels: u32) -> Self {
+        Self {
+            width,
+            height,
+            channels,
+            data: vec![0; (width * height * channels) as usize],
+            shelves: Vec::new(),
+            bottom: 0,
+        }
+    }
+
+    /// Returns the width of the atlas in pixels.
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    /// Returns the height of the atlas in pixels.
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// Returns the number of bytes per pixel. This is 1 for masks and
+    /// distance fields and 4 for color images and subpixel masks.
+    pub fn channels(&self) -> u32 {
+        self.channels
+    }
+
+    /// Returns the pixel data of the atlas in rows from top to bottom.
+    pub fn data(&self) -> &[u8] {
+        &self.data
+    }
+
+    /// Allocates a rectangle of the specified size, preferring shelves with
+    /// the least wasted height.
+    fn allocate(&mut self, width: u32, height: u32) -> Option<(u32, u32)> {
+        if width > self.width || height > self.height {
+            return None;
+        }
+        // Shelves that would waste more than half of their height are only
+        // considered when empty and no new shelf fits.
+        let limit = height + height / 2;
+        let mut best: Option<usize> = None;
+        let mut fallback: Option<usize> = None;
+        for (i, shelf) in self.shelves.iter().enumerate() {
+            if shelf.height < height || !shelf.fits(width, self.width) {
+                continue;
+            }
+            let slot = if shelf.height <= limit {
+                &mut best
+            } else if shelf.is_empty() {
+                &mut fallback
+            } else {
+                continue;
+            };
+            if slot
+                .map(|j| shelf.height < self.shelves[j].height)
+                .unwrap_or(true)
+            {
+                *slot = Some(i);
+            }
+        }
+        let index = match best {
+            Some(index) => index,
+            None if self.bottom + height <= self.height => {
+                self.shelves.push(Shelf::new(self.bottom, height));
+                self.bottom += height;
+                self.shelves.len() - 1
+            }
+            None => fallback?,
+        };
+        let shelf = &mut self.shelves[index];
+        Some((shelf.allocate(width, self.width)?, shelf.y))
+    }
+
+    fn deallocate(&mut self, x: u32, y: u32, width: u32) {
+        if let Some(shelf) = self.shelves.iter_mut().find(|s| s.y == y) {
+            shelf.deallocate(x, width);
+        }
+        while let Some(shelf) = self.shelves.last() {
+            if !shelf.is_empty() || shelf.y + shelf.height != self.bottom {
+                break;
+            }
+            self.bottom = shelf.y;
+            self.shelves.pop();
+        }
+    }
+
+    /// Clears the specified region and copies the image into its top left
+    /// corner.
+    fn write(&mut self, x: u32, y: u32, width: u32, height: u32, image: &Image) {
+        let channels = self.channels as usize;
+        let pitch = self.width as usize * channels;
+        let src_pitch = image.placement.width as usize * channels;
+        for row in 0..height as usize {
+            let start = (y as usize + row) * pitch + x as usize * channels;
+            let dst = &mut self.data[start..start + width as usize * channels];
+            for b in dst.iter_mut() {
+                *b = 0;
+            }
+            if let Some(src) = image.data.get(row * src_pitch..(row + 1) * src_pitch) {
+                dst[..src_pitch].copy_from_slice(src);
+            }
+        }
+    }
+}
+
+/// Horizontal strip of an atlas holding images of similar height.
+struct Shelf {
+    y: u32,
+    height: u32,
+    end: u32,
+    free: Vec<(u32, u32)>,
+}
+
+impl Shelf {
+    fn new(y: u32, height: u32) -> Self {
+        Self {
+            y,
+            height,
+            end: 0,
+            free: Vec::new(),
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.end == 0
+    }
+
+    fn fits(&self, width: u32, limit: u32) -> bool {
+        self.end + width <= limit || self.free.iter().any(|s| s.1 >= width)
+    }
+
+    fn allocate(&mut self, width: u32, limit: u32) -> Option<u32> {
+        let mut best: Option<usize> = None;
+        for (i, slot) in self.free.iter().enumerate() {
+            if slot.1 >= width && best.map(|b| slot.1 < self.free[b].1).unwrap_or(true) {
+                best = Some(i);
+            }
+        }
+        if let Some(i) = best {
+            let (x, w) = self.free[i];
+            if w == width {
+                self.free.remove(i);
+            } else {
+                self.free[i] = (x + width, w - width);
+            }
+            return Some(x);
+        }
+        if self.end + width > limit {
+            return None;
+        }
+        let x = self.end;
+        self.end += width;
+        Some(x)
+    }
+
+    fn deallocate(&mut self, x: u32, width: u32) {
+        let i = self
+            .free
+            .iter()
+            .position(|s| s.0 > x)
+            .unwrap_or(self.free.len());
+        self.free.insert(i, (x, width));
+        // Merge with the following and preceding slots.
+        if i + 1 < self.free.len() && x + width == self.free[i + 1].0 {
+            self.free[i].1 += self.free[i + 1].1;
+            self.free.remove(i + 1);
+        }
+        if i > 0 && self.free[i - 1].0 + self.free[i - 1].1 == x {
+            self.free[i - 1].1 += self.free[i].1;
+            self.free.remove(i);
+        }
+        if let Some(&(x, w)) = self.free.last() {
+            if x + w == self.end {
+                self.end = x;
+                self.free.pop();
+            }
+        }
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Hash)]
+struct Key {
+    font: u64,
+    glyph_id: GlyphId,
+    size: u32,
+    coords: usize,
+    hinting: Hinting,
+    force_autohint: bool,
+    offset: [u8; 2],
+    sources: usize,
+}
+
+struct Entry {
+    glyph: Option<CachedGlyph>,
+    epoch: u64,
+}
+
+impl Entry {
+    /// Returns the atlas and rectangle occupied by the entry, if any.
+    fn image(&self) -> Option<(usize, AtlasRect)> {
+        let glyph = self.glyph.as_ref()?;
+        if glyph.rect.width == 0 {
+            return None;
+        }
+        Some((glyph.atlas, glyph.rect))
+    }
+}
+
+/// Set of lists referenced by cache keys. Lists are identified by index and
+/// dropped when no longer referenced.
+struct Interner<T> {
+    ids: HashMap<Vec<T>, usize>,
+    lists: Vec<(Vec<T>, usize)>,
+    free: Vec<usize>,
+}
+
+impl<T: Copy + Eq + Hash> Interner<T> {
+    fn new() -> Self {
+        Self {
+            ids: HashMap::new(),
+            lists: Vec::new(),
+            free: Vec::new(),
+        }
+    }
+
+    /// Returns the index of the list, if present.
+    fn find(&self, list: &[T]) -> Option<usize> {
+        self.ids.get(list).copied()
+    }
+
+    /// Adds a reference to the list, returning its index.
+    fn acquire(&mut self, list: &[T]) -> usize {
+        let index = match self.ids.get(list) {
+            Some(&index) => index,
+            None => {
+                let index = match self.free.pop() {
+                    Some(index) => {
+                        self.lists[index].0.extend_from_slice(list);
+                        index
+                    }
+                    None => {
+                        self.lists.push((list.to_vec(), 0));
+                        self.lists.len() - 1
+                    }
+                };
+                self.ids.insert(list.to_vec(), index);
+                index
+            }
+        };
+        self.lists[index].1 += 1;
+        index
+    }
+
+    /// Removes a reference to the list at the specified index.
+    fn release(&mut self, index: usize) {
+        let (list, refs) = &mut self.lists[index];
+        *refs -= 1;
+        if *refs == 0 {
+            self.ids.remove(&list[..]);
+            list.clear();
+            self.free.push(index);
+        }
+    }
+
+    fn clear(&mut self) {
+        self.ids.clear();
+        self.lists.clear();
+        self.free.clear();
+    }
+}
+
+/// Cache of rendered glyphs packed into texture atlases.
+///
+/// A cache assumes a single configuration of [`Render`] apart from the list
+/// of sources. Use separate caches for other settings such as subpixel
+/// formats, transforms or distance fields.
+///
+/// When all atlases are full, adding a glyph evicts the least recently used
+/// glyphs, which may include glyphs returned earlier in the same frame. The
+/// atlases should be large enough to hold the glyphs of a typical frame.
+/// Glyphs that cannot be stored in an atlas are not cached and are rendered
+/// again when requested.
+pub struct GlyphCache {
+    width: u32,
+    height: u32,
+    max_atlases: usize,
+    padding: u32,
+    steps: [u8; 2],
+    atlases: Vec<Atlas>,
+    entries: HashMap<Key, Entry>,
+    empty_entries: usize,
+    coords: Interner<NormalizedCoord>,
+    sources: Interner<Source>,
+    events: Vec<AtlasEvent>,
+    image: Image,
+    epoch: u64,
+}
+
+impl GlyphCache {
+    /// Creates a new glyph cache that allocates atlases of the specified
+    /// size, up to the specified maximum number of atlases.
+    pub fn new(atlas_width: u32, atlas_height: u32, max_atlases: usize) -> Self {
+        Self {
+            width: atlas_width,
+            height: atlas_height,
+            max_atlases: max_atlases.max(1),
+            padding: 1,
+            steps: [4, 1],
+            atlases: Vec::new(),
+            entries: HashMap::new(),
+            empty_entries: 0,
+            coords: Interner::new(),
+            sources: Interner::new(),
+            events: Vec::new(),
+            image: Image::new(),
+            epoch: 0,
+        }
+    }
+
+    /// Sets the number of horizontal and vertical subpixel positions that
+    /// are rendered for each glyph. Default is `(4, 1)`.
+    pub fn set_subpixel_steps(&mut self, x: u8, y: u8) {
+        self.steps = [x.max(1), y.max(1)];
+    }
+
+    /// Sets the number of empty pixels kept to the right of and below each
+    /// image to avoid bleeding when sampling. Default is `1`.
+    pub fn set_padding(&mut self, padding: u32) {
+        self.padding = padding;
+    }
+
+    /// Returns the atlases of the cache.
+    pub fn atlases(&self) -> &[Atlas] {
+        &self.atlases
+    }
+
+    /// Returns an iterator over the changes to the atlases since the last
+    /// call, removing them from the cache.
+    pub fn drain_events(&mut self) -> impl Iterator<Item = AtlasEvent> + '_ {
+        self.events.drain(..)
+    }
+
+    /// Removes all glyphs and atlases from the cache.
+    pub fn clear(&mut self) {
+        self.atlases.clear();
+        self.entries.clear();
+        self.empty_entries = 0;
+        self.coords.clear();
+        self.sources.clear();
+        self.events.clear();
+    }
+
+    /// Returns the cached image for the specified glyph with its origin at
+    /// the specified position in a coordinate system where y points down,
+    /// rendering it if necessary. The position is quantized to the subpixel
+    /// steps of the cache and the offset of the renderer is ignored.
+    pub fn get(
+        &mut self,
+        scaler: &mut Scaler,
+        render: &Render,
+        glyph_id: GlyphId,
+        x: f32,
+        y: f32,
+    ) -> Option<CachedGlyph> {
+        let offset = [quantize(x, self.steps[0]), quantize(y, self.steps[1])];
+        let mut key = Key {
+            font: scaler.id,
+            glyph_id,
+            size: scaler.size.to_bits(),
+            coords: 0,
+            hinting: scaler.hinting,
+            force_autohint: scaler.force_autohint,
+            offset,
+            sources: 0,
+        };
+        self.epoch += 1;
+        // Entries only exist for interned lists.
+        if let (Some(coords), Some(sources)) = (
+            self.coords.find(scaler.coords),
+            self.sources.find(render.sources),
+        ) {
+            key.coords = coords;
+            key.sources = sources;
+            if let Some(entry) = self.entries.get_mut(&key) {
+                entry.epoch = self.epoch;
+                return entry.glyph;
+            }
+        }
+        let sources = render.sources;
+        let render = Render {
+            offset: Vector::new(
+                offset[0] as f32 / self.steps[0] as f32,
+                -(offset[1] as f32) / self.steps[1] as f32,
+            ),
+            ..*render
+        };
+        self.image.clear();
+        let glyph = if render.render_into(scaler, glyph_id, &mut self.image) {
+            Some(self.insert()?)
+        } else {
+            None
+        };
+        let entry = Entry {
+            glyph,
+            epoch: self.epoch,
+        };
+        if entry.image().is_none() {
+            if self.empty_entries >= MAX_EMPTY_ENTRIES {
+                self.evict_empty();
+            }
+            self.empty_entries += 1;
+        }
+        key.coords = self.coords.acquire(scaler.coords);
+        key.sources = self.sources.acquire(sources);
+        self.entries.insert(key, entry);
+        glyph
+    }
+
+    /// Removes an entry, releasing its interned lists.
+    fn remove(&mut self, key: &Key) -> Option<Entry> {
+        let entry = self.entries.remove(key)?;
+        self.coords.release(key.coords);
+        self.sources.release(key.sources);
+        if entry.image().is_none() {
+            self.empty_entries -= 1;
+        }
+        Some(entry)
+    }
+
+    /// Removes the least recently used half of the entries without an
+    /// image.
+    fn evict_empty(&mut self) {
+        let mut candidates = self
+            .entries
+            .iter()
+            .filter(|(_, entry)| entry.image().is_none())
+            .map(|(key, entry)| (entry.epoch, *key))
+            .collect::<Vec<_>>();
+        candidates.sort_unstable_by_key(|c| c.0);
+        candidates.truncate(candidates.len() / 2);
+        for (_, key) in candidates {
+            self.remove(&key);
+        }
+    }
+
+    /// Stores the current image in an atlas.
+    fn insert(&mut self) -> Option<CachedGlyph> {
+        let image = &self.image;
+        let placement = image.placement;
+        let mut glyph = CachedGlyph {
+            source: image.source,
+            content: image.content,
+            placement,
+            atlas: 0,
+            rect: AtlasRect::default(),
+        };
+        if placement.width == 0 || placement.height == 0 {
+            return Some(glyph);
+        }
+        let channels = match image.content {
+            Content::Mask | Content::DistanceField => 1,
+            _ => 4,
+        };
+        let width = placement.width + self.padding;
+        let height = placement.height + self.padding;
+        if width > self.width || height > self.height {
+            return None;
+        }
+        let (atlas, x, y) = self.allocate(width, height, channels)?;
+        self.atlases[atlas].write(x, y, width, height, &self.image);
+        glyph.atlas = atlas;
+        glyph.rect = AtlasRect {
+            x,
+            y,
+            width: placement.width,
+            height: placement.height,
+        };
+        self.events.push(AtlasEvent::Added(atlas, glyph.rect));
+        Some(glyph)
+    }
+
+    /// Allocates space in an atlas with the specified number of channels,
+    /// creating atlases or evicting glyphs as necessary.
+    fn allocate(&mut self, width: u32, height: u32, channels: u32) -> Option<(usize, u32, u32)> {
+        if let Some(pos) = self.try_allocate(width, height, channels) {
+            return Some(pos);
+        }
+        if self.atlases.len() < self.max_atlases {
+            let index = self.atlases.len();
+            self.atlases
+                .push(Atlas::new(self.width, self.height, channels));
+            self.events.push(AtlasEvent::Created(index));
+            return self.try_allocate(width, height, channels);
+        }
+        let atlases = &self.atlases;
+        let mut candidates = self
+            .entries
+            .iter()
+            .filter_map(|(key, entry)| {
+                let (atlas, _) = entry.image()?;
+                if atlases[atlas].channels != channels {
+                    return None;
+                }
+                Some((entry.epoch, *key))
+            })
+            .collect::<Vec<_>>();
+        candidates.sort_unstable_by_key(|c| c.0);
+        for (_, key) in candidates {
+            if let Some((atlas, rect)) = self.remove(&key).and_then(|e| e.image()) {
+                self.atlases[atlas].deallocate(rect.x, rect.y, rect.width + self.padding);
+                self.events.push(AtlasEvent::Evicted(atlas, rect));
+            }
+            if let Some(pos) = self.try_allocate(width, height, channels) {
+                return Some(pos);
+            }
+        }
+        self.replace_atlas(channels)?;
+        self.try_allocate(width, height, channels)
+    }
+
+    /// Replaces the least recently used atlas that has a different number
+    /// of channels with an empty atlas, evicting all of its glyphs.
+    fn replace_atlas(&mut self, channels: u32) -> Option<()> {
+        let mut last_used = vec![0; self.atlases.len()];
+        for entry in self.entries.values() {
+            if let Some((atlas, _)) = entry.image() {
+                last_used[atlas] = last_used[atlas].max(entry.epoch);
+            }
+        }
+        let index = (0..self.atlases.len())
+            .filter(|&i| self.atlases[i].channels != channels)
+            .min_by_key(|&i| last_used[i])?;
+        let keys = self
+            .entries
+            .iter()
+            .filter(|(_, entry)| entry.image().map(|(atlas, _)| atlas) == Some(index))
+            .map(|(key, _)| *key)
+            .collect::<Vec<_>>();
+        for key in keys {
+            if let Some((atlas, rect)) = self.remove(&key).and_then(|e| e.image()) {
+                self.events.push(AtlasEvent::Evicted(atlas, rect));
+            }
+        }
+        self.atlases[index] = Atlas::new(self.width, self.height, channels);
+        self.events.push(AtlasEvent::Created(index));
+        Some(())
+    }
+
+    fn try_allocate(
+        &mut self,
+        width: u32,
+        height: u32,
+        channels: u32,
+    ) -> Option<(usize, u32, u32)> {
+        for (i, atlas) in self.atlases.iter_mut().enumerate() {
+            if atlas.channels != channels {
+                continue;
+            }
+            if let Some((x, y)) = atlas.allocate(width, height) {
+                return Some((i, x, y));
+            }
+        }
+        None
+    }
+}
+
+/// Returns the subpixel step for the fractional part of a coordinate.
+fn quantize(v: f32, steps: u8) -> u8 {
+    let fract = v - v.floor();
+    ((fract * steps as f32) as u8).min(steps - 1)
+}
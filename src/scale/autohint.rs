@@ -0,0 +1,687 @@
+//! Automatic hinting for outlines without native hints.
+//!
+//! This is loosely modeled on the FreeType autofitter. Blue zones are
+//! measured per script from the outlines of reference characters, nearly
+//! horizontal runs of each outline are collected into edges that are linked
+//! into stems, and the edges are then fitted to the pixel grid along the
+//! y-axis. All remaining points are interpolated between the fitted edges.
+
+use super::Outline;
+use crate::{charmap::Charmap, GlyphId};
+use zeno::{Point, Verb};
+
+/// Maximum number of blue zones per script.
+const MAX_ZONES: usize = 6;
+
+/// Distance in pixels within which segments are merged into a single edge.
+const EDGE_FUZZ: f32 = 0.25;
+
+/// Distance in pixels by which an edge may lie outside a blue zone and
+/// still be aligned to it.
+const BLUE_FUZZ: f32 = 0.5;
+
+/// Script marker for glyphs that are not mapped by the character map.
+const UNMAPPED: u8 = 0xFF;
+
+/// Edge fitting states.
+const UNFITTED: u8 = 0;
+const BLUE: u8 = 1;
+const STEM: u8 = 2;
+
+/// Script with reference characters for measuring blue zones.
+struct Script {
+    /// Ranges of codepoints that belong to the script.
+    ranges: &'static [(u32, u32)],
+    /// Reference characters for each zone and whether the zone measures
+    /// the tops of the glyphs.
+    blues: &'static [(&'static str, bool)],
+}
+
+const SCRIPTS: [Script; 4] = [
+    // Latin
+    Script {
+        ranges: &[
+            (0x0000, 0x024F),
+            (0x1E00, 0x1EFF),
+            (0x2C60, 0x2C7F),
+            (0xA720, 0xA7FF),
+        ],
+        blues: &[
+            ("THEZOCQS", true),
+            ("HEZLOCUS", false),
+            ("bdhkl", true),
+            ("xzroesc", true),
+            ("xzroesc", false),
+            ("pqgjy", false),
+        ],
+    },
+    // Greek
+    Script {
+        ranges: &[(0x0370, 0x03FF), (0x1F00, 0x1FFF)],
+        blues: &[
+            ("ΓΒΕΖΘΟΩ", true),
+            ("ΒΔΖΞΘΟ", false),
+            ("βθδζλξ", true),
+            ("αειοπστω", true),
+            ("αειοπστω", false),
+            ("βγημρφχψ", false),
+        ],
+    },
+    // Cyrillic
+    Script {
+        ranges: &[(0x0400, 0x052F), (0x1C80, 0x1C8F), (0xA640, 0xA69F)],
+        blues: &[
+            ("БВЕПЗОСЭ", true),
+            ("БВЕШЗОСЭ", false),
+            ("хпншезос", true),
+            ("хпншезос", false),
+            ("руф", false),
+        ],
+    },
+    // Hebrew
+    Script {
+        ranges: &[(0x0590, 0x05FF), (0xFB1D, 0xFB4F)],
+        blues: &[("בדהחךכםס", true), ("בטכםסצ", false), ("קךןףץ", false)],
+    },
+];
+
+/// Returns the index of the script that contains the specified codepoint.
+fn script_for(codepoint: u32) -> Option<u8> {
+    SCRIPTS
+        .iter()
+        .position(|script| {
+            script
+                .ranges
+                .iter()
+                .any(|&(start, end)| codepoint >= start && codepoint <= end)
+        })
+        .map(|index| index as u8)
+}
+
+/// Alignment zone for the tops or bottoms of glyphs.
+#[derive(Copy, Clone, Default)]
+struct BlueZone {
+    top: bool,
+    /// Position of flat features in pixels.
+    reference: f32,
+    /// Position of round features in pixels.
+    overshoot: f32,
+    fitted_reference: f32,
+    fitted_overshoot: f32,
+}
+
+/// Blue zones and glyph scripts for a font at a particular size.
+#[derive(Clone, Default)]
+pub struct Metrics {
+    zones: [[BlueZone; MAX_ZONES]; SCRIPTS.len()],
+    zone_counts: [u8; SCRIPTS.len()],
+    scripts: Vec<u8>,
+    size: f32,
+}
+
+impl Metrics {
+    /// Measures the blue zones of each script at the specified size. The
+    /// loader is invoked to produce unhinted, scaled outlines for the
+    /// reference glyphs.
+    pub fn compute(
+        &mut self,
+        charmap: &Charmap,
+        size: f32,
+        outline: &mut Outline,
+        mut load: impl FnMut(GlyphId, &mut Outline) -> bool,
+    ) {
+        self.size = size;
+        self.zone_counts = [0; SCRIPTS.len()];
+        let scripts = &mut self.scripts;
+        scripts.clear();
+        charmap.enumerate(|codepoint, glyph_id| {
+            if let Some(script) = script_for(codepoint) {
+                let index = glyph_id as usize;
+                if index >= scripts.len() {
+                    scripts.resize(index + 1, UNMAPPED);
+                }
+                if scripts[index] == UNMAPPED {
+                    scripts[index] = script;
+                }
+            }
+        });
+        let threshold = size / 100.;
+        let mut on_curve = Vec::new();
+        let mut contours = Vec::new();
+        for (index, script) in SCRIPTS.iter().enumerate() {
+            for &(chars, top) in script.blues {
+                let mut flat = (0., 0);
+                let mut round = (0., 0);
+                for ch in chars.chars() {
+                    let glyph_id = charmap.map(ch);
+                    if glyph_id == 0 || !load(glyph_id, outline) {
+                        continue;
+                    }
+                    let layer = match outline.get(0) {
+                        Some(layer) => layer,
+                        _ => continue,
+                    };
+                    let (points, verbs) = (layer.points(), layer.verbs());
+                    if !classify(verbs, points.len(), &mut on_curve, &mut contours) {
+                        continue;
+                    }
+                    let (y, is_flat) = match extremum(points, &on_curve, &contours, top, threshold)
+                    {
+                        Some(extremum) => extremum,
+                        _ => continue,
+                    };
+                    let sum = if is_flat { &mut flat } else { &mut round };
+                    sum.0 += y;
+                    sum.1 += 1;
+                }
+                if flat.1 + round.1 == 0 {
+                    continue;
+                }
+                let reference = if flat.1 != 0 {
+                    flat.0 / flat.1 as f32
+                } else {
+                    round.0 / round.1 as f32
+                };
+                let mut overshoot = if round.1 != 0 {
+                    round.0 / round.1 as f32
+                } else {
+                    reference
+                };
+                overshoot = if top {
+                    overshoot.max(reference)
+                } else {
+                    overshoot.min(reference)
+                };
+                // Overshoots are suppressed until they reach half a pixel.
+                let fitted_reference = reference.round();
+                let delta = (overshoot - reference).abs();
+                let delta = if delta < 0.5 { 0. } else { delta.round() };
+                let count = self.zone_counts[index] as usize;
+                self.zones[index][count] = BlueZone {
+                    top,
+                    reference,
+                    overshoot,
+                    fitted_reference,
+                    fitted_overshoot: if top {
+                        fitted_reference + delta
+                    } else {
+                        fitted_reference - delta
+                    },
+                };
+                self.zone_counts[index] += 1;
+            }
+        }
+    }
+
+    fn zones(&self, glyph_id: GlyphId) -> &[BlueZone] {
+        let script = match self.scripts.get(glyph_id as usize) {
+            Some(&script) if script != UNMAPPED => script as usize,
+            _ => 0,
+        };
+        let zones = &self.zones[script][..self.zone_counts[script] as usize];
+        if zones.is_empty() {
+            &self.zones[0][..self.zone_counts[0] as usize]
+        } else {
+            zones
+        }
+    }
+}
+
+/// Cache of metrics for the automatic hinter.
+pub struct Cache {
+    entries: Vec<Entry>,
+    max_entries: usize,
+    epoch: u64,
+    hinter: Hinter,
+    /// Scratch outline for loading reference glyphs.
+    pub outline: Outline,
+}
+
+struct Entry {
+    epoch: u64,
+    id: u64,
+    coords: Vec<i16>,
+    size: f32,
+    metrics: Metrics,
+}
+
+impl Cache {
+    pub fn new(max_entries: usize) -> Self {
+        Self {
+            entries: Vec::new(),
+            max_entries,
+            epoch: 0,
+            hinter: Hinter::default(),
+            outline: Outline::new(),
+        }
+    }
+
+    /// Returns the index of the entry for the specified font, coordinates
+    /// and size along with true if the metrics have already been computed.
+    pub fn find(&mut self, id: u64, coords: &[i16], size: f32) -> (bool, usize) {
+        self.epoch += 1;
+        let epoch = self.epoch;
+        let mut lowest_epoch = epoch;
+        let mut lowest_index = 0;
+        for (i, entry) in self.entries.iter_mut().enumerate() {
+            if entry.id == id && entry.size == size && entry.coords == coords {
+                entry.epoch = epoch;
+                return (true, i);
+            }
+            if entry.epoch < lowest_epoch {
+                lowest_epoch = entry.epoch;
+                lowest_index = i;
+            }
+        }
+        if self.entries.len() < self.max_entries {
+            lowest_index = self.entries.len();
+        }
+        (false, lowest_index)
+    }
+
+    /// Removes the metrics from the entry at the specified index so that
+    /// they can be recomputed.
+    pub fn take(&mut self, index: usize) -> Metrics {
+        match self.entries.get_mut(index) {
+            Some(entry) => core::mem::take(&mut entry.metrics),
+            _ => Metrics::default(),
+        }
+    }
+
+    /// Stores computed metrics in the entry at the specified index.
+    pub fn insert(&mut self, index: usize, id: u64, coords: &[i16], size: f32, metrics: Metrics) {
+        let epoch = self.epoch;
+        if index == self.entries.len() {
+            self.entries.push(Entry {
+                epoch,
+                id,
+                coords: Vec::from(coords),
+                size,
+                metrics,
+            });
+        } else if let Some(entry) = self.entries.get_mut(index) {
+            entry.epoch = epoch;
+            entry.id = id;
+            entry.coords.clear();
+            entry.coords.extend_from_slice(coords);
+            entry.size = size;
+            entry.metrics = metrics;
+        }
+    }
+
+    /// Hints the points of a scaled outline using the metrics at the
+    /// specified index.
+    pub fn hint(&mut self, index: usize, glyph_id: GlyphId, points: &mut [Point], verbs: &[Verb]) {
+        if let Some(entry) = self.entries.get(index) {
+            let metrics = &entry.metrics;
+            self.hinter
+                .hint(metrics.zones(glyph_id), metrics.size, points, verbs);
+        }
+    }
+}
+
+/// Nearly horizontal run of an outline.
+#[derive(Copy, Clone)]
+struct Segment {
+    y: f32,
+    min_x: f32,
+    max_x: f32,
+    /// True if the ink lies below the segment.
+    top: bool,
+}
+
+/// Group of segments at the same position that is fitted as a unit.
+#[derive(Copy, Clone)]
+struct Edge {
+    y: f32,
+    min_x: f32,
+    max_x: f32,
+    top: bool,
+    fitted: f32,
+    state: u8,
+    link: Option<usize>,
+}
+
+/// Retained buffers for hinting glyphs.
+#[derive(Default)]
+struct Hinter {
+    on_curve: Vec<bool>,
+    contours: Vec<(usize, usize)>,
+    segments: Vec<Segment>,
+    edges: Vec<Edge>,
+    map: Vec<(f32, f32)>,
+}
+
+impl Hinter {
+    fn hint(&mut self, zones: &[BlueZone], size: f32, points: &mut [Point], verbs: &[Verb]) {
+        if !classify(verbs, points.len(), &mut self.on_curve, &mut self.contours) {
+            return;
+        }
+        self.find_segments(points);
+        self.find_edges();
+        self.link_stems(size * 0.3);
+        self.fit_edges(zones);
+        self.map.clear();
+        for edge in &self.edges {
+            if self.map.last().map(|last| last.0) != Some(edge.y) {
+                self.map.push((edge.y, edge.fitted));
+            }
+        }
+        for p in points.iter_mut() {
+            p.y = interpolate(&self.map, p.y);
+        }
+    }
+
+    /// Collects runs of nearly horizontal lines between consecutive points
+    /// of each contour.
+    fn find_segments(&mut self, points: &[Point]) {
+        self.segments.clear();
+        let mut area = 0.;
+        for &(start, end) in &self.contours {
+            let mut prev = points[end - 1];
+            for p in &points[start..end] {
+                area += (p.y - prev.y) * (p.x + prev.x);
+                prev = *p;
+            }
+        }
+        let clockwise = area < 0.;
+        for &(start, end) in &self.contours {
+            let points = &points[start..end];
+            let n = points.len();
+            if n < 2 {
+                continue;
+            }
+            let direction = |i: usize| -> i8 {
+                let a = points[i];
+                let b = points[(i + 1) % n];
+                let (dx, dy) = (b.x - a.x, b.y - a.y);
+                if dx != 0. && dy.abs() * 14. <= dx.abs() {
+                    if dx > 0. {
+                        1
+                    } else {
+                        -1
+                    }
+                } else {
+                    0
+                }
+            };
+            // Start at a change of direction so that no run wraps around.
+            let first = match (0..n).find(|&i| direction(i) != direction((i + n - 1) % n)) {
+                Some(first) => first,
+                _ => continue,
+            };
+            let mut i = 0;
+            while i < n {
+                let dir = direction((first + i) % n);
+                if dir == 0 {
+                    i += 1;
+                    continue;
+                }
+                let mut j = i;
+                while j < n && direction((first + j) % n) == dir {
+                    j += 1;
+                }
+                let mut sum = 0.;
+                let mut min_x = f32::MAX;
+                let mut max_x = f32::MIN;
+                for k in i..=j {
+                    let index = (first + k) % n;
+                    let p = points[index];
+                    sum += p.y;
+                    min_x = min_x.min(p.x);
+                    max_x = max_x.max(p.x);
+                }
+                self.segments.push(Segment {
+                    y: sum / (j - i + 1) as f32,
+                    min_x,
+                    max_x,
+                    top: (dir > 0) == clockwise,
+                });
+                i = j;
+            }
+        }
+    }
+
+    /// Merges segments with the same orientation at nearly the same
+    /// position into edges.
+    fn find_edges(&mut self) {
+        self.edges.clear();
+        self.segments
+            .sort_unstable_by(|a, b| a.y.partial_cmp(&b.y).unwrap_or(core::cmp::Ordering::Equal));
+        for segment in &self.segments {
+            let edge = self
+                .edges
+                .iter_mut()
+                .rev()
+                .find(|edge| edge.top == segment.top)
+                .filter(|edge| segment.y - edge.y < EDGE_FUZZ);
+            match edge {
+                Some(edge) => {
+                    edge.min_x = edge.min_x.min(segment.min_x);
+                    edge.max_x = edge.max_x.max(segment.max_x);
+                }
+                _ => self.edges.push(Edge {
+                    y: segment.y,
+                    min_x: segment.min_x,
+                    max_x: segment.max_x,
+                    top: segment.top,
+                    fitted: segment.y,
+                    state: UNFITTED,
+                    link: None,
+                }),
+            }
+        }
+    }
+
+    /// Links pairs of edges that bound the ink of a horizontal stem.
+    fn link_stems(&mut self, max_width: f32) {
+        let edges = &mut self.edges;
+        let best = |edges: &[Edge], i: usize| -> Option<usize> {
+            let edge = &edges[i];
+            let mut best = None;
+            let mut best_width = max_width;
+            for (j, other) in edges.iter().enumerate() {
+                if other.top == edge.top {
+                    continue;
+                }
+                let width = if edge.top {
+                    edge.y - other.y
+                } else {
+                    other.y - edge.y
+                };
+                let overlap = edge.max_x.min(other.max_x) - edge.min_x.max(other.min_x);
+                if width > 0. && width < best_width && overlap > 0. {
+                    best_width = width;
+                    best = Some(j);
+                }
+            }
+            best
+        };
+        for i in 0..edges.len() {
+            if let Some(j) = best(edges, i) {
+                if best(edges, j) == Some(i) {
+                    edges[i].link = Some(j);
+                }
+            }
+        }
+    }
+
+    /// Fits the edges to blue zones, then rounds the widths and positions of
+    /// stems and finally interpolates the remaining edges.
+    fn fit_edges(&mut self, zones: &[BlueZone]) {
+        let edges = &mut self.edges;
+        for edge in edges.iter_mut() {
+            let top = edge.top;
+            let mut best = f32::MAX;
+            for zone in zones.iter().filter(|zone| zone.top == top) {
+                let lo = zone.reference.min(zone.overshoot) - BLUE_FUZZ;
+                let hi = zone.reference.max(zone.overshoot) + BLUE_FUZZ;
+                if edge.y < lo || edge.y > hi {
+                    continue;
+                }
+                let to_reference = (edge.y - zone.reference).abs();
+                let to_overshoot = (edge.y - zone.overshoot).abs();
+                let distance = to_reference.min(to_overshoot);
+                if distance < best {
+                    best = distance;
+                    edge.state = BLUE;
+                    edge.fitted = if to_reference <= to_overshoot {
+                        zone.fitted_reference
+                    } else {
+                        zone.fitted_overshoot
+                    };
+                }
+            }
+        }
+        for bottom in 0..edges.len() {
+            // Edges are sorted, so the linked edge of a bottom edge lies
+            // above it.
+            let top = match edges[bottom].link {
+                Some(top) if top > bottom => top,
+                _ => continue,
+            };
+            let width = edges[top].y - edges[bottom].y;
+            let fitted_width = width.round().max(1.);
+            match (edges[bottom].state, edges[top].state) {
+                (BLUE, BLUE) => {}
+                (BLUE, _) => {
+                    edges[top].fitted = edges[bottom].fitted + fitted_width;
+                    edges[top].state = STEM;
+                }
+                (_, BLUE) => {
+                    edges[bottom].fitted = edges[top].fitted - fitted_width;
+                    edges[bottom].state = STEM;
+                }
+                _ => {
+                    let center = (edges[bottom].y + edges[top].y) * 0.5;
+                    let fitted = (center - fitted_width * 0.5).round();
+                    edges[bottom].fitted = fitted;
+                    edges[top].fitted = fitted + fitted_width;
+                    edges[bottom].state = STEM;
+                    edges[top].state = STEM;
+                }
+            }
+        }
+        // Drop fitted edges that would change the order of the outline.
+        let mut last = f32::MIN;
+        for edge in edges.iter_mut() {
+            if edge.state != UNFITTED {
+                if edge.fitted < last {
+                    edge.state = UNFITTED;
+                } else {
+                    last = edge.fitted;
+                }
+            }
+        }
+        for i in 0..edges.len() {
+            if edges[i].state != UNFITTED {
+                continue;
+            }
+            let y = edges[i].y;
+            let prev = edges[..i].iter().rev().find(|e| e.state != UNFITTED);
+            let next = edges[i + 1..].iter().find(|e| e.state != UNFITTED);
+            let fitted = match (prev, next) {
+                (Some(a), Some(b)) if b.y > a.y => {
+                    a.fitted + (y - a.y) * (b.fitted - a.fitted) / (b.y - a.y)
+                }
+                (Some(a), _) => y + a.fitted - a.y,
+                (_, Some(b)) => y + b.fitted - b.y,
+                _ => y.round(),
+            };
+            edges[i].fitted = fitted;
+        }
+    }
+}
+
+/// Maps a coordinate through a sorted list of original and fitted pairs,
+/// interpolating between pairs and shifting beyond the ends.
+fn interpolate(map: &[(f32, f32)], y: f32) -> f32 {
+    let (first, last) = match (map.first(), map.last()) {
+        (Some(first), Some(last)) => (*first, *last),
+        _ => return y,
+    };
+    if y <= first.0 {
+        return y + first.1 - first.0;
+    }
+    if y >= last.0 {
+        return y + last.1 - last.0;
+    }
+    for pair in map.windows(2) {
+        let ((y0, f0), (y1, f1)) = (pair[0], pair[1]);
+        if y <= y1 {
+            return f0 + (y - y0) * (f1 - f0) / (y1 - y0);
+        }
+    }
+    y
+}
+
+/// Computes the on-curve flags for each point and the point ranges of each
+/// contour. Returns false if the verbs do not match the points.
+fn classify(
+    verbs: &[Verb],
+    len: usize,
+    on_curve: &mut Vec<bool>,
+    contours: &mut Vec<(usize, usize)>,
+) -> bool {
+    on_curve.clear();
+    contours.clear();
+    let mut start = 0;
+    for verb in verbs {
+        match verb {
+            Verb::MoveTo => {
+                if on_curve.len() > start {
+                    contours.push((start, on_curve.len()));
+                }
+                start = on_curve.len();
+                on_curve.push(true);
+            }
+            Verb::LineTo => on_curve.push(true),
+            Verb::QuadTo => on_curve.extend_from_slice(&[false, true]),
+            Verb::CurveTo => on_curve.extend_from_slice(&[false, false, true]),
+            Verb::Close => {
+                if on_curve.len() > start {
+                    contours.push((start, on_curve.len()));
+                }
+                start = on_curve.len();
+            }
+        }
+    }
+    if on_curve.len() > start {
+        contours.push((start, on_curve.len()));
+    }
+    on_curve.len() == len
+}
+
+/// Returns the highest or lowest position of an outline and whether the
+/// feature at that position is flat.
+fn extremum(
+    points: &[Point],
+    on_curve: &[bool],
+    contours: &[(usize, usize)],
+    top: bool,
+    threshold: f32,
+) -> Option<(f32, bool)> {
+    let mut best: Option<(usize, usize, usize)> = None;
+    for &(start, end) in contours {
+        for i in start..end {
+            let better = match best {
+                Some((b, ..)) if top => points[i].y > points[b].y,
+                Some((b, ..)) => points[i].y < points[b].y,
+                _ => true,
+            };
+            if better {
+                best = Some((i, start, end));
+            }
+        }
+    }
+    let (i, start, end) = best?;
+    let p = points[i];
+    let prev = if i == start { end - 1 } else { i - 1 };
+    let next = if i + 1 == end { start } else { i + 1 };
+    let is_flat = on_curve[i]
+        && [prev, next].iter().any(|&j| {
+            let q = points[j];
+            on_curve[j] && (q.y - p.y).abs() <= threshold && (q.x - p.x).abs() > threshold
+        });
+    Some((p.y, is_flat))
+}
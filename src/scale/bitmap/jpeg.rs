@@ -0,0 +1,589 @@
+//! Baseline JPEG decoder.
+
+const SOI: u8 = 0xD8;
+const EOI: u8 = 0xD9;
+const SOS: u8 = 0xDA;
+const DQT: u8 = 0xDB;
+const DHT: u8 = 0xC4;
+const DRI: u8 = 0xDD;
+const APP14: u8 = 0xEE;
+
+/// Natural order of coefficients in zigzag sequence.
+const ZIGZAG: [u8; 64] = [
+    0, 1, 8, 16, 9, 2, 3, 10, 17, 24, 32, 25, 18, 11, 4, 5, 12, 19, 26, 33, 40, 48, 41, 34, 27, 20,
+    13, 6, 7, 14, 21, 28, 35, 42, 49, 56, 57, 50, 43, 36, 29, 22, 15, 23, 30, 37, 44, 51, 58, 59,
+    52, 45, 38, 31, 39, 46, 53, 60, 61, 54, 47, 55, 62, 63,
+];
+
+/// The JPEG frame header.
+#[derive(Copy, Clone, Debug)]
+pub struct Header {
+    pub width: u32,
+    pub height: u32,
+    pub components: u8,
+}
+
+impl Header {
+    /// Attempts to decode a JPEG frame header from the specified buffer.
+    pub fn decode(data: &[u8]) -> Option<Self> {
+        let mut markers = Markers::new(data)?;
+        while let Some((marker, segment)) = markers.next() {
+            match marker {
+                0xC0..=0xCF if marker != DHT && marker != 0xC8 && marker != 0xCC => {
+                    return Some(Self {
+                        height: get_u16be(segment, 1)? as u32,
+                        width: get_u16be(segment, 3)? as u32,
+                        components: *segment.get(5)?,
+                    });
+                }
+                SOS | EOI => break,
+                _ => {}
+            }
+        }
+        None
+    }
+}
+
+/// Returns true if the specified buffer might represent a JPEG image.
+pub fn check_signature(data: &[u8]) -> bool {
+    data.len() >= 3 && data[0] == 0xFF && data[1] == SOI && data[2] == 0xFF
+}
+
+/// Decodes a baseline JPEG image into 8-bit RGBA.
+pub fn decode(data: &[u8], scratch: &mut Vec<u8>, target: &mut [u8]) -> Option<(u32, u32)> {
+    let mut frame = Frame::new();
+    let mut markers = Markers::new(data)?;
+    while let Some((marker, segment)) = markers.next() {
+        match marker {
+            // Baseline and extended sequential Huffman.
+            0xC0 | 0xC1 => frame.read_header(segment, scratch)?,
+            // Progressive, lossless, hierarchical and arithmetic coding.
+            0xC2..=0xCF if marker != DHT && marker != 0xCC => return None,
+            DHT => frame.read_huffman(segment)?,
+            DQT => frame.read_quant(segment)?,
+            DRI => frame.restart_interval = get_u16be(segment, 0)? as usize,
+            APP14 if segment.starts_with(b"Adobe") => {
+                frame.adobe_transform = segment.get(11).copied();
+            }
+            SOS => {
+                let end = frame.read_scan(segment, markers.remaining(), scratch)?;
+                markers.skip(end);
+            }
+            EOI => break,
+            _ => {}
+        }
+    }
+    if frame.components.is_empty() {
+        return None;
+    }
+    let (w, h) = (frame.width, frame.height);
+    if target.len() < w * h * 4 {
+        return None;
+    }
+    frame.emit(scratch, target)?;
+    Some((w as u32, h as u32))
+}
+
+/// Iterator over the marker segments of a JPEG stream.
+struct Markers<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Markers<'a> {
+    fn new(data: &'a [u8]) -> Option<Self> {
+        if !check_signature(data) {
+            return None;
+        }
+        Some(Self { data, pos: 2 })
+    }
+
+    fn remaining(&self) -> &'a [u8] {
+        self.data.get(self.pos..).unwrap_or(&[])
+    }
+
+    fn skip(&mut self, len: usize) {
+        self.pos += len;
+    }
+
+    fn next(&mut self) -> Option<(u8, &'a [u8])> {
+        let d = self.data;
+        while self.pos + 1 < d.len() {
+            if d[self.pos] != 0xFF {
+                self.pos += 1;
+                continue;
+            }
+            let marker = d[self.pos + 1];
+            match marker {
+                // Fill bytes, stuffed zeros and restart markers in entropy
+                // coded data.
+                0xFF => {
+                    self.pos += 1;
+                    continue;
+                }
+                0x00 | 0x01 | 0xD0..=0xD7 => {
+                    self.pos += 2;
+                    continue;
+                }
+                SOI | EOI => {
+                    self.pos += 2;
+                    return Some((marker, &[]));
+                }
+                _ => {}
+            }
+            let len = get_u16be(d, self.pos + 2)? as usize;
+            let segment = d.get(self.pos + 4..self.pos + 2 + len)?;
+            self.pos += 2 + len;
+            return Some((marker, segment));
+        }
+        None
+    }
+}
+
+#[derive(Copy, Clone, Default)]
+struct Component {
+    id: u8,
+    h: usize,
+    v: usize,
+    quant: usize,
+    dc_table: usize,
+    ac_table: usize,
+    pred: i32,
+    /// Offset of the sample plane in the scratch buffer.
+    offset: usize,
+    /// Row stride of the sample plane.
+    stride: usize,
+}
+
+struct Frame {
+    width: usize,
+    height: usize,
+    components: Vec<Component>,
+    h_max: usize,
+    v_max: usize,
+    mcus_x: usize,
+    mcus_y: usize,
+    quant: [[u16; 64]; 4],
+    dc: [Huffman; 4],
+    ac: [Huffman; 4],
+    restart_interval: usize,
+    adobe_transform: Option<u8>,
+}
+
+impl Frame {
+    fn new() -> Self {
+        Self {
+            width: 0,
+            height: 0,
+            components: Vec::new(),
+            h_max: 1,
+            v_max: 1,
+            mcus_x: 0,
+            mcus_y: 0,
+            quant: [[1; 64]; 4],
+            dc: Default::default(),
+            ac: Default::default(),
+            restart_interval: 0,
+            adobe_transform: None,
+        }
+    }
+
+    fn read_header(&mut self, d: &[u8], scratch: &mut Vec<u8>) -> Option<()> {
+        if !self.components.is_empty() || *d.first()? != 8 {
+            return None;
+        }
+        self.height = get_u16be(d, 1)? as usize;
+        self.width = get_u16be(d, 3)? as usize;
+        let count = *d.get(5)? as usize;
+        if self.width == 0 || self.height == 0 || (count != 1 && count != 3) {
+            return None;
+        }
+        for i in 0..count {
+            let c = d.get(6 + i * 3..9 + i * 3)?;
+            let (h, v) = ((c[1] >> 4) as usize, (c[1] & 0xF) as usize);
+            if h == 0 || h > 4 || v == 0 || v > 4 || c[2] > 3 {
+                return None;
+            }
+            self.h_max = self.h_max.max(h);
+            self.v_max = self.v_max.max(v);
+            self.components.push(Component {
+                id: c[0],
+                h,
+                v,
+                quant: c[2] as usize,
+                ..Default::default()
+            });
+        }
+        self.mcus_x = self.width.div_ceil(8 * self.h_max);
+        self.mcus_y = self.height.div_ceil(8 * self.v_max);
+        scratch.clear();
+        for c in &mut self.components {
+            c.offset = scratch.len();
+            c.stride = self.mcus_x * c.h * 8;
+            scratch.resize(c.offset + c.stride * self.mcus_y * c.v * 8, 0);
+        }
+        Some(())
+    }
+
+    fn read_quant(&mut self, mut d: &[u8]) -> Option<()> {
+        while !d.is_empty() {
+            let precision = d[0] >> 4;
+            let table = self.quant.get_mut((d[0] & 0xF) as usize)?;
+            if precision == 0 {
+                for (q, v) in table.iter_mut().zip(d.get(1..65)?) {
+                    *q = *v as u16;
+                }
+                d = &d[65..];
+            } else {
+                for (i, q) in table.iter_mut().enumerate() {
+                    *q = get_u16be(d, 1 + i * 2)?;
+                }
+                d = d.get(129..)?;
+            }
+        }
+        Some(())
+    }
+
+    fn read_huffman(&mut self, mut d: &[u8]) -> Option<()> {
+        while !d.is_empty() {
+            let class = d[0] >> 4;
+            let index = (d[0] & 0xF) as usize;
+            let counts = d.get(1..17)?;
+            let len = counts.iter().map(|&n| n as usize).sum::<usize>();
+            let values = d.get(17..17 + len)?;
+            let table = match class {
+                0 => self.dc.get_mut(index)?,
+                1 => self.ac.get_mut(index)?,
+                _ => return None,
+            };
+            table.build(counts, values)?;
+            d = &d[17 + len..];
+        }
+        Some(())
+    }
+
+    /// Decodes the entropy coded data of a scan and returns the number of
+    /// bytes consumed.
+    fn read_scan(&mut self, d: &[u8], data: &[u8], scratch: &mut [u8]) -> Option<usize> {
+        let count = *d.first()? as usize;
+        if count == 0 || count > 4 {
+            return None;
+        }
+        let mut scan = [0usize; 4];
+        for (i, slot) in scan.iter_mut().enumerate().take(count) {
+            let id = *d.get(1 + i * 2)?;
+            let tables = *d.get(2 + i * 2)?;
+            let index = self.components.iter().position(|c| c.id == id)?;
+            let c = &mut self.components[index];
+            c.dc_table = (tables >> 4) as usize & 3;
+            c.ac_table = (tables & 0xF) as usize & 3;
+            c.pred = 0;
+            *slot = index;
+        }
+        let scan = &scan[..count];
+        let mut bits = Bits::new(data);
+        let mut block = [0i32; 64];
+        let mut pixels = [0u8; 64];
+        let table = idct_table();
+        // Interleaved scans are composed of MCUs while a single component
+        // scan covers only the blocks of that component.
+        let (units_x, units_y) = if count == 1 {
+            let c = &self.components[scan[0]];
+            let w = (self.width * c.h).div_ceil(self.h_max);
+            let h = (self.height * c.v).div_ceil(self.v_max);
+            (w.div_ceil(8), h.div_ceil(8))
+        } else {
+            (self.mcus_x, self.mcus_y)
+        };
+        let mut unit = 0;
+        for y in 0..units_y {
+            for x in 0..units_x {
+                if self.restart_interval != 0 && unit != 0 && unit % self.restart_interval == 0 {
+                    bits.restart();
+                    for &index in scan {
+                        self.components[index].pred = 0;
+                    }
+                }
+                unit += 1;
+                for &index in scan {
+                    let mut c = self.components[index];
+                    let (h, v) = if count == 1 { (1, 1) } else { (c.h, c.v) };
+                    for by in 0..v {
+                        for bx in 0..h {
+                            c.pred = self.decode_block(&mut bits, &c, &mut block)?;
+                            idct(&block, &table, &mut pixels);
+                            let row = (y * v + by) * 8;
+                            let col = (x * h + bx) * 8;
+                            for (i, src) in pixels.chunks(8).enumerate() {
+                                let start = c.offset + (row + i) * c.stride + col;
+                                scratch.get_mut(start..start + 8)?.copy_from_slice(src);
+                            }
+                        }
+                    }
+                    self.components[index].pred = c.pred;
+                }
+            }
+        }
+        Some(bits.pos)
+    }
+
+    /// Decodes a single block of coefficients and returns the new DC
+    /// predictor.
+    fn decode_block(&self, bits: &mut Bits, c: &Component, block: &mut [i32; 64]) -> Option<i32> {
+        let q = &self.quant[c.quant];
+        *block = [0; 64];
+        // Baseline DC differences have at most 11 bits. AC sizes are
+        // limited to 15 bits by the low nibble of the run/size symbol.
+        let t = self.dc[c.dc_table].decode(bits).filter(|t| *t <= 11)?;
+        let pred = c.pred + extend(bits.get(t), t);
+        block[0] = pred * q[0] as i32;
+        let ac = &self.ac[c.ac_table];
+        let mut k = 1;
+        while k < 64 {
+            let rs = ac.decode(bits)?;
+            let (r, s) = ((rs >> 4) as usize, rs & 0xF);
+            if s == 0 {
+                if r != 15 {
+                    break;
+                }
+                k += 16;
+                continue;
+            }
+            k += r;
+            if k > 63 {
+                return None;
+            }
+            block[ZIGZAG[k] as usize] = extend(bits.get(s), s) * q[k] as i32;
+            k += 1;
+        }
+        Some(pred)
+    }
+
+    /// Upsamples the component planes and converts them to RGBA.
+    fn emit(&self, scratch: &[u8], target: &mut [u8]) -> Option<()> {
+        let (w, h) = (self.width, self.height);
+        let sample = |c: &Component, x: usize, y: usize| {
+            let x = x * c.h / self.h_max;
+            let y = y * c.v / self.v_max;
+            scratch[c.offset + y * c.stride + x] as f32
+        };
+        let c = &self.components;
+        let is_rgb = c.len() == 3 && self.adobe_transform == Some(0);
+        let mut out = 0;
+        for y in 0..h {
+            for x in 0..w {
+                let rgb = if c.len() == 1 {
+                    let l = sample(&c[0], x, y);
+                    [l, l, l]
+                } else if is_rgb {
+                    [
+                        sample(&c[0], x, y),
+                        sample(&c[1], x, y),
+                        sample(&c[2], x, y),
+                    ]
+                } else {
+                    let l = sample(&c[0], x, y);
+                    let cb = sample(&c[1], x, y) - 128.;
+                    let cr = sample(&c[2], x, y) - 128.;
+                    [
+                        l + 1.402 * cr,
+                        l - 0.344_136 * cb - 0.714_136 * cr,
+                        l + 1.772 * cb,
+                    ]
+                };
+                let dst = target.get_mut(out..out + 4)?;
+                for (d, s) in dst.iter_mut().zip(&rgb) {
+                    *d = (s + 0.5).clamp(0., 255.) as u8;
+                }
+                dst[3] = 255;
+                out += 4;
+            }
+        }
+        Some(())
+    }
+}
+
+/// Huffman decoding table in the form described in annex F of the
+/// specification.
+#[derive(Copy, Clone)]
+struct Huffman {
+    max_code: [i32; 18],
+    val_ptr: [i32; 17],
+    min_code: [i32; 17],
+    values: [u8; 256],
+}
+
+impl Default for Huffman {
+    fn default() -> Self {
+        Self {
+            max_code: [-1; 18],
+            val_ptr: [0; 17],
+            min_code: [0; 17],
+            values: [0; 256],
+        }
+    }
+}
+
+impl Huffman {
+    fn build(&mut self, counts: &[u8], values: &[u8]) -> Option<()> {
+        if values.len() > 256 {
+            return None;
+        }
+        let mut code = 0;
+        let mut k = 0;
+        for l in 1..=16 {
+            let count = counts[l - 1] as i32;
+            self.val_ptr[l] = k;
+            self.min_code[l] = code;
+            code += count;
+            k += count;
+            self.max_code[l] = if count != 0 { code - 1 } else { -1 };
+            code <<= 1;
+        }
+        self.max_code[17] = i32::MAX;
+        self.values[..values.len()].copy_from_slice(values);
+        Some(())
+    }
+
+    fn decode(&self, bits: &mut Bits) -> Option<u32> {
+        let mut code = bits.get(1) as i32;
+        let mut l = 1;
+        while code > self.max_code[l] {
+            code = (code << 1) | bits.get(1) as i32;
+            l += 1;
+            if l > 16 {
+                return None;
+            }
+        }
+        let index = self.val_ptr[l] + code - self.min_code[l];
+        self.values.get(index as usize).map(|&v| v as u32)
+    }
+}
+
+/// Bit reader for entropy coded data.
+struct Bits<'a> {
+    data: &'a [u8],
+    pos: usize,
+    acc: u32,
+    count: u32,
+    marker: bool,
+}
+
+impl<'a> Bits<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        Self {
+            data,
+            pos: 0,
+            acc: 0,
+            count: 0,
+            marker: false,
+        }
+    }
+
+    fn fill(&mut self) {
+        while self.count <= 24 {
+            let mut byte = 0;
+            if !self.marker && self.pos < self.data.len() {
+                byte = self.data[self.pos];
+                if byte == 0xFF {
+                    if self.data.get(self.pos + 1) == Some(&0) {
+                        self.pos += 2;
+                    } else {
+                        self.marker = true;
+                        byte = 0;
+                    }
+                } else {
+                    self.pos += 1;
+                }
+            }
+            self.acc |= (byte as u32) << (24 - self.count);
+            self.count += 8;
+        }
+    }
+
+    fn get(&mut self, n: u32) -> u32 {
+        if n == 0 {
+            return 0;
+        }
+        if self.count < n {
+            self.fill();
+        }
+        let v = self.acc >> (32 - n);
+        self.acc <<= n;
+        self.count -= n;
+        v
+    }
+
+    /// Discards buffered bits and skips the next restart marker.
+    fn restart(&mut self) {
+        self.acc = 0;
+        self.count = 0;
+        if !self.marker {
+            while self.pos < self.data.len() && self.data[self.pos] != 0xFF {
+                self.pos += 1;
+            }
+        }
+        let d = self.data;
+        while self.pos + 1 < d.len() && d[self.pos] == 0xFF && d[self.pos + 1] == 0xFF {
+            self.pos += 1;
+        }
+        if self.pos + 1 < d.len() && (0xD0..=0xD7).contains(&d[self.pos + 1]) {
+            self.pos += 2;
+        }
+        self.marker = false;
+    }
+}
+
+fn extend(v: u32, n: u32) -> i32 {
+    if n == 0 {
+        0
+    } else if v < 1 << (n - 1) {
+        v as i32 - (1 << n) + 1
+    } else {
+        v as i32
+    }
+}
+
+fn idct_table() -> [[f32; 8]; 8] {
+    let mut table = [[0f32; 8]; 8];
+    for (x, row) in table.iter_mut().enumerate() {
+        for (u, c) in row.iter_mut().enumerate() {
+            let scale = if u == 0 {
+                core::f32::consts::FRAC_1_SQRT_2
+            } else {
+                1.
+            };
+            let angle = (2 * x + 1) as f32 * u as f32 * core::f32::consts::PI / 16.;
+            *c = scale * angle.cos() * 0.5;
+        }
+    }
+    table
+}
+
+/// Separable inverse DCT producing level shifted samples.
+fn idct(block: &[i32; 64], table: &[[f32; 8]; 8], out: &mut [u8; 64]) {
+    let mut tmp = [0f32; 64];
+    for y in 0..8 {
+        for u in 0..8 {
+            let mut sum = 0.;
+            for v in 0..8 {
+                sum += table[y][v] * block[v * 8 + u] as f32;
+            }
+            tmp[y * 8 + u] = sum;
+        }
+    }
+    for y in 0..8 {
+        for x in 0..8 {
+            let mut sum = 128.5;
+            for u in 0..8 {
+                sum += table[x][u] * tmp[y * 8 + u];
+            }
+            out[y * 8 + x] = sum.clamp(0., 255.) as u8;
+        }
+    }
+}
+
+fn get_u16be(buf: &[u8], offset: usize) -> Option<u16> {
+    let b = buf.get(offset..offset + 2)?;
+    Some((b[0] as u16) << 8 | b[1] as u16)
+}
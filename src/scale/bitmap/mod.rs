@@ -2,7 +2,9 @@
 
 #![allow(dead_code)]
 
+mod jpeg;
 mod png;
+mod tiff;
 
 /// Decodes a PNG image.
 pub fn decode_png(data: &[u8], scratch: &mut Vec<u8>, target: &mut [u8]) -> Option<(u32, u32)> {
@@ -11,6 +13,26 @@ pub fn decode_png(data: &[u8], scratch: &mut Vec<u8>, target: &mut [u8]) -> Opti
         .ok()
 }
 
+/// Decodes a baseline JPEG image.
+pub fn decode_jpeg(data: &[u8], scratch: &mut Vec<u8>, target: &mut [u8]) -> Option<(u32, u32)> {
+    jpeg::decode(data, scratch, target)
+}
+
+/// Decodes an uncompressed or PackBits compressed TIFF image.
+pub fn decode_tiff(data: &[u8], scratch: &mut Vec<u8>, target: &mut [u8]) -> Option<(u32, u32)> {
+    tiff::decode(data, scratch, target)
+}
+
+/// Returns the dimensions of a JPEG image.
+pub fn jpeg_size(data: &[u8]) -> Option<(u32, u32)> {
+    jpeg::Header::decode(data).map(|h| (h.width, h.height))
+}
+
+/// Returns the dimensions of a TIFF image.
+pub fn tiff_size(data: &[u8]) -> Option<(u32, u32)> {
+    tiff::Header::decode(data).map(|h| (h.width, h.height))
+}
+
 pub fn blit(
     mask: &[u8],
     mask_width: u32,
@@ -73,6 +95,41 @@ pub fn blit(
     }
 }
 
+/// Blends a color into the target through an RGBA subpixel mask, using the
+/// coverage of each channel to blend the corresponding color component.
+#[allow(clippy::too_many_arguments)]
+pub fn blit_subpixel(
+    mask: &[u8],
+    mask_width: u32,
+    mask_height: u32,
+    x: i32,
+    y: i32,
+    color: [u8; 4],
+    target: &mut [u8],
+    target_width: u32,
+    target_height: u32,
+) {
+    let (w, h) = (mask_width as i32, mask_height as i32);
+    let (tw, th) = (target_width as i32, target_height as i32);
+    let color_a = color[3] as u32;
+    for sy in 0.max(-y)..h.min(th - y) {
+        for sx in 0.max(-x)..w.min(tw - x) {
+            let s = ((sy * w + sx) * 4) as usize;
+            let d = (((sy + y) * tw + sx + x) * 4) as usize;
+            let src = &mask[s..s + 4];
+            let dst = &mut target[d..d + 4];
+            for i in 0..4 {
+                let a = ((src[i] as u32 * color_a) >> 8).min(255);
+                if a == 0 {
+                    continue;
+                }
+                let c = if i == 3 { 255 } else { color[i] as u32 };
+                dst[i] = (((255 - a) * dst[i] as u32 + a * c) >> 8) as u8;
+            }
+        }
+    }
+}
+
 #[derive(Copy, Clone, PartialEq)]
 pub enum Filter {
     Nearest,
@@ -0,0 +1,250 @@
+//! TIFF decoder for uncompressed and PackBits images.
+
+const WIDTH: u16 = 256;
+const HEIGHT: u16 = 257;
+const BITS_PER_SAMPLE: u16 = 258;
+const COMPRESSION: u16 = 259;
+const PHOTOMETRIC: u16 = 262;
+const STRIP_OFFSETS: u16 = 273;
+const SAMPLES_PER_PIXEL: u16 = 277;
+const ROWS_PER_STRIP: u16 = 278;
+const STRIP_BYTE_COUNTS: u16 = 279;
+const PLANAR_CONFIG: u16 = 284;
+const PREDICTOR: u16 = 317;
+const EXTRA_SAMPLES: u16 = 338;
+
+const NO_COMPRESSION: u32 = 1;
+const PACKBITS: u32 = 32773;
+
+/// The TIFF image header.
+#[derive(Copy, Clone, Debug)]
+pub struct Header {
+    pub width: u32,
+    pub height: u32,
+    pub samples: u32,
+    pub photometric: u32,
+    pub compression: u32,
+    pub predictor: u32,
+    pub rows_per_strip: u32,
+    /// True if the extra sample is premultiplied alpha.
+    pub associated_alpha: bool,
+}
+
+impl Header {
+    /// Attempts to decode the header of the first image from the specified
+    /// buffer.
+    pub fn decode(data: &[u8]) -> Option<Self> {
+        Self::decode_ifd(&Ifd::new(data)?)
+    }
+
+    fn decode_ifd(ifd: &Ifd) -> Option<Self> {
+        let width = ifd.get(WIDTH, 0).filter(|w| *w != 0)?;
+        let height = ifd.get(HEIGHT, 0).filter(|h| *h != 0)?;
+        let samples = ifd.get(SAMPLES_PER_PIXEL, 0).unwrap_or(1);
+        for i in 0..samples {
+            if ifd.get(BITS_PER_SAMPLE, i).unwrap_or(1) != 8 {
+                return None;
+            }
+        }
+        if ifd.get(PLANAR_CONFIG, 0).unwrap_or(1) != 1 {
+            return None;
+        }
+        let photometric = ifd.get(PHOTOMETRIC, 0)?;
+        let color_samples = match photometric {
+            0 | 1 => 1,
+            2 => 3,
+            _ => return None,
+        };
+        if samples < color_samples || samples > color_samples + 1 {
+            return None;
+        }
+        let compression = ifd.get(COMPRESSION, 0).unwrap_or(NO_COMPRESSION);
+        if compression != NO_COMPRESSION && compression != PACKBITS {
+            return None;
+        }
+        let predictor = ifd.get(PREDICTOR, 0).unwrap_or(1);
+        if predictor != 1 && predictor != 2 {
+            return None;
+        }
+        Some(Self {
+            width,
+            height,
+            samples,
+            photometric,
+            compression,
+            predictor,
+            rows_per_strip: ifd.get(ROWS_PER_STRIP, 0).unwrap_or(height).min(height),
+            associated_alpha: ifd.get(EXTRA_SAMPLES, 0) == Some(1),
+        })
+    }
+}
+
+/// Returns true if the specified buffer might represent a TIFF image.
+pub fn check_signature(data: &[u8]) -> bool {
+    data.starts_with(b"II*\0") || data.starts_with(b"MM\0*")
+}
+
+/// Decodes a TIFF image into 8-bit RGBA.
+pub fn decode(data: &[u8], scratch: &mut Vec<u8>, target: &mut [u8]) -> Option<(u32, u32)> {
+    let ifd = Ifd::new(data)?;
+    let header = Header::decode_ifd(&ifd)?;
+    let (w, h) = (header.width as usize, header.height as usize);
+    if target.len() < w * h * 4 {
+        return None;
+    }
+    let spp = header.samples as usize;
+    let row_len = w * spp;
+    let rows_per_strip = (header.rows_per_strip as usize).max(1);
+    let strips = h.div_ceil(rows_per_strip);
+    scratch.clear();
+    for strip in 0..strips as u32 {
+        let offset = ifd.get(STRIP_OFFSETS, strip)? as usize;
+        let len = ifd.get(STRIP_BYTE_COUNTS, strip)? as usize;
+        let src = data.get(offset..offset.checked_add(len)?)?;
+        let rows = rows_per_strip.min(h - strip as usize * rows_per_strip);
+        let start = scratch.len();
+        match header.compression {
+            PACKBITS => unpack_bits(src, scratch, row_len * rows)?,
+            _ => scratch.extend_from_slice(src.get(..row_len * rows)?),
+        }
+        if scratch.len() - start < row_len * rows {
+            return None;
+        }
+    }
+    let rows = scratch.chunks_exact_mut(row_len).take(h);
+    for (row, dst) in rows.zip(target.chunks_exact_mut(w * 4)) {
+        if header.predictor == 2 {
+            for i in spp..row_len {
+                row[i] = row[i].wrapping_add(row[i - spp]);
+            }
+        }
+        for (src, dst) in row.chunks_exact(spp).zip(dst.chunks_exact_mut(4)) {
+            let (color, alpha) = match spp {
+                1 | 2 => {
+                    let mut l = src[0];
+                    if header.photometric == 0 {
+                        l = 255 - l;
+                    }
+                    ([l, l, l], src.get(1).copied())
+                }
+                _ => ([src[0], src[1], src[2]], src.get(3).copied()),
+            };
+            let alpha = alpha.unwrap_or(255);
+            dst[..3].copy_from_slice(&color);
+            dst[3] = alpha;
+            if header.associated_alpha && alpha != 255 {
+                for c in &mut dst[..3] {
+                    *c = match alpha {
+                        0 => 0,
+                        _ => ((*c as u32 * 255 + alpha as u32 / 2) / alpha as u32).min(255) as u8,
+                    };
+                }
+            }
+        }
+    }
+    Some((header.width, header.height))
+}
+
+/// Decompresses PackBits encoded data until the target length is reached.
+fn unpack_bits(mut src: &[u8], dst: &mut Vec<u8>, len: usize) -> Option<()> {
+    let end = dst.len() + len;
+    while dst.len() < end && !src.is_empty() {
+        let n = src[0] as i8;
+        src = &src[1..];
+        if n >= 0 {
+            let count = n as usize + 1;
+            dst.extend_from_slice(src.get(..count)?);
+            src = &src[count..];
+        } else if n != -128 {
+            let count = (1 - n as isize) as usize;
+            let value = *src.first()?;
+            src = &src[1..];
+            dst.resize(dst.len() + count, value);
+        }
+    }
+    dst.truncate(end);
+    Some(())
+}
+
+/// First image file directory in a TIFF stream.
+struct Ifd<'a> {
+    data: &'a [u8],
+    big_endian: bool,
+    offset: usize,
+    len: usize,
+}
+
+impl<'a> Ifd<'a> {
+    fn new(data: &'a [u8]) -> Option<Self> {
+        if !check_signature(data) {
+            return None;
+        }
+        let mut ifd = Self {
+            data,
+            big_endian: data[0] == b'M',
+            offset: 0,
+            len: 0,
+        };
+        ifd.offset = ifd.u32(4)? as usize;
+        ifd.len = ifd.u16(ifd.offset)? as usize;
+        ifd.offset += 2;
+        Some(ifd)
+    }
+
+    /// Returns the value at the specified index for a tag.
+    fn get(&self, tag: u16, index: u32) -> Option<u32> {
+        let mut l = 0;
+        let mut h = self.len;
+        while l < h {
+            let i = l + (h - l) / 2;
+            let entry = self.offset + i * 12;
+            let t = self.u16(entry)?;
+            if tag < t {
+                h = i;
+            } else if tag > t {
+                l = i + 1;
+            } else {
+                let ty = self.u16(entry + 2)?;
+                let count = self.u32(entry + 4)?;
+                if index >= count {
+                    return None;
+                }
+                let size = match ty {
+                    3 => 2,
+                    4 => 4,
+                    _ => return None,
+                };
+                let base = if count as usize * size <= 4 {
+                    entry + 8
+                } else {
+                    self.u32(entry + 8)? as usize
+                };
+                let offset = base + index as usize * size;
+                return match size {
+                    2 => self.u16(offset).map(|v| v as u32),
+                    _ => self.u32(offset),
+                };
+            }
+        }
+        None
+    }
+
+    fn u16(&self, offset: usize) -> Option<u16> {
+        let b = self.data.get(offset..offset + 2)?;
+        Some(if self.big_endian {
+            u16::from_be_bytes([b[0], b[1]])
+        } else {
+            u16::from_le_bytes([b[0], b[1]])
+        })
+    }
+
+    fn u32(&self, offset: usize) -> Option<u32> {
+        let b = self.data.get(offset..offset + 4)?;
+        let b = [b[0], b[1], b[2], b[3]];
+        Some(if self.big_endian {
+            u32::from_be_bytes(b)
+        } else {
+            u32::from_le_bytes(b)
+        })
+    }
+}
@@ -0,0 +1,678 @@
+//! Software rasterizer for color glyph paint graphs.
+
+use super::paint::{Brush, ColorPainter, ColorStop, CompositeMode, Extend, PaintColor};
+use super::Outline;
+use crate::GlyphId;
+use zeno::{Bounds, Fill, Mask, Origin, Placement, Point, Scratch, Transform, Vector};
+
+/// Computes the bounds of a paint graph in device space.
+pub struct BoundsPainter {
+    transforms: Vec<Transform>,
+    clips: Vec<Option<Bounds>>,
+    bounds: Option<Bounds>,
+}
+
+impl BoundsPainter {
+    pub fn new(base: Transform) -> Self {
+        Self {
+            transforms: vec![base],
+            clips: Vec::new(),
+            bounds: None,
+        }
+    }
+
+    /// Returns the pixel aligned placement for the accumulated bounds or
+    /// `None` if the area is unreasonably large.
+    pub fn placement(&self) -> Option<Placement> {
+        let bounds = match self.bounds {
+            Some(bounds) => bounds,
+            _ => return Some(Placement::default()),
+        };
+        let left = bounds.min.x.floor();
+        let bottom = bounds.min.y.floor();
+        let width = (bounds.max.x.ceil() - left) as u32;
+        let height = (bounds.max.y.ceil() - bottom) as u32;
+        if width > 4096 || height > 4096 {
+            return None;
+        }
+        Some(Placement {
+            left: left as i32,
+            top: bottom as i32 + height as i32,
+            width,
+            height,
+        })
+    }
+
+    fn transform(&self) -> Transform {
+        *self.transforms.last().unwrap_or(&Transform::IDENTITY)
+    }
+}
+
+impl ColorPainter for BoundsPainter {
+    fn push_transform(&mut self, transform: Transform) {
+        let t = concat(&self.transform(), &transform);
+        self.transforms.push(t);
+    }
+
+    fn pop_transform(&mut self) {
+        self.transforms.pop();
+    }
+
+    fn push_clip_glyph(&mut self, _glyph_id: GlyphId, outline: &Outline) {
+        let t = self.transform();
+        let bounds = Bounds::from_points(outline.points().iter().map(|p| t.transform_point(*p)));
+        let clip = intersect(self.clips.last().copied().flatten(), bounds);
+        self.clips.push(Some(clip));
+    }
+
+    fn push_clip_box(&mut self, bounds: Bounds) {
+        let bounds = transform_bounds(&self.transform(), &bounds);
+        let clip = intersect(self.clips.last().copied().flatten(), bounds);
+        self.clips.push(Some(clip));
+    }
+
+    fn pop_clip(&mut self) {
+        self.clips.pop();
+    }
+
+    fn fill(&mut self, _brush: &Brush) {
+        // Unbounded fills are limited to the clip region.
+        if let Some(Some(clip)) = self.clips.last() {
+            if clip.is_empty() {
+                return;
+            }
+            self.bounds = Some(match self.bounds {
+                Some(b) => Bounds::new(
+                    Point::new(b.min.x.min(clip.min.x), b.min.y.min(clip.min.y)),
+                    Point::new(b.max.x.max(clip.max.x), b.max.y.max(clip.max.y)),
+                ),
+                None => *clip,
+            });
+        }
+    }
+
+    fn push_layer(&mut self, _mode: CompositeMode) {}
+
+    fn pop_layer(&mut self) {}
+}
+
+/// Rasterizes a paint graph into a premultiplied RGBA image.
+#[derive(Default)]
+pub struct Canvas {
+    placement: Placement,
+    transforms: Vec<Transform>,
+    layers: Vec<Vec<f32>>,
+    modes: Vec<CompositeMode>,
+    layer_count: usize,
+    clips: Vec<Vec<f32>>,
+    clip_count: usize,
+    colors: Vec<[u8; 4]>,
+    foreground: [u8; 4],
+    mask: Vec<u8>,
+    rcx: Scratch,
+    pub(super) even_odd: bool,
+    /// Lookup table applied to the coverage of rasterized outlines.
+    pub(super) gamma: Option<[u8; 256]>,
+}
+
+impl Canvas {
+    /// Prepares the canvas for painting into the area described by the
+    /// placement with the specified palette colors.
+    pub fn begin(
+        &mut self,
+        placement: Placement,
+        base: Transform,
+        colors: impl Iterator<Item = [u8; 4]>,
+        foreground: [u8; 4],
+    ) {
+        self.placement = placement;
+        self.transforms.clear();
+        self.transforms.push(base);
+        self.modes.clear();
+        self.layer_count = 0;
+        self.clip_count = 0;
+        self.colors.clear();
+        self.colors.extend(colors);
+        self.foreground = foreground;
+        self.even_odd = false;
+        self.push_layer(CompositeMode::SrcOver);
+    }
+
+    /// Writes the painted image as 8-bit premultiplied RGBA.
+    pub fn finish(&mut self, target: &mut Vec<u8>) {
+        target.clear();
+        if let Some(layer) = self.layers.first() {
+            target.extend(layer.iter().map(|c| (c.clamp(0., 1.) * 255. + 0.5) as u8));
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.placement.width as usize * self.placement.height as usize
+    }
+
+    fn transform(&self) -> Transform {
+        *self.transforms.last().unwrap_or(&Transform::IDENTITY)
+    }
+
+    /// Returns the premultiplied color for a paint color.
+    fn color(&self, color: &PaintColor) -> [f32; 4] {
+        let c = color
+            .palette_index
+            .and_then(|i| self.colors.get(i as usize).copied())
+            .unwrap_or(self.foreground);
+        let a = c[3] as f32 / 255. * color.alpha;
+        [
+            c[0] as f32 / 255. * a,
+            c[1] as f32 / 255. * a,
+            c[2] as f32 / 255. * a,
+            a,
+        ]
+    }
+
+    /// Returns the premultiplied color at the specified position on a color
+    /// line.
+    fn stop_color(&self, stops: &[ColorStop], extend: Extend, t: f32) -> [f32; 4] {
+        let (first, last) = match (stops.first(), stops.last()) {
+            (Some(first), Some(last)) => (first, last),
+            _ => return [0.; 4],
+        };
+        let range = last.offset - first.offset;
+        let mut t = t;
+        if range > 0. {
+            let x = (t - first.offset) / range;
+            let x = match extend {
+                Extend::Pad => x,
+                Extend::Repeat => x - x.floor(),
+                Extend::Reflect => {
+                    let x = x.rem_euclid(2.);
+                    if x > 1. {
+                        2. - x
+                    } else {
+                        x
+                    }
+                }
+            };
+            t = first.offset + x * range;
+        }
+        if t <= first.offset {
+            return self.color(&first.color);
+        }
+        if t >= last.offset {
+            return self.color(&last.color);
+        }
+        for pair in stops.windows(2) {
+            let (a, b) = (&pair[0], &pair[1]);
+            if t >= a.offset && t <= b.offset {
+                let ca = self.color(&a.color);
+                let cb = self.color(&b.color);
+                let span = b.offset - a.offset;
+                let f = if span > 0. { (t - a.offset) / span } else { 1. };
+                let mut c = [0.; 4];
+                for i in 0..4 {
+                    c[i] = ca[i] + (cb[i] - ca[i]) * f;
+                }
+                return c;
+            }
+        }
+        self.color(&last.color)
+    }
+
+    /// Returns the coverage of the current clip at the specified pixel.
+    fn coverage(&self, index: usize) -> f32 {
+        if self.clip_count == 0 {
+            1.
+        } else {
+            self.clips[self.clip_count - 1][index]
+        }
+    }
+
+    /// Pushes a clip mask, intersecting it with the current clip.
+    fn push_clip_mask(&mut self) {
+        let len = self.len();
+        if self.clips.len() == self.clip_count {
+            self.clips.push(Vec::new());
+        }
+        let mut clip = core::mem::take(&mut self.clips[self.clip_count]);
+        clip.clear();
+        clip.extend(self.mask.iter().map(|&m| m as f32 / 255.));
+        clip.resize(len, 0.);
+        if self.clip_count != 0 {
+            for (c, p) in clip.iter_mut().zip(&self.clips[self.clip_count - 1]) {
+                *c *= p;
+            }
+        }
+        self.clips[self.clip_count] = clip;
+        self.clip_count += 1;
+    }
+
+    /// Renders the specified path in device space into the mask buffer.
+    fn render_mask(&mut self, outline: &Outline, transform: Transform) {
+        let p = self.placement;
+        self.mask.clear();
+        self.mask.resize(self.len(), 0);
+        Mask::with_scratch(outline.path(), &mut self.rcx)
+            .transform(Some(transform))
+            .style(if self.even_odd {
+                Fill::EvenOdd
+            } else {
+                Fill::NonZero
+            })
+            .origin(Origin::BottomLeft)
+            .size(p.width, p.height)
+            .offset(Vector::new(
+                -p.left as f32,
+                -(p.top - p.height as i32) as f32,
+            ))
+            .render_into(&mut self.mask, None);
+        if let Some(table) = &self.gamma {
+            super::lcd::apply_table(table, &mut self.mask);
+        }
+    }
+}
+
+impl ColorPainter for Canvas {
+    fn push_transform(&mut self, transform: Transform) {
+        let t = concat(&self.transform(), &transform);
+        self.transforms.push(t);
+    }
+
+    fn pop_transform(&mut self) {
+        self.transforms.pop();
+    }
+
+    fn push_clip_glyph(&mut self, _glyph_id: GlyphId, outline: &Outline) {
+        self.render_mask(outline, self.transform());
+        self.push_clip_mask();
+    }
+
+    fn push_clip_box(&mut self, bounds: Bounds) {
+        let mut outline = Outline::new();
+        outline.begin_layer(None);
+        outline.move_to(bounds.min);
+        outline.line_to(Point::new(bounds.max.x, bounds.min.y));
+        outline.line_to(bounds.max);
+        outline.line_to(Point::new(bounds.min.x, bounds.max.y));
+        outline.close();
+        outline.finish();
+        self.render_mask(&outline, self.transform());
+        self.push_clip_mask();
+    }
+
+    fn pop_clip(&mut self) {
+        self.clip_count = self.clip_count.saturating_sub(1);
+    }
+
+    fn fill(&mut self, brush: &Brush) {
+        if self.layer_count == 0 {
+            return;
+        }
+        let p = self.placement;
+        let inverse = match invert(&self.transform()) {
+            Some(inverse) => inverse,
+            _ => return,
+        };
+        let solid = match brush {
+            Brush::Solid(color) => Some(self.color(color)),
+            _ => None,
+        };
+        let mut layer = core::mem::take(&mut self.layers[self.layer_count - 1]);
+        let bottom = p.top - p.height as i32;
+        for y in 0..p.height as usize {
+            // Rows are stored from top to bottom.
+            let dy = bottom as f32 + (p.height as usize - y) as f32 - 0.5;
+            for x in 0..p.width as usize {
+                let index = y * p.width as usize + x;
+                let coverage = self.coverage(index);
+                if coverage == 0. {
+                    continue;
+                }
+                let c = match solid {
+                    Some(c) => c,
+                    None => {
+                        let dx = p.left as f32 + x as f32 + 0.5;
+                        let point = inverse.transform_point(Point::new(dx, dy));
+                        self.brush_color(brush, point)
+                    }
+                };
+                let dst = &mut layer[index * 4..index * 4 + 4];
+                let a = c[3] * coverage;
+                for i in 0..4 {
+                    dst[i] = c[i] * coverage + dst[i] * (1. - a);
+                }
+            }
+        }
+        self.layers[self.layer_count - 1] = layer;
+    }
+
+    fn push_layer(&mut self, mode: CompositeMode) {
+        let len = self.len() * 4;
+        if self.layers.len() == self.layer_count {
+            self.layers.push(Vec::new());
+        }
+        let layer = &mut self.layers[self.layer_count];
+        layer.clear();
+        layer.resize(len, 0.);
+        self.modes.push(mode);
+        self.layer_count += 1;
+    }
+
+    fn pop_layer(&mut self) {
+        let mode = match self.modes.pop() {
+            Some(mode) => mode,
+            _ => return,
+        };
+        if self.layer_count < 2 {
+            return;
+        }
+        self.layer_count -= 1;
+        let src = core::mem::take(&mut self.layers[self.layer_count]);
+        let mut dst = core::mem::take(&mut self.layers[self.layer_count - 1]);
+        for i in 0..self.len() {
+            let coverage = self.coverage(i);
+            if coverage == 0. {
+                continue;
+            }
+            let s = &src[i * 4..i * 4 + 4];
+            let d = &mut dst[i * 4..i * 4 + 4];
+            let c = composite(mode, [s[0], s[1], s[2], s[3]], [d[0], d[1], d[2], d[3]]);
+            for j in 0..4 {
+                d[j] += (c[j] - d[j]) * coverage;
+            }
+        }
+        self.layers[self.layer_count] = src;
+        self.layers[self.layer_count - 1] = dst;
+    }
+}
+
+impl Canvas {
+    /// Returns the premultiplied color of a gradient brush at the specified
+    /// point in paint space.
+    fn brush_color(&self, brush: &Brush, p: Point) -> [f32; 4] {
+        match *brush {
+            Brush::Solid(ref color) => self.color(color),
+            Brush::LinearGradient {
+                start,
+                end,
+                stops,
+                extend,
+            } => {
+                let d = end - start;
+                let len = d.dot(d);
+                let t = if len != 0. {
+                    (p - start).dot(d) / len
+                } else {
+                    0.
+                };
+                self.stop_color(stops, extend, t)
+            }
+            Brush::RadialGradient {
+                start_center,
+                start_radius,
+                end_center,
+                end_radius,
+                stops,
+                extend,
+            } => match radial_position(p, start_center, start_radius, end_center, end_radius) {
+                Some(t) => self.stop_color(stops, extend, t),
+                None => [0.; 4],
+            },
+            Brush::SweepGradient {
+                center,
+                start_angle,
+                end_angle,
+                stops,
+                extend,
+            } => {
+                let d = p - center;
+                let mut angle = d.y.atan2(d.x).to_degrees();
+                if angle < 0. {
+                    angle += 360.;
+                }
+                let span = end_angle - start_angle;
+                if span == 0. {
+                    return if angle < start_angle {
+                        self.stop_color(stops, Extend::Pad, 0.)
+                    } else {
+                        self.stop_color(stops, Extend::Pad, 1.)
+                    };
+                }
+                let t = (angle - start_angle) / span;
+                self.stop_color(stops, extend, t)
+            }
+        }
+    }
+}
+
+/// Returns the position on the color line of a two point conical gradient
+/// for the specified point, selecting the largest valid solution.
+fn radial_position(p: Point, c0: Point, r0: f32, c1: Point, r1: f32) -> Option<f32> {
+    let cd = c1 - c0;
+    let pd = p - c0;
+    let dr = r1 - r0;
+    let a = cd.dot(cd) - dr * dr;
+    let b = pd.dot(cd) + r0 * dr;
+    let c = pd.dot(pd) - r0 * r0;
+    let valid = |t: f32| r0 + t * dr >= 0.;
+    if a.abs() < 1e-6 {
+        if b == 0. {
+            return None;
+        }
+        let t = c / (2. * b);
+        return Some(t).filter(|t| valid(*t));
+    }
+    let disc = b * b - a * c;
+    if disc < 0. {
+        return None;
+    }
+    let sqrt = disc.sqrt();
+    let t1 = (b + sqrt) / a;
+    let t2 = (b - sqrt) / a;
+    let (hi, lo) = if t1 > t2 { (t1, t2) } else { (t2, t1) };
+    if valid(hi) {
+        Some(hi)
+    } else if valid(lo) {
+        Some(lo)
+    } else {
+        None
+    }
+}
+
+/// Composites premultiplied source and destination colors.
+fn composite(mode: CompositeMode, s: [f32; 4], d: [f32; 4]) -> [f32; 4] {
+    use CompositeMode::*;
+    let (sa, da) = (s[3], d[3]);
+    let porter_duff = |fa: f32, fb: f32| {
+        [
+            s[0] * fa + d[0] * fb,
+            s[1] * fa + d[1] * fb,
+            s[2] * fa + d[2] * fb,
+            s[3] * fa + d[3] * fb,
+        ]
+    };
+    match mode {
+        Clear => [0.; 4],
+        Src => s,
+        Dest => d,
+        SrcOver => porter_duff(1., 1. - sa),
+        DestOver => porter_duff(1. - da, 1.),
+        SrcIn => porter_duff(da, 0.),
+        DestIn => porter_duff(0., sa),
+        SrcOut => porter_duff(1. - da, 0.),
+        DestOut => porter_duff(0., 1. - sa),
+        SrcAtop => porter_duff(da, 1. - sa),
+        DestAtop => porter_duff(1. - da, sa),
+        Xor => porter_duff(1. - da, 1. - sa),
+        Plus => {
+            let c = porter_duff(1., 1.);
+            [c[0].min(1.), c[1].min(1.), c[2].min(1.), c[3].min(1.)]
+        }
+        _ => {
+            // Blend modes operate on unpremultiplied colors.
+            let unpremul = |c: [f32; 4]| {
+                if c[3] > 0. {
+                    [c[0] / c[3], c[1] / c[3], c[2] / c[3]]
+                } else {
+                    [0.; 3]
+                }
+            };
+            let (cs, cb) = (unpremul(s), unpremul(d));
+            let blended = blend(mode, cs, cb);
+            let a = sa + da - sa * da;
+            let mut out = [0., 0., 0., a];
+            for i in 0..3 {
+                out[i] = s[i] * (1. - da) + d[i] * (1. - sa) + sa * da * blended[i];
+            }
+            out
+        }
+    }
+}
+
+/// Applies a blend mode to unpremultiplied source and backdrop colors.
+fn blend(mode: CompositeMode, cs: [f32; 3], cb: [f32; 3]) -> [f32; 3] {
+    use CompositeMode::*;
+    let separable = |f: fn(f32, f32) -> f32| [f(cs[0], cb[0]), f(cs[1], cb[1]), f(cs[2], cb[2])];
+    match mode {
+        Screen => separable(|s, b| s + b - s * b),
+        Overlay => separable(|s, b| hard_light(b, s)),
+        Darken => separable(|s, b| s.min(b)),
+        Lighten => separable(|s, b| s.max(b)),
+        ColorDodge => separable(|s, b| {
+            if b == 0. {
+                0.
+            } else if s >= 1. {
+                1.
+            } else {
+                (b / (1. - s)).min(1.)
+            }
+        }),
+        ColorBurn => separable(|s, b| {
+            if b >= 1. {
+                1.
+            } else if s <= 0. {
+                0.
+            } else {
+                1. - ((1. - b) / s).min(1.)
+            }
+        }),
+        HardLight => separable(hard_light),
+        SoftLight => separable(|s, b| {
+            if s <= 0.5 {
+                b - (1. - 2. * s) * b * (1. - b)
+            } else {
+                let d = if b <= 0.25 {
+                    ((16. * b - 12.) * b + 4.) * b
+                } else {
+                    b.sqrt()
+                };
+                b + (2. * s - 1.) * (d - b)
+            }
+        }),
+        Difference => separable(|s, b| (s - b).abs()),
+        Exclusion => separable(|s, b| s + b - 2. * s * b),
+        Multiply => separable(|s, b| s * b),
+        Hue => set_lum(set_sat(cs, sat(cb)), lum(cb)),
+        Saturation => set_lum(set_sat(cb, sat(cs)), lum(cb)),
+        Color => set_lum(cs, lum(cb)),
+        Luminosity => set_lum(cb, lum(cs)),
+        _ => cs,
+    }
+}
+
+fn hard_light(s: f32, b: f32) -> f32 {
+    if s <= 0.5 {
+        b * 2. * s
+    } else {
+        let s = 2. * s - 1.;
+        b + s - b * s
+    }
+}
+
+fn lum(c: [f32; 3]) -> f32 {
+    0.3 * c[0] + 0.59 * c[1] + 0.11 * c[2]
+}
+
+fn sat(c: [f32; 3]) -> f32 {
+    c[0].max(c[1]).max(c[2]) - c[0].min(c[1]).min(c[2])
+}
+
+fn set_lum(c: [f32; 3], l: f32) -> [f32; 3] {
+    let d = l - lum(c);
+    let c = [c[0] + d, c[1] + d, c[2] + d];
+    let l = lum(c);
+    let n = c[0].min(c[1]).min(c[2]);
+    let x = c[0].max(c[1]).max(c[2]);
+    let mut out = c;
+    for v in out.iter_mut() {
+        if n < 0. && l != n {
+            *v = l + (*v - l) * l / (l - n);
+        }
+        if x > 1. && x != l {
+            *v = l + (*v - l) * (1. - l) / (x - l);
+        }
+    }
+    out
+}
+
+fn set_sat(c: [f32; 3], s: f32) -> [f32; 3] {
+    let max = c[0].max(c[1]).max(c[2]);
+    let min = c[0].min(c[1]).min(c[2]);
+    if max <= min {
+        return [0.; 3];
+    }
+    let mut out = [0.; 3];
+    for i in 0..3 {
+        out[i] = (c[i] - min) * s / (max - min);
+    }
+    out
+}
+
+/// Returns a transform that applies `transform` followed by `parent`.
+pub fn concat(parent: &Transform, transform: &Transform) -> Transform {
+    let (p, t) = (parent, transform);
+    Transform::new(
+        t.xx * p.xx + t.xy * p.yx,
+        t.xx * p.xy + t.xy * p.yy,
+        t.yx * p.xx + t.yy * p.yx,
+        t.yx * p.xy + t.yy * p.yy,
+        t.x * p.xx + t.y * p.yx + p.x,
+        t.x * p.xy + t.y * p.yy + p.y,
+    )
+}
+
+/// Returns the inverse of the specified transform.
+fn invert(t: &Transform) -> Option<Transform> {
+    let det = t.xx * t.yy - t.xy * t.yx;
+    if !det.is_finite() || det == 0. {
+        return None;
+    }
+    let s = 1. / det;
+    Some(Transform::new(
+        t.yy * s,
+        -t.xy * s,
+        -t.yx * s,
+        t.xx * s,
+        (t.yx * t.y - t.yy * t.x) * s,
+        (t.xy * t.x - t.xx * t.y) * s,
+    ))
+}
+
+fn transform_bounds(t: &Transform, b: &Bounds) -> Bounds {
+    let corners = [
+        b.min,
+        Point::new(b.max.x, b.min.y),
+        b.max,
+        Point::new(b.min.x, b.max.y),
+    ];
+    Bounds::from_points(corners.iter().map(|p| t.transform_point(*p)))
+}
+
+fn intersect(clip: Option<Bounds>, bounds: Bounds) -> Bounds {
+    match clip {
+        Some(clip) => {
+            let min = Point::new(clip.min.x.max(bounds.min.x), clip.min.y.max(bounds.min.y));
+            let max = Point::new(clip.max.x.min(bounds.max.x), clip.max.y.min(bounds.max.y));
+            Bounds::new(min, Point::new(max.x.max(min.x), max.y.max(min.y)))
+        }
+        None => bounds,
+    }
+}
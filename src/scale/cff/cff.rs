@@ -19,6 +19,18 @@ pub struct Cff<'a> {
 }
 
 impl<'a> Cff<'a> {
+    /// Creates a new CFF or CFF2 font from bare table data, such as an
+    /// embedded font program that is not wrapped in an sfnt container.
+    pub fn new(data: &'a [u8]) -> Option<Self> {
+        let proxy = CffProxy::parse(data, 0)?;
+        Some(Self { data, proxy })
+    }
+
+    /// Returns the number of glyphs in the font.
+    pub fn glyph_count(&self) -> u16 {
+        self.proxy.char_strings.count(self.data) as u16
+    }
+
     /// Returns the glyph with the specified glyph id.
     pub fn get(&self, glyph_id: GlyphId) -> Option<Glyph> {
         Glyph::new(self.data, &self.proxy, glyph_id)
@@ -25,6 +25,7 @@ pub struct HinterState {
     boost: Fixed,
     zones: [BlueZone; MAX_BLUE_ZONES],
     zone_count: u16,
+    hint_x: bool,
 }
 
 impl HinterState {
@@ -47,6 +48,7 @@ impl HinterState {
             boost: Fixed(0),
             zones: [BlueZone::default(); MAX_BLUE_ZONES],
             zone_count: 0,
+            hint_x: false,
         };
         glyph.eval_private_dict(coords, &mut state);
         state.initialize_zones();
@@ -58,6 +60,17 @@ impl HinterState {
         self.fscale
     }
 
+    /// Specifies whether vertical stems should be fitted to the pixel grid
+    /// along the x-axis.
+    pub fn set_hint_x(&mut self, yes: bool) {
+        self.hint_x = yes;
+    }
+
+    /// Returns true if the private dictionary defines alignment zones.
+    pub fn has_blue_zones(&self) -> bool {
+        self.zone_count != 0 || self.em_box_hints
+    }
+
     fn initialize_zones(&mut self) {
         self.em_box_hints = false;
         if self.language_group == 1 {
@@ -247,6 +260,12 @@ pub struct Hinter<'a, 'b, Sink> {
     mask: HintMask,
     initial_map: HintMap,
     map: HintMap,
+    vstems: [(f32, f32); MAX_STEM_HINTS],
+    vstem_count: u8,
+    /// Pairs of original and fitted stem edges in pixels.
+    x_edges: [(f32, f32); MAX_HINTS],
+    x_edge_count: u8,
+    x_map_valid: bool,
 }
 
 impl<'a, 'b, Sink: GlyphSink> Hinter<'a, 'b, Sink> {
@@ -259,6 +278,11 @@ impl<'a, 'b, Sink: GlyphSink> Hinter<'a, 'b, Sink> {
             mask: HintMask::all(),
             initial_map: HintMap::new(),
             map: HintMap::new(),
+            vstems: [(0., 0.); MAX_STEM_HINTS],
+            vstem_count: 0,
+            x_edges: [(0., 0.); MAX_HINTS],
+            x_edge_count: 0,
+            x_map_valid: false,
         }
     }
 
@@ -277,6 +301,71 @@ impl<'a, 'b, Sink: GlyphSink> Hinter<'a, 'b, Sink> {
         ((Fixed::from_f32(coord) * self.state.scale).0 >> 10) as f32 / 64.
     }
 
+    /// Scales a horizontal coordinate, fitting it to the vertical stems if
+    /// enabled.
+    fn hint_x(&mut self, coord: f32) -> f32 {
+        let x = self.scale(coord);
+        if !self.state.hint_x {
+            return x;
+        }
+        if !self.x_map_valid {
+            self.build_x_map();
+        }
+        let edges = &self.x_edges[..self.x_edge_count as usize];
+        let (first, last) = match (edges.first(), edges.last()) {
+            (Some(first), Some(last)) => (*first, *last),
+            _ => return x,
+        };
+        if x <= first.0 {
+            return x + first.1 - first.0;
+        }
+        if x >= last.0 {
+            return x + last.1 - last.0;
+        }
+        for pair in edges.windows(2) {
+            let ((x0, f0), (x1, f1)) = (pair[0], pair[1]);
+            if x <= x1 {
+                if x1 == x0 {
+                    return f0;
+                }
+                return f0 + (x - x0) * (f1 - f0) / (x1 - x0);
+            }
+        }
+        x
+    }
+
+    /// Rounds the edges of the vertical stems to the pixel grid, skipping
+    /// stems that overlap a previous one.
+    fn build_x_map(&mut self) {
+        self.x_map_valid = true;
+        let mut stems = self.vstems;
+        let stems = &mut stems[..self.vstem_count as usize];
+        stems.sort_unstable_by(|a, b| {
+            a.0.partial_cmp(&b.0)
+                .unwrap_or(core::cmp::Ordering::Equal)
+        });
+        let mut count = 0;
+        let mut last_edge = f32::MIN;
+        let mut last_fitted = f32::MIN;
+        for &(min, max) in stems.iter() {
+            if max <= min {
+                continue;
+            }
+            let (left, right) = (self.scale(min), self.scale(max));
+            if left < last_edge {
+                continue;
+            }
+            let width = (right - left).round().max(1.);
+            let fitted = ((left + right - width) * 0.5).round().max(last_fitted);
+            self.x_edges[count] = (left, fitted);
+            self.x_edges[count + 1] = (right, fitted + width);
+            count += 2;
+            last_edge = right;
+            last_fitted = fitted + width;
+        }
+        self.x_edge_count = count as u8;
+    }
+
     fn add_stem(&mut self, min: Fixed, max: Fixed) {
         let index = self.stem_count as usize;
         if index >= MAX_STEM_HINTS || self.map.valid {
@@ -310,6 +399,15 @@ impl<'a, 'b, Sink: GlyphSink> GlyphSink for Hinter<'a, 'b, Sink> {
         self.add_stem(Fixed(y), Fixed(dy));
     }
 
+    fn vstem(&mut self, x: f32, dx: f32) {
+        let index = self.vstem_count as usize;
+        if index >= MAX_STEM_HINTS || self.x_map_valid {
+            return;
+        }
+        self.vstems[index] = (x, dx);
+        self.vstem_count = index as u8 + 1;
+    }
+
     fn hint_mask(&mut self, mask: &[u8]) {
         let mut hint_mask = HintMask::new();
         hint_mask.set_mask(mask);
@@ -337,23 +435,23 @@ impl<'a, 'b, Sink: GlyphSink> GlyphSink for Hinter<'a, 'b, Sink> {
     }
 
     fn move_to(&mut self, x: f32, y: f32) {
-        let x = self.scale(x);
+        let x = self.hint_x(x);
         let y = self.hint(y);
         self.sink.move_to(x, y);
     }
 
     fn line_to(&mut self, x: f32, y: f32) {
-        let x = self.scale(x);
+        let x = self.hint_x(x);
         let y = self.hint(y);
         self.sink.line_to(x, y);
     }
 
     fn curve_to(&mut self, cx1: f32, cy1: f32, cx2: f32, cy2: f32, x: f32, y: f32) {
-        let cx1 = self.scale(cx1);
+        let cx1 = self.hint_x(cx1);
         let cy1 = self.hint(cy1);
-        let cx2 = self.scale(cx2);
+        let cx2 = self.hint_x(cx2);
         let cy2 = self.hint(cy2);
-        let x = self.scale(x);
+        let x = self.hint_x(x);
         let y = self.hint(y);
         self.sink.curve_to(cx1, cy1, cx2, cy2, x, y);
     }
@@ -6,13 +6,26 @@ PostScript outlines.
 #[allow(clippy::module_inception)]
 mod cff;
 mod hint;
+mod type1;
 
 pub use cff::{Glyph, GlyphSink, Cff, CffProxy};
 pub use hint::HinterState;
+pub use type1::Type1;
 
 use super::{internal, TRACE};
 
 use crate::font::FontRef;
+use crate::scale::Hinting;
+
+/// Returns true if the private dictionary for the first glyph of the font
+/// defines alignment zones.
+pub fn has_blue_zones(font: &FontRef, proxy: &CffProxy, coords: &[i16]) -> bool {
+    let cff = proxy.materialize(font);
+    match cff.get(0) {
+        Some(glyph) => HinterState::new(&glyph, 1., coords).has_blue_zones(),
+        _ => false,
+    }
+}
 
 pub struct Scaler {
     entries: Vec<Entry>,
@@ -36,15 +49,16 @@ impl Scaler {
         coords: &[i16],
         proxy: &CffProxy,
         scale: f32,
-        hint: bool,
+        hinting: Hinting,
         glyph_id: u16,
         sink: &mut impl GlyphSink,
     ) -> Option<()> {
         let cff = proxy.materialize(font);
         let glyph = cff.get(glyph_id)?;
-        if hint {
+        if hinting != Hinting::None {
             let dict = glyph.subfont_index();
             let state = self.entry(id, dict, coords, scale, &glyph);
+            state.set_hint_x(matches!(hinting, Hinting::Normal | Hinting::Mono));
             if glyph.path(scale, coords, Some(state), sink) {
                 Some(())
             } else {
@@ -64,13 +78,13 @@ impl Scaler {
         coords: &[i16],
         scale: f32,
         glyph: &Glyph,
-    ) -> &HinterState {
+    ) -> &mut HinterState {
         let epoch = self.epoch;
         let (found, index) = self.find_entry(id, dict, coords, scale);
         if found {
             let entry = &mut self.entries[index];
             entry.epoch = epoch;
-            &entry.state
+            &mut entry.state
         } else {
             self.epoch += 1;
             let state = HinterState::new(glyph, scale, coords);
@@ -83,7 +97,7 @@ impl Scaler {
                     coords: Vec::from(coords),
                     scale,
                 });
-                &self.entries[index].state
+                &mut self.entries[index].state
             } else {
                 let entry = &mut self.entries[index];
                 entry.epoch = epoch;
@@ -93,7 +107,7 @@ impl Scaler {
                 entry.coords.clear();
                 entry.coords.extend_from_slice(coords);
                 entry.scale = scale;
-                &entry.state
+                &mut entry.state
             }
         }
     }
@@ -0,0 +1,768 @@
+//! Adobe Type 1 font programs.
+
+use super::cff::GlyphSink;
+use super::internal::cff::{STANDARD_ENCODING, STANDARD_STRINGS};
+
+/// Key for decrypting the private portion of a font program.
+const EEXEC_KEY: u16 = 55665;
+
+/// Key for decrypting char strings and subroutines.
+const CHAR_STRING_KEY: u16 = 4330;
+
+/// Maximum number of subroutines in a font program.
+const MAX_SUBRS: usize = 65536;
+
+/// Type 1 font program with decrypted char strings.
+#[derive(Clone)]
+pub struct Type1 {
+    name: String,
+    font_matrix: [f32; 6],
+    /// Decrypted char string and subroutine data.
+    data: Vec<u8>,
+    /// Glyph names and char string ranges, with `.notdef` first.
+    glyphs: Vec<(String, (u32, u32))>,
+    subrs: Vec<(u32, u32)>,
+    encoding: Vec<u16>,
+}
+
+impl Type1 {
+    /// Parses a Type 1 font program in PFB (segmented binary), PFA
+    /// (hexadecimal encrypted portion) or raw binary form as embedded in
+    /// PDF documents.
+    pub fn new(data: &[u8]) -> Option<Self> {
+        let (clear, encrypted) = split(data)?;
+        let mut private = decrypt(&encrypted, EEXEC_KEY);
+        private.drain(..4.min(private.len()));
+        let mut font = Self {
+            name: String::new(),
+            font_matrix: [0.001, 0., 0., 0.001, 0., 0.],
+            data: Vec::new(),
+            glyphs: Vec::new(),
+            subrs: Vec::new(),
+            encoding: Vec::new(),
+        };
+        let encoding = font.parse_clear(&clear)?;
+        font.parse_private(&private)?;
+        if let Some(notdef) = font.glyphs.iter().position(|g| g.0 == ".notdef") {
+            let glyph = font.glyphs.remove(notdef);
+            font.glyphs.insert(0, glyph);
+        }
+        font.encoding = encoding
+            .iter()
+            .map(|name| match name {
+                Some(name) => font.glyph_by_name(name).unwrap_or(0),
+                None => 0,
+            })
+            .collect();
+        Some(font)
+    }
+
+    /// Returns the PostScript name of the font.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the font matrix.
+    pub fn font_matrix(&self) -> [f32; 6] {
+        self.font_matrix
+    }
+
+    /// Returns the number of glyphs in the font.
+    pub fn glyph_count(&self) -> u16 {
+        self.glyphs.len() as u16
+    }
+
+    /// Returns the name of the specified glyph.
+    pub fn glyph_name(&self, glyph_id: u16) -> Option<&str> {
+        self.glyphs.get(glyph_id as usize).map(|g| g.0.as_str())
+    }
+
+    /// Returns the identifier of the glyph with the specified name.
+    pub fn glyph_by_name(&self, name: &str) -> Option<u16> {
+        self.glyphs
+            .iter()
+            .position(|g| g.0 == name)
+            .map(|gid| gid as u16)
+    }
+
+    /// Returns the glyph mapped to the specified code by the built-in
+    /// encoding.
+    pub fn glyph_by_code(&self, code: u8) -> Option<u16> {
+        match self.encoding.get(code as usize) {
+            Some(0) | None => None,
+            Some(gid) => Some(*gid),
+        }
+    }
+
+    /// Evaluates the glyph in font units, directing the result to the
+    /// specified sink.
+    pub fn path(&self, glyph_id: u16, sink: &mut impl GlyphSink) -> bool {
+        self.glyph_path(glyph_id, (0., 0.), true, sink).is_some()
+    }
+
+    fn glyph_path(
+        &self,
+        glyph_id: u16,
+        offset: (f32, f32),
+        allow_seac: bool,
+        sink: &mut impl GlyphSink,
+    ) -> Option<()> {
+        let range = self.glyphs.get(glyph_id as usize)?.1;
+        let mut state = State {
+            x: 0.,
+            y: 0.,
+            offset,
+            open: false,
+            allow_seac,
+            flex: false,
+            flex_points: [(0., 0.); 7],
+            flex_len: 0,
+            results: Stack::new(),
+        };
+        let mut stack = Stack::new();
+        self.parse(&mut state, &mut stack, range, 0, sink)?;
+        if state.open {
+            sink.close();
+        }
+        Some(())
+    }
+
+    /// Returns the glyph for a character code in the standard encoding as
+    /// used by the `seac` operator.
+    fn standard_glyph(&self, code: f32) -> Option<u16> {
+        let sid = *STANDARD_ENCODING.get(code as usize)?;
+        if sid == 0 {
+            return None;
+        }
+        self.glyph_by_name(STANDARD_STRINGS[sid as usize])
+    }
+
+    /// Evaluates a char string. Returns true if the end of the glyph was
+    /// reached.
+    fn parse(
+        &self,
+        s: &mut State,
+        stack: &mut Stack,
+        range: (u32, u32),
+        depth: u32,
+        sink: &mut impl GlyphSink,
+    ) -> Option<bool> {
+        if depth > 10 {
+            return None;
+        }
+        use opcodes::*;
+        let data = self.data.get(range.0 as usize..range.1 as usize)?;
+        let mut i = 0;
+        while i < data.len() {
+            let b0 = data[i];
+            i += 1;
+            match b0 {
+                32..=246 => stack.push(b0 as f32 - 139.)?,
+                247..=250 => {
+                    let b1 = *data.get(i)? as f32;
+                    i += 1;
+                    stack.push((b0 as f32 - 247.) * 256. + b1 + 108.)?;
+                }
+                251..=254 => {
+                    let b1 = *data.get(i)? as f32;
+                    i += 1;
+                    stack.push(-(b0 as f32 - 251.) * 256. - b1 - 108.)?;
+                }
+                255 => {
+                    let bytes = data.get(i..i + 4)?;
+                    i += 4;
+                    let value = i32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+                    stack.push(value as f32)?;
+                }
+                HSTEM | VSTEM => stack.clear(),
+                VMOVETO => {
+                    let dy = stack.args(1)?[0];
+                    s.move_to(0., dy, sink);
+                    stack.clear();
+                }
+                RLINETO => {
+                    let args = stack.args(2)?;
+                    s.line_to(args[0], args[1], sink);
+                    stack.clear();
+                }
+                HLINETO => {
+                    let dx = stack.args(1)?[0];
+                    s.line_to(dx, 0., sink);
+                    stack.clear();
+                }
+                VLINETO => {
+                    let dy = stack.args(1)?[0];
+                    s.line_to(0., dy, sink);
+                    stack.clear();
+                }
+                RRCURVETO => {
+                    let a = stack.args(6)?;
+                    s.curve_to([a[0], a[1], a[2], a[3], a[4], a[5]], sink);
+                    stack.clear();
+                }
+                CLOSEPATH => {
+                    if s.open {
+                        s.open = false;
+                        sink.close();
+                    }
+                    stack.clear();
+                }
+                CALLSUBR => {
+                    let index = stack.pop()? as usize;
+                    let range = *self.subrs.get(index)?;
+                    if self.parse(s, stack, range, depth + 1, sink)? {
+                        return Some(true);
+                    }
+                }
+                RETURN => return Some(false),
+                ESCAPE => {
+                    let b1 = *data.get(i)?;
+                    i += 1;
+                    match b1 {
+                        SEAC => {
+                            if !s.allow_seac {
+                                return None;
+                            }
+                            let a = stack.args(5)?;
+                            let (asb, adx, ady) = (a[0], a[1], a[2]);
+                            let base = self.standard_glyph(a[3])?;
+                            let accent = self.standard_glyph(a[4])?;
+                            if s.open {
+                                s.open = false;
+                                sink.close();
+                            }
+                            self.glyph_path(base, s.offset, false, sink)?;
+                            let offset = (s.offset.0 + adx - asb, s.offset.1 + ady);
+                            self.glyph_path(accent, offset, false, sink)?;
+                            return Some(true);
+                        }
+                        SBW => {
+                            let a = stack.args(4)?;
+                            s.x = a[0];
+                            s.y = a[1];
+                            stack.clear();
+                        }
+                        DIV => {
+                            let b = stack.pop()?;
+                            let a = stack.pop()?;
+                            stack.push(if b != 0. { a / b } else { 0. })?;
+                        }
+                        CALLOTHERSUBR => {
+                            let index = stack.pop()? as i32;
+                            let count = stack.pop()? as usize;
+                            let args = stack.args(count)?;
+                            s.results.clear();
+                            match index {
+                                // End of a flex sequence.
+                                0 => {
+                                    if s.flex && s.flex_len == 7 {
+                                        let p = s.flex_points;
+                                        let (ox, oy) = s.offset;
+                                        s.ensure_open(sink);
+                                        for c in [&p[1..4], &p[4..7]].iter() {
+                                            sink.curve_to(
+                                                c[0].0 + ox,
+                                                c[0].1 + oy,
+                                                c[1].0 + ox,
+                                                c[1].1 + oy,
+                                                c[2].0 + ox,
+                                                c[2].1 + oy,
+                                            );
+                                        }
+                                        s.x = p[6].0;
+                                        s.y = p[6].1;
+                                    }
+                                    s.flex = false;
+                                    // The final point is returned for a
+                                    // subsequent setcurrentpoint.
+                                    s.results.push(s.y)?;
+                                    s.results.push(s.x)?;
+                                }
+                                // Start of a flex sequence.
+                                1 => {
+                                    s.flex = true;
+                                    s.flex_len = 0;
+                                }
+                                // Add a flex point.
+                                2 => {
+                                    if s.flex && s.flex_len < 7 {
+                                        s.flex_points[s.flex_len] = (s.x, s.y);
+                                        s.flex_len += 1;
+                                    }
+                                }
+                                // Other subroutines, including hint
+                                // replacement, return their arguments.
+                                _ => {
+                                    for arg in args.iter().rev() {
+                                        s.results.push(*arg)?;
+                                    }
+                                }
+                            }
+                            stack.truncate(stack.len() - count);
+                        }
+                        POP => {
+                            let value = s.results.pop().unwrap_or(0.);
+                            stack.push(value)?;
+                        }
+                        SETCURRENTPOINT => {
+                            let a = stack.args(2)?;
+                            s.x = a[0];
+                            s.y = a[1];
+                            stack.clear();
+                        }
+                        // Dot section, vstem3, hstem3 and unsupported
+                        // operators.
+                        _ => stack.clear(),
+                    }
+                }
+                HSBW => {
+                    s.x = stack.args(2)?[0];
+                    s.y = 0.;
+                    stack.clear();
+                }
+                ENDCHAR => {
+                    if s.open {
+                        s.open = false;
+                        sink.close();
+                    }
+                    return Some(true);
+                }
+                RMOVETO => {
+                    let args = stack.args(2)?;
+                    s.move_to(args[0], args[1], sink);
+                    stack.clear();
+                }
+                HMOVETO => {
+                    let dx = stack.args(1)?[0];
+                    s.move_to(dx, 0., sink);
+                    stack.clear();
+                }
+                VHCURVETO => {
+                    let a = stack.args(4)?;
+                    s.curve_to([0., a[0], a[1], a[2], a[3], 0.], sink);
+                    stack.clear();
+                }
+                HVCURVETO => {
+                    let a = stack.args(4)?;
+                    s.curve_to([a[0], 0., a[1], a[2], 0., a[3]], sink);
+                    stack.clear();
+                }
+                _ => stack.clear(),
+            }
+        }
+        Some(false)
+    }
+
+    /// Parses the clear text portion of the font program, returning the
+    /// glyph names of the built-in encoding.
+    fn parse_clear(&mut self, data: &[u8]) -> Option<Vec<Option<String>>> {
+        let mut encoding = vec![None; 256];
+        let mut lexer = Lexer::new(data);
+        while let Some(token) = lexer.next() {
+            match token {
+                b"/FontName" => {
+                    if let Some(name) = lexer.next().and_then(|t| t.strip_prefix(b"/")) {
+                        self.name = String::from_utf8_lossy(name).into_owned();
+                    }
+                }
+                b"/FontMatrix" => {
+                    let mut values = [0f32; 6];
+                    let mut count = 0;
+                    while count < 6 {
+                        let token = lexer.next()?;
+                        if let Some(value) = number(token) {
+                            values[count] = value;
+                            count += 1;
+                        } else if token != b"[" && token != b"{" {
+                            break;
+                        }
+                    }
+                    if count == 6 {
+                        self.font_matrix = values;
+                    }
+                }
+                b"/Encoding" => {
+                    if lexer.next()? == b"StandardEncoding" {
+                        for (code, sid) in STANDARD_ENCODING.iter().enumerate() {
+                            if *sid != 0 {
+                                encoding[code] = Some(STANDARD_STRINGS[*sid as usize].into());
+                            }
+                        }
+                        continue;
+                    }
+                    while let Some(token) = lexer.next() {
+                        if token == b"def" {
+                            break;
+                        }
+                        if token != b"dup" {
+                            continue;
+                        }
+                        let code = lexer.next().and_then(number);
+                        let name = lexer.next().and_then(|t| t.strip_prefix(b"/"));
+                        if let (Some(code), Some(name)) = (code, name) {
+                            if let Some(entry) = encoding.get_mut(code as usize) {
+                                *entry = Some(String::from_utf8_lossy(name).into_owned());
+                            }
+                        }
+                    }
+                }
+                b"eexec" => break,
+                _ => {}
+            }
+        }
+        Some(encoding)
+    }
+
+    /// Parses the decrypted private portion of the font program, collecting
+    /// the subroutines and char strings.
+    fn parse_private(&mut self, data: &[u8]) -> Option<()> {
+        let mut len_iv = 4i32;
+        let mut read_string: Vec<&[u8]> = vec![b"RD", b"-|"];
+        let mut lexer = Lexer::new(data);
+        let mut prev: [&[u8]; 2] = [b"", b""];
+        // Declared size of the subroutine array.
+        let mut subr_count = MAX_SUBRS;
+        while let Some(token) = lexer.next() {
+            match token {
+                b"array" if prev[0] == b"/Subrs" => {
+                    subr_count = number(prev[1])
+                        .filter(|count| *count >= 0.)
+                        .map_or(MAX_SUBRS, |count| (count as usize).min(MAX_SUBRS));
+                }
+                b"/lenIV" => {
+                    len_iv = lexer.next().and_then(number)? as i32;
+                    prev = [b"", b""];
+                    continue;
+                }
+                b"{" if prev[1].starts_with(b"/") => {
+                    // Some fonts define their own name for the procedure
+                    // that reads binary data.
+                    const PROC: [&[u8]; 5] = [b"string", b"currentfile", b"exch", b"readstring", b"pop"];
+                    let mut ahead = lexer.clone();
+                    if PROC.iter().all(|t| ahead.next() == Some(*t)) {
+                        read_string.push(&prev[1][1..]);
+                    }
+                }
+                b"closefile" => break,
+                _ if read_string.contains(&token) => {
+                    let len = number(prev[1]).filter(|len| *len >= 0.)? as usize;
+                    let bytes = lexer.read_binary(len)?;
+                    let mut bytes = if len_iv >= 0 {
+                        decrypt(bytes, CHAR_STRING_KEY)
+                    } else {
+                        bytes.to_vec()
+                    };
+                    bytes.drain(..(len_iv.max(0) as usize).min(bytes.len()));
+                    let start = self.data.len() as u32;
+                    self.data.extend_from_slice(&bytes);
+                    let range = (start, self.data.len() as u32);
+                    if let Some(name) = prev[0].strip_prefix(b"/") {
+                        let name = String::from_utf8_lossy(name).into_owned();
+                        self.glyphs.push((name, range));
+                    } else if let Some(index) = number(prev[0]) {
+                        // Entries outside of the declared array are ignored.
+                        if index < 0. || index >= subr_count as f32 {
+                            prev = [b"", b""];
+                            continue;
+                        }
+                        let index = index as usize;
+                        if index >= self.subrs.len() {
+                            self.subrs.resize(index + 1, (0, 0));
+                        }
+                        self.subrs[index] = range;
+                    }
+                    prev = [b"", b""];
+                    continue;
+                }
+                _ => {}
+            }
+            prev = [prev[1], token];
+        }
+        if self.glyphs.is_empty() {
+            return None;
+        }
+        Some(())
+    }
+}
+
+struct State {
+    x: f32,
+    y: f32,
+    offset: (f32, f32),
+    open: bool,
+    allow_seac: bool,
+    flex: bool,
+    flex_points: [(f32, f32); 7],
+    flex_len: usize,
+    /// Results of the last call to an other subroutine, returned by the
+    /// pop operator.
+    results: Stack,
+}
+
+impl State {
+    fn move_to(&mut self, dx: f32, dy: f32, sink: &mut impl GlyphSink) {
+        self.x += dx;
+        self.y += dy;
+        // Points in a flex sequence are collected by other subroutine 2.
+        if self.flex {
+            return;
+        }
+        if self.open {
+            sink.close();
+        }
+        self.open = true;
+        sink.move_to(self.x + self.offset.0, self.y + self.offset.1);
+    }
+
+    fn line_to(&mut self, dx: f32, dy: f32, sink: &mut impl GlyphSink) {
+        self.ensure_open(sink);
+        self.x += dx;
+        self.y += dy;
+        sink.line_to(self.x + self.offset.0, self.y + self.offset.1);
+    }
+
+    fn curve_to(&mut self, d: [f32; 6], sink: &mut impl GlyphSink) {
+        self.ensure_open(sink);
+        let (ox, oy) = self.offset;
+        let (x1, y1) = (self.x + d[0], self.y + d[1]);
+        let (x2, y2) = (x1 + d[2], y1 + d[3]);
+        self.x = x2 + d[4];
+        self.y = y2 + d[5];
+        sink.curve_to(x1 + ox, y1 + oy, x2 + ox, y2 + oy, self.x + ox, self.y + oy);
+    }
+
+    fn ensure_open(&mut self, sink: &mut impl GlyphSink) {
+        if !self.open {
+            self.open = true;
+            sink.move_to(self.x + self.offset.0, self.y + self.offset.1);
+        }
+    }
+}
+
+const MAX_STACK: usize = 48;
+
+#[derive(Copy, Clone)]
+struct Stack {
+    elements: [f32; MAX_STACK],
+    top: usize,
+}
+
+impl Stack {
+    fn new() -> Self {
+        Self {
+            elements: [0.; MAX_STACK],
+            top: 0,
+        }
+    }
+
+    fn push(&mut self, value: f32) -> Option<()> {
+        if self.top == MAX_STACK {
+            return None;
+        }
+        self.elements[self.top] = value;
+        self.top += 1;
+        Some(())
+    }
+
+    fn pop(&mut self) -> Option<f32> {
+        self.top = self.top.checked_sub(1)?;
+        Some(self.elements[self.top])
+    }
+
+    fn len(&self) -> usize {
+        self.top
+    }
+
+    /// Returns the specified number of arguments from the top of the stack.
+    fn args(&self, count: usize) -> Option<&[f32]> {
+        self.elements.get(self.top.checked_sub(count)?..self.top)
+    }
+
+    fn truncate(&mut self, len: usize) {
+        self.top = self.top.min(len);
+    }
+
+    fn clear(&mut self) {
+        self.top = 0;
+    }
+}
+
+/// Splits a font program into the clear text and encrypted portions,
+/// converting the latter to binary.
+fn split(data: &[u8]) -> Option<(Vec<u8>, Vec<u8>)> {
+    if data.starts_with(&[0x80, 1]) {
+        // PFB: a sequence of ASCII (1) and binary (2) segments terminated
+        // by an end of file marker (3).
+        let (mut clear, mut encrypted) = (Vec::new(), Vec::new());
+        let mut offset = 0;
+        while let Some(&[0x80, ty]) = data.get(offset..offset + 2) {
+            if ty == 3 {
+                break;
+            }
+            let len = data.get(offset + 2..offset + 6)?;
+            let len = u32::from_le_bytes([len[0], len[1], len[2], len[3]]) as usize;
+            let segment = data.get(offset + 6..(offset + 6).checked_add(len)?)?;
+            match ty {
+                1 if encrypted.is_empty() => clear.extend_from_slice(segment),
+                1 => {}
+                2 => encrypted.extend_from_slice(segment),
+                _ => return None,
+            }
+            offset += 6 + len;
+        }
+        return Some((clear, encrypted));
+    }
+    let start = find(data, b"eexec")? + 5;
+    let clear = data[..start].to_vec();
+    let mut rest = &data[start..];
+    while let Some((b, tail)) = rest.split_first() {
+        if !matches!(b, b' ' | b'\t' | b'\r' | b'\n') {
+            break;
+        }
+        rest = tail;
+    }
+    // The encrypted portion is hexadecimal if the first four bytes are hex
+    // digits.
+    let is_hex = rest.len() >= 4 && rest[..4].iter().all(|b| b.is_ascii_hexdigit());
+    if !is_hex {
+        return Some((clear, rest.to_vec()));
+    }
+    let mut encrypted = Vec::with_capacity(rest.len() / 2);
+    let mut high = None;
+    for b in rest {
+        let digit = match b {
+            b'0'..=b'9' => b - b'0',
+            b'a'..=b'f' => b - b'a' + 10,
+            b'A'..=b'F' => b - b'A' + 10,
+            b' ' | b'\t' | b'\r' | b'\n' => continue,
+            _ => break,
+        };
+        match high.take() {
+            Some(high) => encrypted.push(high << 4 | digit),
+            None => high = Some(digit),
+        }
+    }
+    Some((clear, encrypted))
+}
+
+fn decrypt(data: &[u8], key: u16) -> Vec<u8> {
+    let mut r = key;
+    data.iter()
+        .map(|&c| {
+            let p = c ^ (r >> 8) as u8;
+            r = (c as u16)
+                .wrapping_add(r)
+                .wrapping_mul(52845)
+                .wrapping_add(22719);
+            p
+        })
+        .collect()
+}
+
+fn find(data: &[u8], pattern: &[u8]) -> Option<usize> {
+    data.windows(pattern.len()).position(|w| w == pattern)
+}
+
+fn number(token: &[u8]) -> Option<f32> {
+    core::str::from_utf8(token).ok()?.parse().ok()
+}
+
+/// Minimal PostScript tokenizer.
+#[derive(Clone)]
+struct Lexer<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Lexer<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        Self { data, pos: 0 }
+    }
+
+    fn next(&mut self) -> Option<&'a [u8]> {
+        let data = self.data;
+        loop {
+            match *data.get(self.pos)? {
+                b' ' | b'\t' | b'\r' | b'\n' | b'\x0C' | 0 => self.pos += 1,
+                b'%' => {
+                    while !matches!(data.get(self.pos), Some(b'\r') | Some(b'\n') | None) {
+                        self.pos += 1;
+                    }
+                }
+                _ => break,
+            }
+        }
+        let start = self.pos;
+        self.pos += 1;
+        match data[start] {
+            b'[' | b']' | b'{' | b'}' => {}
+            b'(' => {
+                let mut depth = 1;
+                while depth != 0 {
+                    match *data.get(self.pos)? {
+                        b'\\' => self.pos += 1,
+                        b'(' => depth += 1,
+                        b')' => depth -= 1,
+                        _ => {}
+                    }
+                    self.pos += 1;
+                }
+            }
+            b'<' | b'>' if data.get(self.pos) == data.get(start) => self.pos += 1,
+            b'<' => {
+                while *data.get(self.pos)? != b'>' {
+                    self.pos += 1;
+                }
+                self.pos += 1;
+            }
+            _ => {
+                while let Some(b) = data.get(self.pos) {
+                    if b.is_ascii_whitespace()
+                        || matches!(b, b'[' | b']' | b'{' | b'}' | b'(' | b'<' | b'>' | b'/' | b'%')
+                    {
+                        break;
+                    }
+                    self.pos += 1;
+                }
+            }
+        }
+        data.get(start..self.pos)
+    }
+
+    /// Reads binary data following a token that is separated by a single
+    /// space.
+    fn read_binary(&mut self, len: usize) -> Option<&'a [u8]> {
+        let start = self.pos + 1;
+        let bytes = self.data.get(start..start.checked_add(len)?)?;
+        self.pos = start + len;
+        Some(bytes)
+    }
+}
+
+mod opcodes {
+    pub const HSTEM: u8 = 1;
+    pub const VSTEM: u8 = 3;
+    pub const VMOVETO: u8 = 4;
+    pub const RLINETO: u8 = 5;
+    pub const HLINETO: u8 = 6;
+    pub const VLINETO: u8 = 7;
+    pub const RRCURVETO: u8 = 8;
+    pub const CLOSEPATH: u8 = 9;
+    pub const CALLSUBR: u8 = 10;
+    pub const RETURN: u8 = 11;
+    pub const ESCAPE: u8 = 12;
+    pub const HSBW: u8 = 13;
+    pub const ENDCHAR: u8 = 14;
+    pub const RMOVETO: u8 = 21;
+    pub const HMOVETO: u8 = 22;
+    pub const VHCURVETO: u8 = 30;
+    pub const HVCURVETO: u8 = 31;
+
+    // Escaped operators.
+    pub const SEAC: u8 = 6;
+    pub const SBW: u8 = 7;
+    pub const DIV: u8 = 12;
+    pub const CALLOTHERSUBR: u8 = 16;
+    pub const POP: u8 = 17;
+    pub const SETCURRENTPOINT: u8 = 33;
+}
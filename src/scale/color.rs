@@ -2,7 +2,10 @@ use super::super::{
     palette::{ColorPalette, ColorPalettes},
     FontRef, GlyphId,
 };
-use super::internal::{raw_tag, Bytes, RawFont, RawTag};
+use super::internal::{raw_tag, var::item_delta, Bytes, RawFont, RawTag, U24};
+use super::paint::{Brush, ColorPainter, ColorStop, CompositeMode, Extend, PaintColor};
+use super::Outline;
+use zeno::{Bounds, Point, Transform};
 
 const COLR: RawTag = raw_tag(b"COLR");
 const CPAL: RawTag = raw_tag(b"CPAL");
@@ -50,13 +53,47 @@ impl ColorProxy {
         None
     }
 
+    /// Returns the offset, relative to the start of the table, of the root
+    /// paint for the specified glyph in a version 1 table.
+    pub fn paint(&self, data: &[u8], glyph_id: GlyphId) -> Option<u32> {
+        let b = Bytes::with_offset(data, self.colr as usize)?;
+        if b.read::<u16>(0)? < 1 {
+            return None;
+        }
+        let list = b.read::<u32>(14)? as usize;
+        if list == 0 {
+            return None;
+        }
+        let count = b.read::<u32>(list)? as usize;
+        let mut l = 0;
+        let mut h = count;
+        while l < h {
+            use core::cmp::Ordering::*;
+            let i = l + (h - l) / 2;
+            let rec = list + 4 + i * 6;
+            let id = b.read::<u16>(rec)?;
+            match glyph_id.cmp(&id) {
+                Less => h = i,
+                Greater => l = i + 1,
+                Equal => return Some((list + b.read::<u32>(rec + 2)? as usize) as u32),
+            }
+        }
+        None
+    }
+
     // Unused when render feature is disabled.
     #[allow(dead_code)]
     pub fn palette<'a>(&self, font: &FontRef<'a>, index: u16) -> Option<ColorPalette<'a>> {
+        self.palettes(font).nth(index as usize)
+    }
+
+    // Unused when render feature is disabled.
+    #[allow(dead_code)]
+    pub fn palettes<'a>(&self, font: &FontRef<'a>) -> ColorPalettes<'a> {
         if self.cpal != 0 {
-            ColorPalettes::from_font_and_offset(font, self.cpal).nth(index as usize)
+            ColorPalettes::from_font_and_offset(font, self.cpal)
         } else {
-            None
+            ColorPalettes::new(*font, &[])
         }
     }
 }
@@ -94,3 +131,481 @@ pub struct Layer {
     pub glyph_id: GlyphId,
     pub color_index: Option<u16>,
 }
+
+/// Maximum nesting depth of the paint graph.
+const MAX_DEPTH: u32 = 64;
+
+/// Maximum number of paint nodes visited for a single glyph, including the
+/// repeated visits of shared subgraphs.
+const MAX_NODES: u32 = 10000;
+
+/// Traverses the paint graph of a color glyph, directing the operations to
+/// a painter.
+pub struct PaintGraph<'a, 'b, F> {
+    data: &'a [u8],
+    colr: Bytes<'a>,
+    proxy: ColorProxy,
+    coords: &'b [i16],
+    scale: f32,
+    var_map: u32,
+    var_store: u32,
+    stops: &'b mut Vec<ColorStop>,
+    outline: &'b mut Outline,
+    outlines: F,
+    active: Vec<u32>,
+    /// Remaining number of nodes that may be visited.
+    budget: u32,
+}
+
+impl<'a, 'b, F> PaintGraph<'a, 'b, F>
+where
+    F: FnMut(GlyphId, &mut Outline) -> bool,
+{
+    pub fn new(
+        data: &'a [u8],
+        proxy: ColorProxy,
+        coords: &'b [i16],
+        scale: f32,
+        stops: &'b mut Vec<ColorStop>,
+        outline: &'b mut Outline,
+        outlines: F,
+    ) -> Option<Self> {
+        let colr = Bytes::with_offset(data, proxy.colr as usize)?;
+        let (mut var_map, mut var_store) = (0, 0);
+        if colr.read::<u16>(0)? >= 1 && !coords.is_empty() {
+            var_map = colr.read::<u32>(26).unwrap_or(0);
+            var_store = colr.read::<u32>(30).unwrap_or(0);
+            if var_map != 0 {
+                var_map += proxy.colr;
+            }
+            if var_store != 0 {
+                var_store += proxy.colr;
+            }
+        }
+        Some(Self {
+            data,
+            colr,
+            proxy,
+            coords,
+            scale,
+            var_map,
+            var_store,
+            stops,
+            outline,
+            outlines,
+            active: Vec::new(),
+            budget: MAX_NODES,
+        })
+    }
+
+    /// Paints the specified glyph. Layered glyphs from version 0 of the
+    /// table are painted as solid fills clipped to each layer.
+    pub fn paint(&mut self, glyph_id: GlyphId, painter: &mut impl ColorPainter) -> Option<()> {
+        self.budget = MAX_NODES;
+        if let Some(paint) = self.proxy.paint(self.data, glyph_id) {
+            return self.paint_root(glyph_id, paint, 0, painter);
+        }
+        let layers = self.proxy.layers(self.data, glyph_id)?;
+        for i in 0..layers.len() {
+            let layer = layers.get(i)?;
+            self.outline.clear();
+            if !(self.outlines)(layer.glyph_id, self.outline) {
+                return None;
+            }
+            painter.push_clip_glyph(layer.glyph_id, self.outline);
+            painter.fill(&Brush::Solid(PaintColor {
+                palette_index: layer.color_index,
+                alpha: 1.,
+            }));
+            painter.pop_clip();
+        }
+        Some(())
+    }
+
+    /// Returns the clip box for the specified glyph.
+    pub fn clip_box(&self, glyph_id: GlyphId) -> Option<Bounds> {
+        let b = &self.colr;
+        let list = b.read::<u32>(22)? as usize;
+        if list == 0 {
+            return None;
+        }
+        let count = b.read::<u32>(list + 1)? as usize;
+        let mut l = 0;
+        let mut h = count;
+        while l < h {
+            let i = l + (h - l) / 2;
+            let rec = list + 5 + i * 7;
+            let start = b.read::<u16>(rec)?;
+            let end = b.read::<u16>(rec + 2)?;
+            if glyph_id < start {
+                h = i;
+            } else if glyph_id > end {
+                l = i + 1;
+            } else {
+                let clip = list + b.read::<U24>(rec + 4)?.0 as usize;
+                let format = b.read::<u8>(clip)?;
+                let mut v = [0f32; 4];
+                for (j, v) in v.iter_mut().enumerate() {
+                    *v = b.read::<i16>(clip + 1 + j * 2)? as f32;
+                    if format == 2 {
+                        *v += self.delta(b.read::<u32>(clip + 9)?, j as u32);
+                    }
+                }
+                let s = self.scale;
+                return Some(Bounds::new(
+                    Point::new(v[0] * s, v[1] * s),
+                    Point::new(v[2] * s, v[3] * s),
+                ));
+            }
+        }
+        None
+    }
+
+    fn paint_root(
+        &mut self,
+        glyph_id: GlyphId,
+        paint: u32,
+        depth: u32,
+        painter: &mut impl ColorPainter,
+    ) -> Option<()> {
+        let clip = self.clip_box(glyph_id);
+        if let Some(clip) = clip {
+            painter.push_clip_box(clip);
+        }
+        let result = self.paint_node(paint, depth, painter);
+        if clip.is_some() {
+            painter.pop_clip();
+        }
+        result
+    }
+
+    fn paint_node(&mut self, p: u32, depth: u32, painter: &mut impl ColorPainter) -> Option<()> {
+        if depth > MAX_DEPTH || self.budget == 0 || self.active.contains(&p) {
+            return None;
+        }
+        self.budget -= 1;
+        self.active.push(p);
+        let result = self.paint_node_imp(p as usize, depth, painter);
+        self.active.pop();
+        result
+    }
+
+    fn paint_node_imp(
+        &mut self,
+        p: usize,
+        depth: u32,
+        painter: &mut impl ColorPainter,
+    ) -> Option<()> {
+        let b = self.colr;
+        let format = b.read::<u8>(p)?;
+        let child = || Some((p + b.read::<U24>(p + 1)?.0 as usize) as u32);
+        let s = self.scale;
+        match format {
+            // PaintColrLayers
+            1 => {
+                let count = b.read::<u8>(p + 1)? as usize;
+                let first = b.read::<u32>(p + 2)? as usize;
+                let list = b.read::<u32>(18)? as usize;
+                for i in first..first + count {
+                    let layer = list + b.read::<u32>(list + 4 + i * 4)? as usize;
+                    self.paint_node(layer as u32, depth + 1, painter)?;
+                }
+            }
+            // PaintSolid, PaintVarSolid
+            2 | 3 => {
+                let index = b.read::<u16>(p + 1)?;
+                let mut alpha = f2dot14(b.read::<i16>(p + 3)?);
+                if format == 3 {
+                    alpha += self.delta(b.read::<u32>(p + 5)?, 0) / 16384.;
+                }
+                painter.fill(&Brush::Solid(color(index, alpha)));
+            }
+            // PaintLinearGradient, PaintVarLinearGradient
+            4 | 5 => {
+                let mut v = self.read_fwords(p + 4, 6, format == 5, p + 16)?;
+                for v in v.iter_mut() {
+                    *v *= s;
+                }
+                let extend = self.color_line(child()? as usize, format == 5)?;
+                let p0 = Point::new(v[0], v[1]);
+                let p1 = Point::new(v[2], v[3]);
+                let p2 = Point::new(v[4], v[5]);
+                // The gradient is perpendicular to the line from p0 to p2, so
+                // project p1 onto the normal of that line.
+                let n = Point::new(p0.y - p2.y, p2.x - p0.x);
+                let nn = n.dot(n);
+                let end = if nn != 0. {
+                    p0 + n * ((p1 - p0).dot(n) / nn)
+                } else {
+                    p1
+                };
+                painter.fill(&Brush::LinearGradient {
+                    start: p0,
+                    end,
+                    stops: self.stops,
+                    extend,
+                });
+            }
+            // PaintRadialGradient, PaintVarRadialGradient
+            6 | 7 => {
+                let mut v = [0f32; 6];
+                for (i, v) in v.iter_mut().enumerate() {
+                    // Radii are unsigned.
+                    *v = if i == 2 || i == 5 {
+                        b.read::<u16>(p + 4 + i * 2)? as f32
+                    } else {
+                        b.read::<i16>(p + 4 + i * 2)? as f32
+                    };
+                    if format == 7 {
+                        *v += self.delta(b.read::<u32>(p + 16)?, i as u32);
+                    }
+                    *v *= s;
+                }
+                let extend = self.color_line(child()? as usize, format == 7)?;
+                painter.fill(&Brush::RadialGradient {
+                    start_center: Point::new(v[0], v[1]),
+                    start_radius: v[2],
+                    end_center: Point::new(v[3], v[4]),
+                    end_radius: v[5],
+                    stops: self.stops,
+                    extend,
+                });
+            }
+            // PaintSweepGradient, PaintVarSweepGradient
+            8 | 9 => {
+                let c = self.read_fwords(p + 4, 2, format == 9, p + 12)?;
+                let mut angles = [0f32; 2];
+                for (i, a) in angles.iter_mut().enumerate() {
+                    *a = f2dot14(b.read::<i16>(p + 8 + i * 2)?);
+                    if format == 9 {
+                        *a += self.delta(b.read::<u32>(p + 12)?, 2 + i as u32) / 16384.;
+                    }
+                }
+                let extend = self.color_line(child()? as usize, format == 9)?;
+                // Angles are biased by 180 degrees so that a full sweep is
+                // representable.
+                painter.fill(&Brush::SweepGradient {
+                    center: Point::new(c[0] * s, c[1] * s),
+                    start_angle: (angles[0] + 1.) * 180.,
+                    end_angle: (angles[1] + 1.) * 180.,
+                    stops: self.stops,
+                    extend,
+                });
+            }
+            // PaintGlyph
+            10 => {
+                let glyph_id = b.read::<u16>(p + 4)?;
+                self.outline.clear();
+                if !(self.outlines)(glyph_id, self.outline) {
+                    return None;
+                }
+                painter.push_clip_glyph(glyph_id, self.outline);
+                let result = self.paint_node(child()?, depth + 1, painter);
+                painter.pop_clip();
+                result?;
+            }
+            // PaintColrGlyph
+            11 => {
+                let glyph_id = b.read::<u16>(p + 1)?;
+                let paint = self.proxy.paint(self.data, glyph_id)?;
+                self.paint_root(glyph_id, paint, depth + 1, painter)?;
+            }
+            // PaintComposite
+            32 => {
+                let source = child()?;
+                let mode = CompositeMode::from_raw(b.read::<u8>(p + 4)?)?;
+                let backdrop = (p + b.read::<U24>(p + 5)?.0 as usize) as u32;
+                painter.push_layer(CompositeMode::SrcOver);
+                let mut result = self.paint_node(backdrop, depth + 1, painter);
+                if result.is_some() {
+                    painter.push_layer(mode);
+                    result = self.paint_node(source, depth + 1, painter);
+                    painter.pop_layer();
+                }
+                painter.pop_layer();
+                result?;
+            }
+            // Transforms
+            12..=31 => {
+                let t = self.transform(p, format)?;
+                // Convert from font units to the scaled space.
+                let t = Transform::new(t[0], t[1], t[2], t[3], t[4] * s, t[5] * s);
+                painter.push_transform(t);
+                let result = self.paint_node(child()?, depth + 1, painter);
+                painter.pop_transform();
+                result?;
+            }
+            _ => {}
+        }
+        Some(())
+    }
+
+    /// Reads the transform for a paint in the range 12..=31 as the
+    /// components `[xx, xy, yx, yy, x, y]` of a transform that maps a point
+    /// to `(x * xx + y * yx + x, x * xy + y * yy + y)`.
+    fn transform(&self, p: usize, format: u8) -> Option<[f32; 6]> {
+        let b = &self.colr;
+        let var = format & 1 == 1;
+        if format == 12 || format == 13 {
+            let affine = p + b.read::<U24>(p + 4)?.0 as usize;
+            let mut v = [0f32; 6];
+            for (i, v) in v.iter_mut().enumerate() {
+                let mut raw = b.read::<i32>(affine + i * 4)? as f32;
+                if var {
+                    raw += self.delta(b.read::<u32>(affine + 24)?, i as u32);
+                }
+                *v = raw / 65536.;
+            }
+            // Affine2x3 is stored as xx, yx, xy, yy, dx, dy.
+            return Some([v[0], v[1], v[2], v[3], v[4], v[5]]);
+        }
+        // Reads the F2DOT14 values followed by FWORD center coordinates for
+        // the remaining formats.
+        let (scalars, centers) = match format {
+            14 | 15 => (0, 2),
+            16 | 17 => (2, 0),
+            18 | 19 => (2, 2),
+            20 | 21 => (1, 0),
+            22 | 23 => (1, 2),
+            24 | 25 => (1, 0),
+            26 | 27 => (1, 2),
+            28 | 29 => (2, 0),
+            30 | 31 => (2, 2),
+            _ => return None,
+        };
+        let count = scalars + centers;
+        let var_base = p + 4 + count * 2;
+        let mut v = [0f32; 4];
+        for (i, v) in v.iter_mut().enumerate().take(count) {
+            let mut raw = b.read::<i16>(p + 4 + i * 2)? as f32;
+            if var {
+                raw += self.delta(b.read::<u32>(var_base)?, i as u32);
+            }
+            *v = if i < scalars { raw / 16384. } else { raw };
+        }
+        let (cx, cy) = if centers != 0 {
+            (v[scalars], v[scalars + 1])
+        } else {
+            (0., 0.)
+        };
+        let m = match format {
+            14 | 15 => return Some([1., 0., 0., 1., v[0], v[1]]),
+            16..=19 => [v[0], 0., 0., v[1]],
+            20..=23 => [v[0], 0., 0., v[0]],
+            24..=27 => {
+                let (sin, cos) = (v[0] * core::f32::consts::PI).sin_cos();
+                [cos, sin, -sin, cos]
+            }
+            _ => {
+                let x = (v[0] * core::f32::consts::PI).tan();
+                let y = (v[1] * core::f32::consts::PI).tan();
+                [1., y, -x, 1.]
+            }
+        };
+        // Apply the transform around the center point.
+        let x = cx - (cx * m[0] + cy * m[2]);
+        let y = cy - (cx * m[1] + cy * m[3]);
+        Some([m[0], m[1], m[2], m[3], x, y])
+    }
+
+    /// Reads a sequence of FWORD values with optional deltas.
+    fn read_fwords(&self, p: usize, count: usize, var: bool, base: usize) -> Option<[f32; 6]> {
+        let mut v = [0f32; 6];
+        for (i, v) in v.iter_mut().enumerate().take(count) {
+            *v = self.colr.read::<i16>(p + i * 2)? as f32;
+            if var {
+                *v += self.delta(self.colr.read::<u32>(base)?, i as u32);
+            }
+        }
+        Some(v)
+    }
+
+    /// Loads the color stops of the color line at the specified offset,
+    /// sorted by position.
+    fn color_line(&mut self, l: usize, var: bool) -> Option<Extend> {
+        let b = self.colr;
+        let extend = match b.read::<u8>(l)? {
+            1 => Extend::Repeat,
+            2 => Extend::Reflect,
+            _ => Extend::Pad,
+        };
+        let count = b.read::<u16>(l + 1)? as usize;
+        let size = if var { 10 } else { 6 };
+        self.stops.clear();
+        for i in 0..count {
+            let stop = l + 3 + i * size;
+            let mut offset = f2dot14(b.read::<i16>(stop)?);
+            let index = b.read::<u16>(stop + 2)?;
+            let mut alpha = f2dot14(b.read::<i16>(stop + 4)?);
+            if var {
+                let base = b.read::<u32>(stop + 6)?;
+                offset += self.delta(base, 0) / 16384.;
+                alpha += self.delta(base, 1) / 16384.;
+            }
+            self.stops.push(ColorStop {
+                offset,
+                color: color(index, alpha),
+            });
+        }
+        self.stops
+            .sort_by(|a, b| a.offset.partial_cmp(&b.offset).unwrap_or(core::cmp::Ordering::Equal));
+        Some(extend)
+    }
+
+    /// Returns the delta in raw units for the value at the specified
+    /// position relative to a variation index base.
+    fn delta(&self, base: u32, index: u32) -> f32 {
+        if self.var_store == 0 || base == 0xFFFFFFFF {
+            return 0.;
+        }
+        let index = base.wrapping_add(index);
+        let (outer, inner) = if self.var_map != 0 {
+            match delta_set_index(self.data, self.var_map, index) {
+                Some(entry) => entry,
+                _ => return 0.,
+            }
+        } else {
+            ((index >> 16) as u16, index as u16)
+        };
+        item_delta(self.data, self.var_store, outer, inner, self.coords)
+            .map(|d| d.to_f32())
+            .unwrap_or(0.)
+    }
+}
+
+/// Maps an index through a delta set index map.
+fn delta_set_index(data: &[u8], map: u32, index: u32) -> Option<(u16, u16)> {
+    let b = Bytes::with_offset(data, map as usize)?;
+    let format = b.read::<u8>(0)?;
+    let entry_format = b.read::<u8>(1)? as u32;
+    let (count, base) = match format {
+        0 => (b.read::<u16>(2)? as u32, 4),
+        1 => (b.read::<u32>(2)?, 6),
+        _ => return None,
+    };
+    if count == 0 {
+        return None;
+    }
+    let index = index.min(count - 1) as usize;
+    let entry_size = ((entry_format >> 4) & 3) + 1;
+    let inner_bits = (entry_format & 0xF) + 1;
+    let offset = base + index * entry_size as usize;
+    let entry = match entry_size {
+        1 => b.read::<u8>(offset)? as u32,
+        2 => b.read::<u16>(offset)? as u32,
+        3 => b.read::<U24>(offset)?.0,
+        _ => b.read::<u32>(offset)?,
+    };
+    Some(((entry >> inner_bits) as u16, (entry & ((1 << inner_bits) - 1)) as u16))
+}
+
+fn f2dot14(value: i16) -> f32 {
+    value as f32 / 16384.
+}
+
+fn color(index: u16, alpha: f32) -> PaintColor {
+    PaintColor {
+        palette_index: if index != 0xFFFF { Some(index) } else { None },
+        alpha: alpha.clamp(0., 1.),
+    }
+}
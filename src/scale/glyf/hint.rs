@@ -9,6 +9,8 @@ pub enum HinterMode {
     /// "Full" hinting mode. May generate rough outlines and poor horizontal
     /// spacing.
     Legacy,
+    /// Same as legacy, but reports a monochrome target to the font.
+    Monochrome,
     /// Subpixel mode for grayscale rendering.
     GrayscaleSubpixel,
     /// Cleartype subpixel mode.
@@ -848,6 +850,7 @@ impl<'a> Hinter<'a> {
         }
         let (v35, grayscale, subpixel, grayscale_cleartype) = match state.mode {
             HinterMode::Legacy => (true, true, false, false),
+            HinterMode::Monochrome => (true, false, false, false),
             HinterMode::GrayscaleSubpixel => (false, false, true, true),
             HinterMode::Subpixel => (false, false, true, false),
             HinterMode::Modern => (false, false, true, false),
@@ -861,7 +864,6 @@ impl<'a> Hinter<'a> {
         } else {
             self.compat = false;
         }
-        self.compat = true;
         state.compat = self.compat;
         self.dv = Point::new(0x4000, 0);
         self.pv = self.dv;
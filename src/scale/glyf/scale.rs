@@ -3,8 +3,10 @@ use super::internal::{
     fixed::{div, mul, muldiv, round},
     Stream,
 };
+use super::hint::HinterMode;
 use super::proxy::GlyfProxy;
 use super::Point;
+use crate::scale::Hinting;
 use crate::{metrics::MetricsProxy, CacheKey, FontRef};
 
 #[derive(Default)]
@@ -187,7 +189,9 @@ impl Scaler {
                         p.x = round(p.x);
                         p.y = round(p.y);
                     }
-                    self.hint(state, point_base, contour_base, ins, false);
+                    if self.hint(state, point_base, contour_base, ins, false) {
+                        self.restore_x(state, point_base);
+                    }
                 }
                 if point_base != 0 {
                     for c in &mut self.contours[contour_base..contour_end] {
@@ -380,7 +384,9 @@ impl Scaler {
                     for t in &mut self.tags[point_base..] {
                         *t &= !(0x08 | 0x10);
                     }
-                    self.hint(state, point_base, contour_base, ins, true);
+                    if self.hint(state, point_base, contour_base, ins, true) {
+                        self.restore_x(state, point_base);
+                    }
                     self.scaled.truncate(self.scaled.len() - 4);
                     self.tags.truncate(self.tags.len() - 4);
                 }
@@ -401,7 +407,6 @@ impl Scaler {
         ins: &[u8],
         is_composite: bool,
     ) -> bool {
-        use super::hint::HinterMode;
         let slot = match state.slot {
             Some(slot) => slot,
             None => {
@@ -412,7 +417,7 @@ impl Scaler {
                     state.coords,
                     state.ppem,
                     state.scale,
-                    HinterMode::Modern,
+                    state.mode,
                 ) {
                     Some(slot) => {
                         state.slot = Some(slot);
@@ -443,6 +448,16 @@ impl Scaler {
         );
         true
     }
+
+    /// Discards hinting adjustments along the x-axis for light hinting.
+    fn restore_x(&mut self, state: &ScalerState, point_base: usize) {
+        if !state.light {
+            return;
+        }
+        for (p, o) in self.scaled[point_base..].iter_mut().zip(&self.original) {
+            p.x = o.x;
+        }
+    }
 }
 
 /// Per-component setup.
@@ -501,6 +516,9 @@ pub struct ScalerState<'a> {
     pub ppem: u16,
     pub scale: i32,
     pub hint: bool,
+    pub mode: HinterMode,
+    /// True if adjustments along the x-axis should be discarded.
+    pub light: bool,
     pub vary: bool,
     pub xmin: i32,
     pub xmax: i32,
@@ -517,7 +535,7 @@ impl<'a> ScalerState<'a> {
         proxy: &'a GlyfProxy,
         metrics: &'a MetricsProxy,
         size: f32,
-        hint: bool,
+        hinting: Hinting,
     ) -> Self {
         let size = size.abs();
         let ppem = size as u16;
@@ -527,6 +545,11 @@ impl<'a> ScalerState<'a> {
         } else {
             (false, 0)
         };
+        let mode = match hinting {
+            Hinting::Normal => HinterMode::Legacy,
+            Hinting::Mono => HinterMode::Monochrome,
+            _ => HinterMode::Modern,
+        };
         Self {
             data,
             id,
@@ -537,7 +560,9 @@ impl<'a> ScalerState<'a> {
             have_scale,
             ppem,
             scale,
-            hint,
+            hint: hinting != Hinting::None,
+            mode,
+            light: hinting == Hinting::Light,
             vary: proxy.axis_count != 0 && !coords.is_empty() && proxy.gvar != 0,
             xmin: 0,
             xmax: 0,
@@ -7,6 +7,7 @@ use super::Source;
 
 /// Content of a scaled glyph image.
 #[derive(Copy, Clone, PartialEq, Eq, Debug)]
+#[non_exhaustive]
 pub enum Content {
     /// 8-bit alpha mask.
     Mask,
@@ -14,6 +15,10 @@ pub enum Content {
     SubpixelMask,
     /// 32-bit RGBA bitmap.
     Color,
+    /// 8-bit signed distance field. A value of 128 lies on the outline and
+    /// values increase towards the inside, saturating at the spread. The
+    /// placement is expressed in field samples.
+    DistanceField,
 }
 
 impl Default for Content {
@@ -0,0 +1,237 @@
+//! Instancing of CFF2 outlines.
+
+use super::super::cff::{CffProxy, GlyphSink};
+use super::{push_u16, GlyphInfo};
+use crate::FontRef;
+
+// Charstring operators.
+const RLINETO: u8 = 5;
+const RRCURVETO: u8 = 8;
+const ENDCHAR: u8 = 14;
+const RMOVETO: u8 = 21;
+
+// Dictionary operators.
+const FONT_BBOX: u8 = 5;
+const CHARSET: u8 = 15;
+const CHAR_STRINGS: u8 = 17;
+const PRIVATE: u8 = 18;
+const NOMINAL_WIDTH_X: u8 = 21;
+
+/// Number of standard strings predefined by the CFF specification.
+const STANDARD_STRING_COUNT: usize = 391;
+
+/// Evaluates all CFF2 glyphs at the specified coordinates and returns a
+/// name-keyed CFF table containing the resulting outlines. The advances in
+/// `glyphs` are written as charstring widths and the side bearings and
+/// bounds are updated from the outlines.
+pub fn instance(
+    font: &FontRef,
+    proxy: &CffProxy,
+    coords: &[i16],
+    name: &str,
+    glyphs: &mut [GlyphInfo],
+) -> Option<Vec<u8>> {
+    let cff = proxy.materialize(font);
+    let mut char_strings = Vec::with_capacity(glyphs.len());
+    for (gid, info) in glyphs.iter_mut().enumerate() {
+        let mut sink = CharStringSink::new(info.advance);
+        if let Some(glyph) = cff.get(gid as u16) {
+            if !glyph.path(1., coords, None, &mut sink) {
+                sink = CharStringSink::new(info.advance);
+            }
+        }
+        info.bounds = sink.bounds;
+        info.lsb = sink.bounds.map(|b| b[0]).unwrap_or(0);
+        char_strings.push(sink.finish());
+    }
+    let mut bounds = [0i32; 4];
+    for b in glyphs.iter().filter_map(|g| g.bounds) {
+        bounds = [
+            bounds[0].min(b[0]),
+            bounds[1].min(b[1]),
+            bounds[2].max(b[2]),
+            bounds[3].max(b[3]),
+        ];
+    }
+    let name = sanitize_name(name);
+    let mut names = Vec::with_capacity(glyphs.len());
+    for gid in 1..glyphs.len() {
+        names.push(format!("glyph{:05}", gid).into_bytes());
+    }
+    let mut name_index = Vec::new();
+    write_index(&mut name_index, core::iter::once(name.as_bytes()));
+    let mut string_index = Vec::new();
+    write_index(&mut string_index, names.iter().map(|n| &n[..]));
+    let mut gsubr_index = Vec::new();
+    write_index(&mut gsubr_index, core::iter::empty());
+    let mut charset = vec![0u8];
+    for i in 0..names.len() {
+        push_u16(&mut charset, (STANDARD_STRING_COUNT + i) as u16);
+    }
+    let mut char_strings_index = Vec::new();
+    write_index(&mut char_strings_index, char_strings.iter().map(|c| &c[..]));
+    let private = [139, NOMINAL_WIDTH_X];
+    // The top dictionary uses fixed size operands, so its length does not
+    // depend on the offsets it contains.
+    const TOP_DICT_LEN: usize = 21 + 6 + 6 + 11;
+    let mut top_dict_index = Vec::new();
+    write_index(&mut top_dict_index, core::iter::once(&[0u8; TOP_DICT_LEN][..]));
+    let header_len = 4;
+    let charset_offset =
+        header_len + name_index.len() + top_dict_index.len() + string_index.len() + gsubr_index.len();
+    let char_strings_offset = charset_offset + charset.len();
+    let private_offset = char_strings_offset + char_strings_index.len();
+    let mut top_dict = Vec::with_capacity(TOP_DICT_LEN);
+    for v in &bounds {
+        push_int(&mut top_dict, *v);
+    }
+    top_dict.push(FONT_BBOX);
+    push_int(&mut top_dict, charset_offset as i32);
+    top_dict.push(CHARSET);
+    push_int(&mut top_dict, char_strings_offset as i32);
+    top_dict.push(CHAR_STRINGS);
+    push_int(&mut top_dict, private.len() as i32);
+    push_int(&mut top_dict, private_offset as i32);
+    top_dict.push(PRIVATE);
+    top_dict_index.clear();
+    write_index(&mut top_dict_index, core::iter::once(&top_dict[..]));
+    let mut out = vec![1, 0, header_len as u8, 4];
+    out.extend_from_slice(&name_index);
+    out.extend_from_slice(&top_dict_index);
+    out.extend_from_slice(&string_index);
+    out.extend_from_slice(&gsubr_index);
+    out.extend_from_slice(&charset);
+    out.extend_from_slice(&char_strings_index);
+    out.extend_from_slice(&private);
+    Some(out)
+}
+
+/// Sink that encodes path commands as a Type 2 charstring.
+struct CharStringSink {
+    data: Vec<u8>,
+    x: i32,
+    y: i32,
+    bounds: Option<[i32; 4]>,
+}
+
+impl CharStringSink {
+    fn new(advance: i32) -> Self {
+        let mut data = Vec::new();
+        // Width is encoded relative to the nominal width of zero and is
+        // consumed by the first stack clearing operator.
+        if advance != 0 {
+            push_number(&mut data, advance);
+        }
+        Self {
+            data,
+            x: 0,
+            y: 0,
+            bounds: None,
+        }
+    }
+
+    fn point(&mut self, x: f32, y: f32) {
+        let (x, y) = (x.round() as i32, y.round() as i32);
+        push_number(&mut self.data, x - self.x);
+        push_number(&mut self.data, y - self.y);
+        self.x = x;
+        self.y = y;
+        self.bounds = Some(match self.bounds {
+            Some(b) => [b[0].min(x), b[1].min(y), b[2].max(x), b[3].max(y)],
+            None => [x, y, x, y],
+        });
+    }
+
+    fn finish(mut self) -> Vec<u8> {
+        self.data.push(ENDCHAR);
+        self.data
+    }
+}
+
+impl GlyphSink for CharStringSink {
+    fn move_to(&mut self, x: f32, y: f32) {
+        self.point(x, y);
+        self.data.push(RMOVETO);
+    }
+
+    fn line_to(&mut self, x: f32, y: f32) {
+        self.point(x, y);
+        self.data.push(RLINETO);
+    }
+
+    fn curve_to(&mut self, cx1: f32, cy1: f32, cx2: f32, cy2: f32, x: f32, y: f32) {
+        self.point(cx1, cy1);
+        self.point(cx2, cy2);
+        self.point(x, y);
+        self.data.push(RRCURVETO);
+    }
+
+    fn close(&mut self) {}
+}
+
+/// Encodes an integer operand in a charstring.
+fn push_number(out: &mut Vec<u8>, v: i32) {
+    if (-107..=107).contains(&v) {
+        out.push((v + 139) as u8);
+    } else if (108..=1131).contains(&v) {
+        let v = v - 108;
+        out.push(((v >> 8) + 247) as u8);
+        out.push(v as u8);
+    } else if (-1131..=-108).contains(&v) {
+        let v = -v - 108;
+        out.push(((v >> 8) + 251) as u8);
+        out.push(v as u8);
+    } else {
+        let v = v.max(i16::MIN as i32).min(i16::MAX as i32) as i16;
+        out.push(28);
+        out.extend_from_slice(&v.to_be_bytes());
+    }
+}
+
+/// Encodes a fixed size integer operand in a dictionary.
+fn push_int(out: &mut Vec<u8>, v: i32) {
+    out.push(29);
+    out.extend_from_slice(&v.to_be_bytes());
+}
+
+fn write_index<'b>(out: &mut Vec<u8>, items: impl Iterator<Item = &'b [u8]> + Clone) {
+    let count = items.clone().count();
+    push_u16(out, count as u16);
+    if count == 0 {
+        return;
+    }
+    let total: usize = items.clone().map(|i| i.len()).sum::<usize>() + 1;
+    let offset_size = match total {
+        0..=0xFF => 1,
+        0x100..=0xFFFF => 2,
+        0x10000..=0xFFFFFF => 3,
+        _ => 4,
+    };
+    out.push(offset_size as u8);
+    let mut offset = 1usize;
+    let push_offset = |out: &mut Vec<u8>, offset: usize| {
+        out.extend_from_slice(&(offset as u32).to_be_bytes()[4 - offset_size..]);
+    };
+    push_offset(out, offset);
+    for item in items.clone() {
+        offset += item.len();
+        push_offset(out, offset);
+    }
+    for item in items {
+        out.extend_from_slice(item);
+    }
+}
+
+/// Returns a valid PostScript font name.
+fn sanitize_name(name: &str) -> String {
+    let name: String = name
+        .chars()
+        .filter(|c| c.is_ascii_graphic() && !"[](){}<>/%".contains(*c))
+        .take(63)
+        .collect();
+    if name.is_empty() {
+        "Instance".into()
+    } else {
+        name
+    }
+}
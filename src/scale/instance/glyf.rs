@@ -0,0 +1,530 @@
+//! Instancing of TrueType outlines.
+
+use super::super::glyf::{GlyfProxy, Point};
+use super::super::internal::Stream;
+use super::{push_u16, GlyphInfo};
+use crate::GlyphMetrics;
+
+const ON_CURVE: u8 = 0x01;
+const X_SHORT: u8 = 0x02;
+const Y_SHORT: u8 = 0x04;
+const REPEAT: u8 = 0x08;
+const X_SAME: u8 = 0x10;
+const Y_SAME: u8 = 0x20;
+const OVERLAP_SIMPLE: u8 = 0x40;
+
+const ARGS_ARE_WORDS: u16 = 0x001;
+const ARGS_ARE_XY_VALUES: u16 = 0x002;
+const HAVE_SCALE: u16 = 0x008;
+const MORE_COMPONENTS: u16 = 0x020;
+const HAVE_XY_SCALE: u16 = 0x040;
+const HAVE_MATRIX: u16 = 0x080;
+const HAVE_INSTRUCTIONS: u16 = 0x100;
+const SCALED_COMPONENT_OFFSET: u16 = 0x800;
+const UNSCALED_COMPONENT_OFFSET: u16 = 0x1000;
+
+/// Instanced `glyf` and `loca` tables.
+#[derive(Default)]
+pub struct Output {
+    pub glyf: Vec<u8>,
+    pub loca: Vec<u8>,
+    pub loca_fmt: u16,
+    pub metrics: Vec<GlyphInfo>,
+}
+
+/// Applies the variation deltas for the specified coordinates to all glyphs
+/// in the font. If `hvar` is provided, advances are taken from the
+/// associated metrics. Otherwise, they are computed from the phantom points.
+pub fn instance(
+    data: &[u8],
+    proxy: &GlyfProxy,
+    coords: &[i16],
+    glyph_count: u16,
+    metrics: &GlyphMetrics,
+    hvar: Option<&GlyphMetrics>,
+    out: &mut Output,
+) -> Option<()> {
+    let vary = proxy.gvar != 0 && coords.iter().any(|c| *c != 0);
+    let mut glyphs = Vec::with_capacity(glyph_count as usize);
+    let mut scratch = Scratch::default();
+    for gid in 0..glyph_count {
+        let glyph_data = proxy.glyph_data(data, gid).unwrap_or(&[]);
+        let mut glyph = Glyph::parse(glyph_data)?;
+        let advance = metrics.advance_width(gid) as i32;
+        let lsb = metrics.lsb(gid) as i32;
+        // Phantom points as specified by the TrueType instancing model.
+        let xmin = glyph.header_bounds[0];
+        let ymax = glyph.header_bounds[3];
+        let mut phantom = [
+            Point::new(xmin - lsb, 0),
+            Point::new(xmin - lsb + advance, 0),
+            Point::new(advance / 2, ymax),
+            Point::new(advance / 2, ymax),
+        ];
+        if vary {
+            glyph.apply_deltas(data, proxy, coords, gid, &mut phantom, &mut scratch);
+        }
+        let advance = match hvar {
+            Some(hvar) => hvar.advance_width(gid).round() as i32,
+            None => phantom[1].x - phantom[0].x,
+        };
+        glyphs.push((glyph, advance, phantom[0].x));
+    }
+    out.glyf.clear();
+    out.metrics.clear();
+    let mut offsets = Vec::with_capacity(glyphs.len() + 1);
+    let mut points = Vec::new();
+    for (gid, (glyph, advance, origin)) in glyphs.iter().enumerate() {
+        points.clear();
+        let bounds = if !vary {
+            match glyph.outline {
+                Outline::Empty => None,
+                _ => Some(glyph.header_bounds),
+            }
+        } else if flatten(&glyphs, gid, 0, &mut points).is_some() {
+            bounds(&points)
+        } else {
+            None
+        };
+        offsets.push(out.glyf.len());
+        glyph.write(bounds.unwrap_or_default(), &mut out.glyf);
+        if out.glyf.len() & 1 != 0 {
+            out.glyf.push(0);
+        }
+        let lsb = bounds.map(|b| b[0] - origin).unwrap_or(0);
+        out.metrics.push(GlyphInfo {
+            advance: *advance,
+            lsb,
+            bounds,
+        });
+    }
+    offsets.push(out.glyf.len());
+    out.loca.clear();
+    if out.glyf.len() <= 0x1FFFE {
+        out.loca_fmt = 0;
+        for offset in offsets {
+            push_u16(&mut out.loca, (offset / 2) as u16);
+        }
+    } else {
+        out.loca_fmt = 1;
+        for offset in offsets {
+            out.loca.extend_from_slice(&(offset as u32).to_be_bytes());
+        }
+    }
+    Some(())
+}
+
+#[derive(Default)]
+struct Scratch {
+    points: Vec<Point>,
+    tags: Vec<u8>,
+    accum: Vec<Point>,
+    deltas: Vec<Point>,
+}
+
+enum Outline<'a> {
+    Empty,
+    Simple {
+        contours: Vec<u16>,
+        points: Vec<Point>,
+        flags: Vec<u8>,
+        instructions: &'a [u8],
+    },
+    Composite {
+        components: Vec<Component<'a>>,
+        instructions: Option<&'a [u8]>,
+    },
+}
+
+struct Component<'a> {
+    flags: u16,
+    glyph_id: u16,
+    args: [i32; 2],
+    transform: &'a [u8],
+}
+
+impl Component<'_> {
+    /// Returns the transform as (xx, yx, xy, yy).
+    fn matrix(&self) -> [f32; 4] {
+        let b = super::Bytes::new(self.transform);
+        let v = |i: usize| b.read_i16(i * 2).unwrap_or(0) as f32 / 16384.;
+        match self.transform.len() {
+            2 => [v(0), 0., 0., v(0)],
+            4 => [v(0), 0., 0., v(1)],
+            8 => [v(0), v(1), v(2), v(3)],
+            _ => [1., 0., 0., 1.],
+        }
+    }
+}
+
+struct Glyph<'a> {
+    header_bounds: [i32; 4],
+    outline: Outline<'a>,
+}
+
+impl<'a> Glyph<'a> {
+    fn parse(data: &'a [u8]) -> Option<Self> {
+        if data.is_empty() {
+            return Some(Self {
+                header_bounds: [0; 4],
+                outline: Outline::Empty,
+            });
+        }
+        let mut s = Stream::new(data);
+        let contour_count = s.read_i16()?;
+        let header_bounds = [
+            s.read_i16()? as i32,
+            s.read_i16()? as i32,
+            s.read_i16()? as i32,
+            s.read_i16()? as i32,
+        ];
+        let outline = if contour_count >= 0 {
+            Self::parse_simple(&mut s, contour_count as usize)?
+        } else {
+            Self::parse_composite(&mut s)?
+        };
+        Some(Self {
+            header_bounds,
+            outline,
+        })
+    }
+
+    fn parse_simple(s: &mut Stream<'a>, contour_count: usize) -> Option<Outline<'a>> {
+        let mut contours = Vec::with_capacity(contour_count);
+        for _ in 0..contour_count {
+            contours.push(s.read_u16()?);
+        }
+        let point_count = contours.last().map(|c| *c as usize + 1).unwrap_or(0);
+        let ins_len = s.read_u16()? as usize;
+        let instructions = s.read_bytes(ins_len)?;
+        let mut flags = Vec::with_capacity(point_count);
+        while flags.len() < point_count {
+            let flag = s.read_u8()?;
+            let count = if flag & REPEAT != 0 {
+                s.read_u8()? as usize + 1
+            } else {
+                1
+            };
+            for _ in 0..count.min(point_count - flags.len()) {
+                flags.push(flag);
+            }
+        }
+        let mut points = vec![Point::default(); point_count];
+        let mut v = 0i32;
+        for (flag, point) in flags.iter().zip(points.iter_mut()) {
+            if flag & X_SHORT != 0 {
+                let delta = s.read_u8()? as i32;
+                v += if flag & X_SAME != 0 { delta } else { -delta };
+            } else if flag & X_SAME == 0 {
+                v += s.read_i16()? as i32;
+            }
+            point.x = v;
+        }
+        v = 0;
+        for (flag, point) in flags.iter().zip(points.iter_mut()) {
+            if flag & Y_SHORT != 0 {
+                let delta = s.read_u8()? as i32;
+                v += if flag & Y_SAME != 0 { delta } else { -delta };
+            } else if flag & Y_SAME == 0 {
+                v += s.read_i16()? as i32;
+            }
+            point.y = v;
+        }
+        for (i, flag) in flags.iter_mut().enumerate() {
+            *flag &= if i == 0 { ON_CURVE | OVERLAP_SIMPLE } else { ON_CURVE };
+        }
+        Some(Outline::Simple {
+            contours,
+            points,
+            flags,
+            instructions,
+        })
+    }
+
+    fn parse_composite(s: &mut Stream<'a>) -> Option<Outline<'a>> {
+        let mut components = Vec::new();
+        loop {
+            let flags = s.read_u16()?;
+            let glyph_id = s.read_u16()?;
+            let args = match (flags & ARGS_ARE_WORDS != 0, flags & ARGS_ARE_XY_VALUES != 0) {
+                (true, true) => [s.read_i16()? as i32, s.read_i16()? as i32],
+                (true, false) => [s.read_u16()? as i32, s.read_u16()? as i32],
+                (false, true) => [s.read_i8()? as i32, s.read_i8()? as i32],
+                (false, false) => [s.read_u8()? as i32, s.read_u8()? as i32],
+            };
+            let transform_len = if flags & HAVE_SCALE != 0 {
+                2
+            } else if flags & HAVE_XY_SCALE != 0 {
+                4
+            } else if flags & HAVE_MATRIX != 0 {
+                8
+            } else {
+                0
+            };
+            let transform = s.read_bytes(transform_len)?;
+            components.push(Component {
+                flags,
+                glyph_id,
+                args,
+                transform,
+            });
+            if flags & MORE_COMPONENTS == 0 {
+                break;
+            }
+        }
+        let instructions = if components
+            .iter()
+            .any(|c| c.flags & HAVE_INSTRUCTIONS != 0)
+        {
+            let len = s.read_u16()? as usize;
+            Some(s.read_bytes(len)?)
+        } else {
+            None
+        };
+        Some(Outline::Composite {
+            components,
+            instructions,
+        })
+    }
+
+    fn apply_deltas(
+        &mut self,
+        data: &[u8],
+        proxy: &GlyfProxy,
+        coords: &[i16],
+        glyph_id: u16,
+        phantom: &mut [Point; 4],
+        scratch: &mut Scratch,
+    ) {
+        match &mut self.outline {
+            Outline::Empty => {
+                let deltas = &mut scratch.deltas;
+                deltas.clear();
+                deltas.resize(4, Point::default());
+                if proxy.composite_deltas(data, coords, glyph_id, deltas) {
+                    apply_phantom(phantom, deltas);
+                }
+            }
+            Outline::Simple {
+                contours, points, ..
+            } => {
+                let len = points.len() + 4;
+                scratch.points.clear();
+                scratch.points.extend_from_slice(points);
+                scratch.points.extend_from_slice(&phantom[..]);
+                scratch.tags.clear();
+                scratch.tags.resize(len, 0);
+                scratch.accum.clear();
+                scratch.accum.resize(len, Point::default());
+                scratch.deltas.clear();
+                scratch.deltas.resize(len, Point::default());
+                if proxy.deltas(
+                    data,
+                    coords,
+                    glyph_id,
+                    &scratch.points,
+                    &mut scratch.tags,
+                    contours,
+                    &mut scratch.accum,
+                    &mut scratch.deltas,
+                ) {
+                    for (p, d) in points.iter_mut().zip(&scratch.deltas) {
+                        p.x += d.x;
+                        p.y += d.y;
+                    }
+                    apply_phantom(phantom, &scratch.deltas[len - 4..]);
+                }
+            }
+            Outline::Composite { components, .. } => {
+                let len = components.len() + 4;
+                let deltas = &mut scratch.deltas;
+                deltas.clear();
+                deltas.resize(len, Point::default());
+                if proxy.composite_deltas(data, coords, glyph_id, deltas) {
+                    for (c, d) in components.iter_mut().zip(deltas.iter()) {
+                        if c.flags & ARGS_ARE_XY_VALUES != 0 {
+                            c.args[0] += d.x;
+                            c.args[1] += d.y;
+                        }
+                    }
+                    apply_phantom(phantom, &deltas[len - 4..]);
+                }
+            }
+        }
+    }
+
+    fn write(&self, bounds: [i32; 4], out: &mut Vec<u8>) {
+        match &self.outline {
+            Outline::Empty => {}
+            Outline::Simple {
+                contours,
+                points,
+                flags,
+                instructions,
+            } => {
+                write_header(out, contours.len() as i16, bounds);
+                for c in contours {
+                    push_u16(out, *c);
+                }
+                push_u16(out, instructions.len() as u16);
+                out.extend_from_slice(instructions);
+                let mut encoded = Vec::with_capacity(points.len());
+                let mut xs = Vec::new();
+                let mut ys = Vec::new();
+                let mut last = Point::default();
+                for (point, flag) in points.iter().zip(flags) {
+                    let mut flag = *flag;
+                    flag |= encode_coord(point.x - last.x, X_SHORT, X_SAME, &mut xs);
+                    flag |= encode_coord(point.y - last.y, Y_SHORT, Y_SAME, &mut ys);
+                    encoded.push(flag);
+                    last = *point;
+                }
+                let mut i = 0;
+                while i < encoded.len() {
+                    let flag = encoded[i];
+                    let mut run = 1;
+                    while i + run < encoded.len() && encoded[i + run] == flag && run < 256 {
+                        run += 1;
+                    }
+                    if run > 1 {
+                        out.push(flag | REPEAT);
+                        out.push((run - 1) as u8);
+                    } else {
+                        out.push(flag);
+                    }
+                    i += run;
+                }
+                out.extend_from_slice(&xs);
+                out.extend_from_slice(&ys);
+            }
+            Outline::Composite {
+                components,
+                instructions,
+            } => {
+                write_header(out, -1, bounds);
+                for c in components {
+                    let mut flags = c.flags & !ARGS_ARE_WORDS;
+                    let is_xy = flags & ARGS_ARE_XY_VALUES != 0;
+                    let words = c.args.iter().any(|&a| {
+                        if is_xy {
+                            a < i8::MIN as i32 || a > i8::MAX as i32
+                        } else {
+                            a > u8::MAX as i32
+                        }
+                    });
+                    if words {
+                        flags |= ARGS_ARE_WORDS;
+                    }
+                    push_u16(out, flags);
+                    push_u16(out, c.glyph_id);
+                    for &a in &c.args {
+                        if words {
+                            push_u16(out, a.max(i16::MIN as i32).min(u16::MAX as i32) as u16);
+                        } else {
+                            out.push(a as u8);
+                        }
+                    }
+                    out.extend_from_slice(c.transform);
+                }
+                if let Some(instructions) = instructions {
+                    push_u16(out, instructions.len() as u16);
+                    out.extend_from_slice(instructions);
+                }
+            }
+        }
+    }
+}
+
+fn apply_phantom(phantom: &mut [Point; 4], deltas: &[Point]) {
+    for (p, d) in phantom.iter_mut().zip(deltas) {
+        p.x += d.x;
+        p.y += d.y;
+    }
+}
+
+fn write_header(out: &mut Vec<u8>, contour_count: i16, bounds: [i32; 4]) {
+    push_u16(out, contour_count as u16);
+    for v in &bounds {
+        push_u16(out, *v as i16 as u16);
+    }
+}
+
+fn encode_coord(delta: i32, short: u8, same: u8, out: &mut Vec<u8>) -> u8 {
+    if delta == 0 {
+        same
+    } else if delta.abs() <= 255 {
+        out.push(delta.unsigned_abs() as u8);
+        if delta > 0 {
+            short | same
+        } else {
+            short
+        }
+    } else {
+        out.extend_from_slice(&(delta as i16).to_be_bytes());
+        0
+    }
+}
+
+/// Collects the final points of the specified glyph, resolving composites.
+fn flatten(
+    glyphs: &[(Glyph, i32, i32)],
+    glyph_id: usize,
+    depth: u8,
+    points: &mut Vec<Point>,
+) -> Option<()> {
+    if depth > 32 {
+        return None;
+    }
+    match &glyphs.get(glyph_id)?.0.outline {
+        Outline::Empty => {}
+        Outline::Simple { points: p, .. } => points.extend_from_slice(p),
+        Outline::Composite { components, .. } => {
+            let base = points.len();
+            for c in components {
+                let start = points.len();
+                flatten(glyphs, c.glyph_id as usize, depth + 1, points)?;
+                let have_transform = !c.transform.is_empty();
+                let [xx, yx, xy, yy] = c.matrix();
+                if have_transform {
+                    for p in &mut points[start..] {
+                        let (x, y) = (p.x as f32, p.y as f32);
+                        p.x = (x * xx + y * xy).round() as i32;
+                        p.y = (x * yx + y * yy).round() as i32;
+                    }
+                }
+                let (dx, dy) = if c.flags & ARGS_ARE_XY_VALUES != 0 {
+                    let (x, y) = (c.args[0] as f32, c.args[1] as f32);
+                    if have_transform
+                        && c.flags & (SCALED_COMPONENT_OFFSET | UNSCALED_COMPONENT_OFFSET)
+                            == SCALED_COMPONENT_OFFSET
+                    {
+                        ((x * xx + y * xy).round() as i32, (x * yx + y * yy).round() as i32)
+                    } else {
+                        (c.args[0], c.args[1])
+                    }
+                } else {
+                    let p1 = *points.get(base + c.args[0] as usize)?;
+                    let p2 = *points.get(start + c.args[1] as usize)?;
+                    (p1.x - p2.x, p1.y - p2.y)
+                };
+                for p in &mut points[start..] {
+                    p.x += dx;
+                    p.y += dy;
+                }
+            }
+        }
+    }
+    Some(())
+}
+
+fn bounds(points: &[Point]) -> Option<[i32; 4]> {
+    let first = points.first()?;
+    let mut b = [first.x, first.y, first.x, first.y];
+    for p in &points[1..] {
+        b[0] = b[0].min(p.x);
+        b[1] = b[1].min(p.y);
+        b[2] = b[2].max(p.x);
+        b[3] = b[3].max(p.y);
+    }
+    Some(b)
+}
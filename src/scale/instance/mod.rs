@@ -0,0 +1,457 @@
+/*!
+Static instancing of variable fonts.
+
+Some consumers, such as PDF writers and older rasterizers, are unable to
+process font variations. The [`Instancer`] applies the variation data of a
+font at a single position in the design space and produces the content of a
+static font file that can be handed off to those consumers.
+
+Outlines in the `glyf` table are adjusted by the deltas in `gvar` (and the
+control values in `cvt ` by `cvar`) while `CFF2` outlines are evaluated with
+their blends applied and written to a `CFF ` table. The horizontal and
+vertical metrics are updated from `HVAR` and `VVAR` and the global metrics in
+`OS/2`, `hhea`, `vhea` and `post` are adjusted by `MVAR`. All variation tables
+are removed from the result.
+
+```
+# use swash::{FontRef, CacheKey, scale::instance::Instancer};
+# let font: FontRef = FontRef { data: &[], offset: 0, key: CacheKey::new() };
+// let font = ...;
+let static_font: Option<Vec<u8>> = Instancer::new(font)
+    .variations(&[("wght", 650.), ("wdth", 87.5)])
+    .build();
+```
+
+Note that the layout tables (`GDEF`, `GSUB` and `GPOS`) are copied unchanged
+and that `CFF2` hints are not preserved.
+*/
+
+mod cff;
+mod glyf;
+
+use super::internal::{
+    glyf::{CVT_, GLYF, LOCA},
+    head::{HEAD, HHEA, OS_2, POST, VHEA},
+    raw_tag,
+    var::{mvar_tags::*, Mvar, AVAR, FVAR, HVAR, MVAR, VVAR},
+    xmtx::{HMTX, VMTX},
+    Bytes, RawFont, RawTag,
+};
+use super::{cff::CffProxy, glyf::GlyfProxy};
+use crate::{metrics::MetricsProxy, setting::Setting, FontRef, Instance, NormalizedCoord, Tag};
+use core::borrow::Borrow;
+use std::borrow::Cow;
+
+const GVAR: RawTag = raw_tag(b"gvar");
+const CVAR: RawTag = raw_tag(b"cvar");
+const STAT: RawTag = raw_tag(b"STAT");
+const CFF_: RawTag = raw_tag(b"CFF ");
+const CFF2: RawTag = raw_tag(b"CFF2");
+
+/// Builder for producing a static instance of a variable font.
+///
+/// See the module level [documentation](index.html) for detail.
+pub struct Instancer<'a> {
+    font: FontRef<'a>,
+    coords: Vec<NormalizedCoord>,
+    values: Vec<Setting<f32>>,
+}
+
+impl<'a> Instancer<'a> {
+    /// Creates a new instancer for the specified font. The initial position
+    /// is the default location in the design space.
+    pub fn new(font: impl Into<FontRef<'a>>) -> Self {
+        let font = font.into();
+        Self {
+            font,
+            coords: vec![0; font.variations().len()],
+            values: Vec::new(),
+        }
+    }
+
+    /// Adds variation settings to the instance.
+    pub fn variations<I>(mut self, settings: I) -> Self
+    where
+        I: IntoIterator,
+        I::Item: Into<Setting<f32>>,
+    {
+        let vars = self.font.variations();
+        for setting in settings {
+            let setting = setting.into();
+            if let Some(var) = vars.find_by_tag(setting.tag) {
+                if let Some(coord) = self.coords.get_mut(var.index()) {
+                    *coord = var.normalize(setting.value);
+                }
+                self.set_value(setting);
+            }
+        }
+        self
+    }
+
+    /// Selects the position of the specified named instance. This will
+    /// replace any previous variation settings.
+    pub fn named_instance(mut self, instance: &Instance) -> Self {
+        self.coords.clear();
+        self.coords.extend(instance.normalized_coords());
+        self.values.clear();
+        for (var, value) in self.font.variations().zip(instance.values()) {
+            self.values.push(Setting {
+                tag: var.tag(),
+                value,
+            });
+        }
+        self
+    }
+
+    /// Specifies the position in terms of normalized coordinates. This will
+    /// replace any previous variation settings.
+    pub fn normalized_coords<I>(mut self, coords: I) -> Self
+    where
+        I: IntoIterator,
+        I::Item: Borrow<NormalizedCoord>,
+    {
+        self.coords.clear();
+        self.coords.extend(coords.into_iter().map(|c| *c.borrow()));
+        self.values.clear();
+        self
+    }
+
+    /// Builds the static font for the current configuration and returns the
+    /// content of the resulting font file. Returns `None` if the font could
+    /// not be processed.
+    pub fn build(&self) -> Option<Vec<u8>> {
+        let font = &self.font;
+        let data = font.data;
+        let coords = &self.coords[..];
+        let glyph_count = font.maxp()?.glyph_count();
+        let proxy = MetricsProxy::from_font(font);
+        let default_metrics = proxy.materialize_glyph_metrics(font, &[]);
+        let varied_metrics = proxy.materialize_glyph_metrics(font, coords);
+        let has_hvar = font.table_offset(HVAR) != 0;
+        let mut tables: Vec<(RawTag, Cow<[u8]>)> = Vec::new();
+        let mut glyphs = Vec::with_capacity(glyph_count as usize);
+        let mut loca_fmt = None;
+        let hvar_metrics = if has_hvar {
+            Some(&varied_metrics)
+        } else {
+            None
+        };
+        if let Some(glyf) = GlyfProxy::from_font(font) {
+            let mut out = glyf::Output::default();
+            glyf::instance(
+                data,
+                &glyf,
+                coords,
+                glyph_count,
+                &default_metrics,
+                hvar_metrics,
+                &mut out,
+            )?;
+            glyphs = out.metrics;
+            loca_fmt = Some(out.loca_fmt);
+            tables.push((GLYF, Cow::Owned(out.glyf)));
+            tables.push((LOCA, Cow::Owned(out.loca)));
+            if let Some(cvt) = font.table_data(CVT_) {
+                let mut values = Vec::new();
+                glyf.cvt(data, None, coords, &mut values)?;
+                let mut cvt = cvt.to_vec();
+                for (i, value) in values.iter().enumerate() {
+                    set_i16(&mut cvt, i * 2, *value);
+                }
+                tables.push((CVT_, Cow::Owned(cvt)));
+            }
+        } else if font.table_offset(CFF2) != 0 {
+            let cff = CffProxy::from_font(font)?;
+            for gid in 0..glyph_count {
+                glyphs.push(GlyphInfo {
+                    advance: varied_metrics.advance_width(gid).round() as i32,
+                    lsb: 0,
+                    bounds: None,
+                });
+            }
+            let name = font
+                .localized_strings()
+                .find_by_id(crate::StringId::PostScript, None)
+                .map(|s| s.to_string())
+                .unwrap_or_default();
+            let table = cff::instance(font, &cff, coords, &name, &mut glyphs)?;
+            tables.push((CFF_, Cow::Owned(table)));
+        } else {
+            for gid in 0..glyph_count {
+                glyphs.push(GlyphInfo {
+                    advance: varied_metrics.advance_width(gid).round() as i32,
+                    lsb: varied_metrics.lsb(gid).round() as i32,
+                    bounds: None,
+                });
+            }
+        }
+        let mvar = font.table_offset(MVAR);
+        let mvar = if mvar != 0 {
+            Mvar::new(data, mvar, coords)
+        } else {
+            None
+        };
+        let delta = |tag: RawTag| mvar.as_ref().map(|m| m.delta(tag)).unwrap_or(0.);
+        let mut bounds: Option<[i32; 4]> = None;
+        let mut max_advance = 0;
+        let mut min_lsb = i32::MAX;
+        let mut min_rsb = i32::MAX;
+        let mut max_extent = i32::MIN;
+        let mut width_sum = 0i64;
+        let mut width_count = 0i64;
+        for glyph in &glyphs {
+            max_advance = max_advance.max(glyph.advance);
+            if glyph.advance != 0 {
+                width_sum += glyph.advance as i64;
+                width_count += 1;
+            }
+            if let Some(b) = glyph.bounds {
+                let extent = glyph.lsb + (b[2] - b[0]);
+                min_lsb = min_lsb.min(glyph.lsb);
+                min_rsb = min_rsb.min(glyph.advance - extent);
+                max_extent = max_extent.max(extent);
+                bounds = Some(match bounds {
+                    Some(u) => [u[0].min(b[0]), u[1].min(b[1]), u[2].max(b[2]), u[3].max(b[3])],
+                    None => b,
+                });
+            }
+        }
+        let mut hmtx = Vec::with_capacity(glyphs.len() * 4);
+        for glyph in &glyphs {
+            push_u16(&mut hmtx, glyph.advance.max(0) as u16);
+            push_u16(&mut hmtx, glyph.lsb as i16 as u16);
+        }
+        tables.push((HMTX, Cow::Owned(hmtx)));
+        if let Some(head) = font.table_data(HEAD) {
+            let mut head = head.to_vec();
+            set_u32(&mut head, 8, 0);
+            if let Some(b) = bounds {
+                for (i, v) in b.iter().enumerate() {
+                    set_i16(&mut head, 36 + i * 2, *v);
+                }
+            }
+            if let Some(fmt) = loca_fmt {
+                set_i16(&mut head, 50, fmt as i32);
+            }
+            tables.push((HEAD, Cow::Owned(head)));
+        }
+        if let Some(hhea) = font.table_data(HHEA) {
+            let mut hhea = hhea.to_vec();
+            // The ascender, descender and line gap receive the same
+            // adjustments as the OS/2 typographic metrics for consistency
+            // with the metrics reported by this crate.
+            adjust_i16(&mut hhea, 4, delta(HASC));
+            adjust_i16(&mut hhea, 6, delta(HDSC));
+            adjust_i16(&mut hhea, 8, delta(HLGP));
+            set_i16(&mut hhea, 10, max_advance);
+            if bounds.is_some() {
+                set_i16(&mut hhea, 12, min_lsb);
+                set_i16(&mut hhea, 14, min_rsb);
+                set_i16(&mut hhea, 16, max_extent);
+            }
+            adjust_i16(&mut hhea, 18, delta(HCRS));
+            adjust_i16(&mut hhea, 20, delta(HCRN));
+            adjust_i16(&mut hhea, 22, delta(HCOF));
+            set_i16(&mut hhea, 34, glyphs.len() as i32);
+            tables.push((HHEA, Cow::Owned(hhea)));
+        }
+        if let Some(os2) = font.table_data(OS_2) {
+            let mut os2 = os2.to_vec();
+            let version = Bytes::new(&os2).read_u16(0).unwrap_or(0);
+            if width_count != 0 && version >= 3 && coords.iter().any(|c| *c != 0) {
+                set_i16(&mut os2, 2, (width_sum / width_count) as i32);
+            }
+            if let Some(weight) = self.value(raw_tag(b"wght")) {
+                set_i16(&mut os2, 4, (weight.round() as i32).clamp(1, 1000));
+            }
+            if let Some(width) = self.value(raw_tag(b"wdth")) {
+                set_i16(&mut os2, 6, width_class(width));
+            }
+            let adjustments = [
+                (10, SBXS),
+                (12, SBYS),
+                (14, SBXO),
+                (16, SBYO),
+                (18, SPXS),
+                (20, SPYS),
+                (22, SPXO),
+                (24, SPYO),
+                (26, STRS),
+                (28, STRO),
+                (68, HASC),
+                (70, HDSC),
+                (72, HLGP),
+                (86, XHGT),
+                (88, CPHT),
+            ];
+            for (offset, tag) in adjustments.iter() {
+                adjust_i16(&mut os2, *offset, delta(*tag));
+            }
+            adjust_u16(&mut os2, 74, delta(HCLA));
+            adjust_u16(&mut os2, 76, delta(HCLD));
+            tables.push((OS_2, Cow::Owned(os2)));
+        }
+        if let Some(post) = font.table_data(POST) {
+            let mut post = post.to_vec();
+            adjust_i16(&mut post, 8, delta(UNDO));
+            adjust_i16(&mut post, 10, delta(UNDS));
+            tables.push((POST, Cow::Owned(post)));
+        }
+        if let Some(vhea) = font.table_data(VHEA) {
+            let mut vhea = vhea.to_vec();
+            adjust_i16(&mut vhea, 4, delta(VASC));
+            adjust_i16(&mut vhea, 6, delta(VDSC));
+            adjust_i16(&mut vhea, 8, delta(VLGP));
+            adjust_i16(&mut vhea, 18, delta(VCRS));
+            adjust_i16(&mut vhea, 20, delta(VCRN));
+            adjust_i16(&mut vhea, 22, delta(VCOF));
+            if font.table_offset(VVAR) != 0 && font.table_offset(VMTX) != 0 {
+                let mut vmtx = Vec::with_capacity(glyphs.len() * 4);
+                let mut max_advance = 0;
+                for gid in 0..glyph_count {
+                    let advance = varied_metrics.advance_height(gid).round() as i32;
+                    max_advance = max_advance.max(advance);
+                    push_u16(&mut vmtx, advance.max(0) as u16);
+                    push_u16(&mut vmtx, varied_metrics.tsb(gid).round() as i16 as u16);
+                }
+                set_i16(&mut vhea, 10, max_advance);
+                set_i16(&mut vhea, 34, glyph_count as i32);
+                tables.push((VMTX, Cow::Owned(vmtx)));
+            }
+            tables.push((VHEA, Cow::Owned(vhea)));
+        }
+        const REMOVED: [RawTag; 9] = [FVAR, AVAR, GVAR, CVAR, HVAR, VVAR, MVAR, STAT, CFF2];
+        for i in 0..font.table_count() {
+            let (tag, range) = font.table_at(i)?;
+            if REMOVED.contains(&tag) || tables.iter().any(|t| t.0 == tag) {
+                continue;
+            }
+            let table = data.get(range.0 as usize..range.1 as usize)?;
+            tables.push((tag, Cow::Borrowed(table)));
+        }
+        let version = Bytes::new(data).read_u32(font.offset as usize)?;
+        Some(write_font(version, &mut tables))
+    }
+
+    fn set_value(&mut self, setting: Setting<f32>) {
+        if let Some(existing) = self.values.iter_mut().find(|v| v.tag == setting.tag) {
+            existing.value = setting.value;
+        } else {
+            self.values.push(setting);
+        }
+    }
+
+    fn value(&self, tag: Tag) -> Option<f32> {
+        self.values.iter().find(|v| v.tag == tag).map(|v| v.value)
+    }
+}
+
+/// Metrics and bounds for an instanced glyph.
+#[derive(Copy, Clone, Default)]
+struct GlyphInfo {
+    advance: i32,
+    lsb: i32,
+    bounds: Option<[i32; 4]>,
+}
+
+/// Maps a `wdth` axis value to the nearest OS/2 width class.
+fn width_class(width: f32) -> i32 {
+    const CLASSES: [f32; 9] = [50., 62.5, 75., 87.5, 100., 112.5, 125., 150., 200.];
+    let mut best = 0;
+    for (i, value) in CLASSES.iter().enumerate() {
+        if (width - value).abs() < (width - CLASSES[best]).abs() {
+            best = i;
+        }
+    }
+    best as i32 + 1
+}
+
+/// Writes the table directory and the specified tables.
+fn write_font(version: u32, tables: &mut [(RawTag, Cow<[u8]>)]) -> Vec<u8> {
+    tables.sort_unstable_by_key(|t| t.0);
+    let count = tables.len();
+    let mut entry_selector = 0;
+    while (2usize << entry_selector) <= count {
+        entry_selector += 1;
+    }
+    let search_range = (1usize << entry_selector) * 16;
+    let mut out = Vec::new();
+    push_u32(&mut out, version);
+    push_u16(&mut out, count as u16);
+    push_u16(&mut out, search_range as u16);
+    push_u16(&mut out, entry_selector as u16);
+    push_u16(&mut out, (count * 16).saturating_sub(search_range) as u16);
+    let mut offset = 12 + count * 16;
+    let mut head_offset = None;
+    for (tag, table) in tables.iter() {
+        if *tag == HEAD {
+            head_offset = Some(offset);
+        }
+        push_u32(&mut out, *tag);
+        push_u32(&mut out, checksum(table));
+        push_u32(&mut out, offset as u32);
+        push_u32(&mut out, table.len() as u32);
+        offset += (table.len() + 3) & !3;
+    }
+    for (_, table) in tables.iter() {
+        out.extend_from_slice(table);
+        while out.len() % 4 != 0 {
+            out.push(0);
+        }
+    }
+    if let Some(head) = head_offset {
+        let adjustment = 0xB1B0AFBAu32.wrapping_sub(checksum(&out));
+        set_u32(&mut out, head + 8, adjustment);
+    }
+    out
+}
+
+fn checksum(data: &[u8]) -> u32 {
+    let mut sum = 0u32;
+    for chunk in data.chunks(4) {
+        let mut bytes = [0u8; 4];
+        bytes[..chunk.len()].copy_from_slice(chunk);
+        sum = sum.wrapping_add(u32::from_be_bytes(bytes));
+    }
+    sum
+}
+
+fn push_u16(buf: &mut Vec<u8>, value: u16) {
+    buf.extend_from_slice(&value.to_be_bytes());
+}
+
+fn push_u32(buf: &mut Vec<u8>, value: u32) {
+    buf.extend_from_slice(&value.to_be_bytes());
+}
+
+fn set_u32(buf: &mut [u8], offset: usize, value: u32) {
+    if let Some(bytes) = buf.get_mut(offset..offset + 4) {
+        bytes.copy_from_slice(&value.to_be_bytes());
+    }
+}
+
+fn set_i16(buf: &mut [u8], offset: usize, value: i32) {
+    let value = value.max(i16::MIN as i32).min(i16::MAX as i32) as i16;
+    if let Some(bytes) = buf.get_mut(offset..offset + 2) {
+        bytes.copy_from_slice(&value.to_be_bytes());
+    }
+}
+
+fn adjust_u16(buf: &mut [u8], offset: usize, delta: f32) {
+    if delta == 0. {
+        return;
+    }
+    if let Some(value) = Bytes::new(buf).read_u16(offset) {
+        let value = (value as f32 + delta).round().max(0.).min(u16::MAX as f32) as u16;
+        buf[offset..offset + 2].copy_from_slice(&value.to_be_bytes());
+    }
+}
+
+fn adjust_i16(buf: &mut [u8], offset: usize, delta: f32) {
+    if delta == 0. {
+        return;
+    }
+    if let Some(value) = Bytes::new(buf).read_i16(offset) {
+        set_i16(buf, offset, (value as f32 + delta).round() as i32);
+    }
+}
@@ -0,0 +1,180 @@
+//! Subpixel filtering, gamma correction and stem darkening.
+
+use super::{LcdFilter, SubpixelOrder};
+use zeno::Placement;
+
+/// Weights of the legacy intra-pixel filter, in 16.16 fixed point, indexed
+/// by subpixel and then by output channel.
+const LEGACY: [[u32; 3]; 3] = [
+    [65538 * 9 / 13, 65538 / 6, 65538 / 13],
+    [65538 * 3 / 13, 65538 * 4 / 6, 65538 * 3 / 13],
+    [65538 / 13, 65538 / 6, 65538 * 9 / 13],
+];
+
+impl LcdFilter {
+    /// Returns the FIR weights for the filter or `None` for the legacy
+    /// filter.
+    fn weights(self) -> Option<[u32; 5]> {
+        let w = match self {
+            Self::Default => [0x08, 0x4D, 0x56, 0x4D, 0x08],
+            Self::Light => [0x00, 0x55, 0x56, 0x55, 0x00],
+            Self::Legacy => return None,
+            Self::Custom(w) => w,
+        };
+        Some(w.map(|w| w as u32))
+    }
+}
+
+/// Converts a coverage mask that was rendered at three times the resolution
+/// along the subpixel axis into an RGBA subpixel mask. The alpha channel
+/// receives the average of the color channels.
+pub fn filter(
+    mask: &[u8],
+    placement: Placement,
+    filter: Option<LcdFilter>,
+    order: SubpixelOrder,
+    target: &mut Vec<u8>,
+) -> Placement {
+    let vertical = matches!(order, SubpixelOrder::Vrgb | SubpixelOrder::Vbgr);
+    let reversed = matches!(order, SubpixelOrder::Bgr | SubpixelOrder::Vbgr);
+    let weights = match filter {
+        Some(filter) => filter.weights(),
+        None => Some([0, 0, 256, 0, 0]),
+    };
+    // Number of subpixels by which the filter spreads coverage.
+    let pad = match weights {
+        Some(w) if w[0] != 0 || w[4] != 0 => 2,
+        Some(w) if w[1] != 0 || w[3] != 0 => 1,
+        _ => 0,
+    };
+    let (w, h) = (placement.width as i32, placement.height as i32);
+    // Subpixel positions run left to right for horizontal orders and top to
+    // bottom for vertical orders.
+    let (origin, len, across) = if vertical {
+        (-placement.top, h, w)
+    } else {
+        (placement.left, w, h)
+    };
+    let start = (origin - pad).div_euclid(3);
+    let end = (origin + len + pad + 2).div_euclid(3);
+    let count = if len == 0 || across == 0 {
+        0
+    } else {
+        end - start
+    };
+    let coverage = |line: i32, s: i32| -> u32 {
+        let s = s - origin;
+        if s < 0 || s >= len {
+            return 0;
+        }
+        let index = if vertical { s * w + line } else { line * w + s };
+        mask[index as usize] as u32
+    };
+    let (out_w, out_h) = if vertical {
+        (across, count)
+    } else {
+        (count, across)
+    };
+    target.clear();
+    target.resize((out_w * out_h * 4) as usize, 0);
+    for line in 0..across {
+        for i in 0..count {
+            let base = (start + i) * 3;
+            let mut sub = [0u32; 3];
+            match weights {
+                Some(weights) => {
+                    for (j, value) in sub.iter_mut().enumerate() {
+                        let s = base + j as i32;
+                        let mut sum = 0;
+                        for (k, weight) in weights.iter().enumerate() {
+                            sum += weight * coverage(line, s + k as i32 - 2);
+                        }
+                        *value = (sum >> 8).min(255);
+                    }
+                }
+                None => {
+                    for (j, row) in LEGACY.iter().enumerate() {
+                        let p = coverage(line, base + j as i32);
+                        for (value, weight) in sub.iter_mut().zip(row) {
+                            *value += weight * p;
+                        }
+                    }
+                    for value in &mut sub {
+                        *value = (*value >> 16).min(255);
+                    }
+                }
+            }
+            if reversed {
+                sub.swap(0, 2);
+            }
+            let (x, y) = if vertical { (line, i) } else { (i, line) };
+            let offset = ((y * out_w + x) * 4) as usize;
+            let pixel = &mut target[offset..offset + 4];
+            pixel[0] = sub[0] as u8;
+            pixel[1] = sub[1] as u8;
+            pixel[2] = sub[2] as u8;
+            pixel[3] = ((sub[0] + sub[1] + sub[2]) / 3) as u8;
+        }
+    }
+    if vertical {
+        Placement {
+            left: placement.left,
+            top: -start,
+            width: out_w as u32,
+            height: out_h as u32,
+        }
+    } else {
+        Placement {
+            left: start,
+            top: placement.top,
+            width: out_w as u32,
+            height: out_h as u32,
+        }
+    }
+}
+
+/// Builds a coverage lookup table for the specified gamma and contrast.
+/// Returns `None` if the table would be the identity.
+pub fn gamma_table(gamma: f32, contrast: f32) -> Option<[u8; 256]> {
+    if (gamma == 1. && contrast == 0.) || gamma <= 0. {
+        return None;
+    }
+    let mut table = [0u8; 256];
+    let exponent = 1. / gamma;
+    for (i, value) in table.iter_mut().enumerate() {
+        let c = i as f32 / 255.;
+        let c = (c + contrast * c * (1. - c)).clamp(0., 1.);
+        *value = (c.powf(exponent) * 255. + 0.5) as u8;
+    }
+    Some(table)
+}
+
+/// Applies a coverage lookup table to every byte of a mask.
+pub fn apply_table(table: &[u8; 256], mask: &mut [u8]) {
+    for value in mask {
+        *value = table[*value as usize];
+    }
+}
+
+/// Returns the amount, in pixels, by which outlines are emboldened when
+/// stem darkening is enabled at the specified size in pixels per em.
+///
+/// This follows the default darkening curve of FreeType, which is defined
+/// in terms of stem width, assuming a typical stem of 8% of the em.
+pub fn darkening(ppem: f32) -> f32 {
+    const CURVE: [(f32, f32); 4] = [(500., 0.4), (1000., 0.275), (1667., 0.275), (2333., 0.)];
+    if ppem <= 0. {
+        return 0.;
+    }
+    let x = ppem * 80.;
+    if x <= CURVE[0].0 {
+        return CURVE[0].1;
+    }
+    for pair in CURVE.windows(2) {
+        let ((x0, y0), (x1, y1)) = (pair[0], pair[1]);
+        if x <= x1 {
+            return y0 + (y1 - y0) * (x - x0) / (x1 - x0);
+        }
+    }
+    0.
+}
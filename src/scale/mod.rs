@@ -95,6 +95,19 @@ let mut scaler = context.builder(font)
 let glyph_id = font.charmap().map('Q');
 let outline = scaler.scale_outline(glyph_id);
 ```
+Enabling hints selects [`Hinting::Subpixel`] which fits outlines to the pixel
+grid along the y-axis. Other modes can be selected with
+[`hinting`](ScalerBuilder::hinting): [`Hinting::Light`] for high density
+displays, [`Hinting::Normal`] to fit along both axes and [`Hinting::Mono`] for
+aliased rendering.
+
+Fonts without hinting programs (TrueType) or alignment zones (Postscript) are
+fitted by an automatic hinter that measures per-script blue zones from
+reference glyphs and aligns horizontal edges and stems to the pixel grid. It
+only fits outlines along the y-axis regardless of the selected mode. The
+[`force_autohint`](ScalerBuilder::force_autohint) option applies it to hinted
+fonts as well.
+
 The [`scale_outline`](Scaler::scale_outline) method returns an [`Outline`] wrapped
 in an option. It will return `None` if an outline was not available or if there was
 an error during the scaling process. Note that
@@ -104,6 +117,13 @@ the `_into` variants of these methods ([`scale_outline_into`](Scaler::scale_outl
 and [`scale_color_outline_into`](Scaler::scale_color_outline_into)) will return
 their results in a previously allocated outline avoiding the extra allocations.
 
+Color glyphs defined by gradients, transforms and compositing in version 1 of
+the `COLR` table cannot be represented as a layered outline. The
+[`paint_color_glyph`](Scaler::paint_color_glyph) method instead traverses the
+paint graph of a color glyph and reports each operation to an implementation
+of the [`ColorPainter`] trait, allowing the glyph to be
+drawn by a GPU renderer or vector backend.
+
 Similar to outlines, bitmaps can be retrieved with the [`scale_bitmap`](Scaler::scale_bitmap)
 and [`scale_color_bitmap`](Scaler::scale_color_bitmap) for alpha and color bitmaps,
 respectively. These methods return an [`Image`] wrapped in an option. The associated
@@ -165,6 +185,12 @@ Render::new(&[
 ]);
 ```
 
+Color glyphs described by documents in the `SVG ` table are rendered with
+[`Source::Svg`]. Only the static subset of SVG commonly used by these fonts
+is supported: paths and basic shapes, solid and gradient fills, strokes,
+transforms, clip paths and `use` references. The raw documents are available
+through [`FontRef::svg_documents`](crate::FontRef::svg_documents).
+
 The [`Render`] struct offers several options that control rasterization of
 outlines such as [`format`](Render::format) for selecting a subpixel rendering mode,
 [`offset`](Render::offset) for applying fractional positioning, and others. See the
@@ -206,45 +232,113 @@ fn render_glyph(
 }
 ```
 Note that rendering also takes care of correctly scaling, rasterizing and
-compositing layered color outlines for us.
+compositing layered color outlines for us, including the gradients and blend
+modes of `COLR` version 1 paint graphs.
 
 There are other options available for emboldening, transforming with an
 affine matrix, and applying path effects. See the methods on [`Render`] for
 more detail.
+
+The raw output of [`Format::Subpixel`] shows color fringing on most displays.
+Setting an [`LcdFilter`] with [`Render::lcd_filter`] oversamples the outline
+along the subpixel axis and filters the result, and [`Render::subpixel_order`]
+selects the layout of the display. Coverage can be further adjusted with
+[`Render::gamma`] and [`Render::stem_darkening`]. All of these options apply
+to layered color outlines as well, and gamma also applies to `COLR` version 1
+paint graphs and SVG documents.
+
+For GPU renderers that draw glyphs from an atlas at arbitrary sizes, outlines
+can instead be rendered as signed distance fields by passing a [`DistanceField`]
+to [`Render::distance_field`]. The resulting image has a content type of
+[`Content::DistanceField`].
+
+Renderers that draw glyphs from textures can use the [`GlyphCache`](atlas::GlyphCache)
+in the [`atlas`] module, which renders glyphs on demand and packs them into
+texture atlases.
 */
 
 const TRACE: bool = false;
 
+#[cfg(feature = "render")]
+pub mod atlas;
 pub mod image;
+pub mod instance;
 pub mod outline;
+pub mod paint;
+pub mod postscript;
 
+mod autohint;
 mod bitmap;
+#[cfg(feature = "render")]
+mod canvas;
 mod cff;
 mod color;
 mod glyf;
 mod proxy;
+#[cfg(feature = "render")]
+mod lcd;
+#[cfg(feature = "render")]
+mod sdf;
+#[cfg(feature = "render")]
+mod svg;
 
 use image::*;
 use outline::*;
+use paint::{ColorPainter, ColorStop};
 
 use super::internal;
-use super::{cache::FontCache, setting::Setting, FontRef, GlyphId, NormalizedCoord};
+use super::{cache::FontCache, setting::Setting, FontRef, GlyphId, NormalizedCoord, Usability};
+#[cfg(feature = "render")]
+use super::iter::SvgDocuments;
 use core::borrow::Borrow;
 use proxy::*;
 #[cfg(feature = "render")]
-use zeno::{Format, Mask, Origin, Scratch, Style, Transform, Vector};
+use zeno::{Format, Mask, Origin, PathData, Scratch, Style, Transform, Vector};
 use zeno::{Placement, Point};
 
-pub(crate) use bitmap::decode_png;
+pub(crate) use bitmap::{decode_jpeg, decode_png, decode_tiff, jpeg_size, tiff_size};
 
 /// Index of a color palette.
 pub type PaletteIndex = u16;
 
+/// Selection of the base palette for color glyphs.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum BasePalette {
+    /// Palette at the specified index.
+    Index(PaletteIndex),
+    /// First palette that is usable with the specified background. A
+    /// palette marked as usable with both light and dark backgrounds
+    /// satisfies either request.
+    Usability(Usability),
+}
+
+/// Hinting mode for scaled outlines.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub enum Hinting {
+    /// Outlines are scaled without hinting.
+    None,
+    /// Outlines are only fitted to the pixel grid along the y-axis,
+    /// preserving glyph shapes and advances. Suited to high density
+    /// displays.
+    Light,
+    /// Outlines are fitted to the pixel grid along both axes. TrueType
+    /// instructions are executed by the legacy (v35) interpreter.
+    Normal,
+    /// Same as [`Normal`](Hinting::Normal), but TrueType instructions are
+    /// told that the target is monochrome. Suited to aliased rendering such
+    /// as on e-ink displays.
+    Mono,
+    /// TrueType instructions are executed in subpixel (v40) mode with
+    /// backward compatibility, which ignores most adjustments along the
+    /// x-axis. PostScript outlines are hinted along the y-axis.
+    Subpixel,
+}
+
 /// Index of a bitmap strike.
 pub type StrikeIndex = u32;
 
 /// Bitmap strike selection mode.
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
 pub enum StrikeWith {
     /// Load a bitmap only if the exact size is available.
     ExactSize,
@@ -257,7 +351,7 @@ pub enum StrikeWith {
 }
 
 /// Glyph sources for the renderer.
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
 pub enum Source {
     /// Scalable outlines.
     Outline,
@@ -267,6 +361,8 @@ pub enum Source {
     Bitmap(StrikeWith),
     /// Embedded color bitmaps.
     ColorBitmap(StrikeWith),
+    /// Color glyphs described by SVG documents.
+    Svg,
 }
 
 impl Default for Source {
@@ -290,8 +386,19 @@ struct State {
     scratch0: Vec<u8>,
     scratch1: Vec<u8>,
     outline: Outline,
+    clip_outline: Outline,
+    autohint: autohint::Cache,
+    stops: Vec<ColorStop>,
     #[cfg(feature = "render")]
     rcx: Scratch,
+    #[cfg(feature = "render")]
+    canvas: canvas::Canvas,
+    #[cfg(feature = "render")]
+    svg: svg::Document,
+    #[cfg(feature = "render")]
+    colors: Vec<[u8; 4]>,
+    #[cfg(feature = "render")]
+    sdf: sdf::FieldBuilder,
 }
 
 impl ScaleContext {
@@ -312,8 +419,19 @@ impl ScaleContext {
                 scratch0: Vec::new(),
                 scratch1: Vec::new(),
                 outline: Outline::new(),
+                clip_outline: Outline::new(),
+                autohint: autohint::Cache::new(max_entries),
+                stops: Vec::new(),
                 #[cfg(feature = "render")]
                 rcx: Scratch::new(),
+                #[cfg(feature = "render")]
+                canvas: canvas::Canvas::default(),
+                #[cfg(feature = "render")]
+                svg: svg::Document::default(),
+                #[cfg(feature = "render")]
+                colors: Vec::new(),
+                #[cfg(feature = "render")]
+                sdf: sdf::FieldBuilder::default(),
             },
             coords: Vec::new(),
         }
@@ -340,7 +458,8 @@ pub struct ScalerBuilder<'a> {
     id: u64,
     coords: &'a mut Vec<i16>,
     size: f32,
-    hint: bool,
+    hinting: Hinting,
+    force_autohint: bool,
 }
 
 impl<'a> ScalerBuilder<'a> {
@@ -356,7 +475,8 @@ impl<'a> ScalerBuilder<'a> {
             id,
             coords: &mut context.coords,
             size: 0.,
-            hint: false,
+            hinting: Hinting::None,
+            force_autohint: false,
         }
     }
 
@@ -368,8 +488,33 @@ impl<'a> ScalerBuilder<'a> {
     }
 
     /// Specifies whether to apply hinting to outlines. The default value is `false`.
+    ///
+    /// This is equivalent to selecting [`Hinting::Subpixel`] or
+    /// [`Hinting::None`] with [`hinting`](ScalerBuilder::hinting).
     pub fn hint(mut self, yes: bool) -> Self {
-        self.hint = yes;
+        self.hinting = if yes {
+            Hinting::Subpixel
+        } else {
+            Hinting::None
+        };
+        self
+    }
+
+    /// Specifies the hinting mode to apply to outlines. The default value is
+    /// [`Hinting::None`].
+    pub fn hinting(mut self, mode: Hinting) -> Self {
+        self.hinting = mode;
+        self
+    }
+
+    /// Specifies whether the automatic hinter should replace the hints
+    /// provided by the font. The default value is `false`.
+    ///
+    /// The automatic hinter is otherwise used only when hinting is enabled
+    /// and the font has no hinting programs (TrueType) or alignment zones
+    /// (Postscript).
+    pub fn force_autohint(mut self, yes: bool) -> Self {
+        self.force_autohint = yes;
         self
     }
 
@@ -425,7 +570,10 @@ impl<'a> ScalerBuilder<'a> {
             coords: &self.coords[..],
             size: self.size,
             scale,
-            hint: self.hint,
+            hinting: self.hinting,
+            force_autohint: self.force_autohint,
+            native_hinting: None,
+            autohint: None,
             glyf_state: None,
         }
     }
@@ -442,7 +590,12 @@ pub struct Scaler<'a> {
     coords: &'a [i16],
     size: f32,
     scale: f32,
-    hint: bool,
+    hinting: Hinting,
+    force_autohint: bool,
+    /// Hinting mode for the native hinters, determined on first use.
+    native_hinting: Option<Hinting>,
+    /// Index of the automatic hinter metrics.
+    autohint: Option<usize>,
     glyf_state: Option<glyf::ScalerState<'a>>,
 }
 
@@ -506,12 +659,54 @@ impl<'a> Scaler<'a> {
         }
     }
 
+    /// Traverses the paint graph of the specified color glyph, reporting each
+    /// operation to the provided painter.
+    ///
+    /// Both version 1 paint graphs and version 0 layered glyphs are
+    /// supported. Returns false if the glyph does not have a color outline.
+    pub fn paint_color_glyph(
+        &mut self,
+        glyph_id: GlyphId,
+        painter: &mut impl ColorPainter,
+    ) -> bool {
+        if !self.has_color_outlines() {
+            return false;
+        }
+        let data = self.font.data;
+        let coords = self.coords;
+        let proxy = self.proxy.color;
+        let scale = self.scale;
+        let mut stops = core::mem::take(&mut self.state.stops);
+        let mut clip_outline = core::mem::take(&mut self.state.clip_outline);
+        let result = color::PaintGraph::new(
+            data,
+            proxy,
+            coords,
+            scale,
+            &mut stops,
+            &mut clip_outline,
+            |glyph_id, outline| {
+                outline.clear();
+                self.scale_outline_impl(glyph_id, None, Some(outline))
+            },
+        )
+        .and_then(|mut graph| graph.paint(glyph_id, painter));
+        self.state.stops = stops;
+        self.state.clip_outline = clip_outline;
+        result.is_some()
+    }
+
     fn scale_outline_impl(
         &mut self,
         glyph_id: GlyphId,
         color_index: Option<u16>,
         outline: Option<&mut Outline>,
     ) -> bool {
+        let hinting = match self.native_hinting {
+            Some(hinting) => hinting,
+            _ => self.prepare_autohint(),
+        };
+        let autohint = self.autohint;
         let outline = match outline {
             Some(x) => x,
             _ => &mut self.state.outline,
@@ -530,7 +725,7 @@ impl<'a> Scaler<'a> {
                         &self.coords,
                         proxy,
                         self.scale,
-                        self.hint,
+                        hinting,
                         glyph_id,
                         &mut builder,
                     )
@@ -538,6 +733,9 @@ impl<'a> Scaler<'a> {
                 {
                     outline.maybe_close();
                     outline.finish();
+                    if let Some(index) = autohint {
+                        autohint_layer(&mut self.state.autohint, index, glyph_id, outline);
+                    }
                     true
                 } else {
                     false
@@ -552,7 +750,7 @@ impl<'a> Scaler<'a> {
                         proxy,
                         &self.proxy.metrics,
                         self.size,
-                        self.hint,
+                        hinting,
                     ));
                 }
                 let state = self.glyf_state.as_mut().unwrap();
@@ -568,6 +766,9 @@ impl<'a> Scaler<'a> {
                     );
                     outline.maybe_close();
                     outline.finish();
+                    if let Some(index) = autohint {
+                        autohint_layer(&mut self.state.autohint, index, glyph_id, outline);
+                    }
                     true
                 } else {
                     false
@@ -576,6 +777,50 @@ impl<'a> Scaler<'a> {
         }
     }
 
+    /// Determines whether the automatic hinter applies to this scaler,
+    /// measures its metrics if necessary and returns the hinting mode for
+    /// the native hinters.
+    fn prepare_autohint(&mut self) -> Hinting {
+        self.native_hinting = Some(self.hinting);
+        if self.hinting == Hinting::None || self.size == 0. {
+            return self.hinting;
+        }
+        if !self.force_autohint {
+            let has_hints = match &self.proxy.outlines {
+                OutlinesProxy::None => return self.hinting,
+                OutlinesProxy::Cff(proxy) => {
+                    cff::has_blue_zones(&self.font, proxy, self.coords)
+                }
+                OutlinesProxy::Glyf(proxy) => {
+                    let data = self.font.data;
+                    !proxy.fpgm(data).is_empty() || !proxy.prep(data).is_empty()
+                }
+            };
+            if has_hints {
+                return self.hinting;
+            }
+        }
+        // The native hinters are disabled while the automatic hinter is
+        // active. This also leaves the reference glyphs unhinted.
+        self.native_hinting = Some(Hinting::None);
+        let (found, index) = self.state.autohint.find(self.id, self.coords, self.size);
+        if !found {
+            let mut metrics = self.state.autohint.take(index);
+            let mut outline = core::mem::take(&mut self.state.autohint.outline);
+            let font = self.font;
+            metrics.compute(&font.charmap(), self.size, &mut outline, |glyph_id, outline| {
+                outline.clear();
+                self.scale_outline_impl(glyph_id, None, Some(outline))
+            });
+            self.state.autohint.outline = outline;
+            self.state
+                .autohint
+                .insert(index, self.id, self.coords, self.size, metrics);
+        }
+        self.autohint = Some(index);
+        Hinting::None
+    }
+
     // Unused when render feature is disabled.
     #[allow(dead_code)]
     fn scale_color_outline_impl(&mut self, glyph_id: GlyphId) -> bool {
@@ -629,6 +874,11 @@ impl<'a> Scaler<'a> {
         self.proxy.bitmaps.has_color()
     }
 
+    /// Returns true if SVG glyph documents are available.
+    pub fn has_svg(&self) -> bool {
+        self.proxy.svg != 0
+    }
+
     /// Scales a color bitmap for the specified glyph and mode into the provided image.
     pub fn scale_color_bitmap_into(
         &mut self,
@@ -756,6 +1006,71 @@ impl<'a> Scaler<'a> {
     }
 }
 
+/// Parameters for rendering an outline as a signed distance field.
+///
+/// Each sample measures its distance to the nearby segments of the flattened
+/// outline, so the cost grows with the number of samples times the number of
+/// segments within the spread of each row. Large spreads and resolutions
+/// should be used sparingly. The resolution is clamped to 16 samples per
+/// pixel, the spread to 256 samples and fields larger than 2^24 samples are
+/// not rendered.
+#[cfg(feature = "render")]
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct DistanceField {
+    /// Distance from the outline, in pixels at the scaled size, at which the
+    /// field saturates.
+    pub spread: f32,
+    /// Number of field samples per pixel at the scaled size.
+    pub resolution: f32,
+}
+
+#[cfg(feature = "render")]
+impl DistanceField {
+    /// Creates new distance field parameters with the specified spread and
+    /// resolution.
+    pub fn new(spread: f32, resolution: f32) -> Self {
+        Self { spread, resolution }
+    }
+}
+
+#[cfg(feature = "render")]
+impl Default for DistanceField {
+    fn default() -> Self {
+        Self::new(4., 1.)
+    }
+}
+
+/// Filter applied to subpixel masks to reduce color fringing.
+#[cfg(feature = "render")]
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum LcdFilter {
+    /// Five tap FIR filter that balances sharpness and fringing.
+    Default,
+    /// Three tap FIR filter that is sharper than the default but shows more
+    /// fringing on some displays.
+    Light,
+    /// Intra-pixel filter that redistributes coverage among the subpixels of
+    /// each pixel.
+    Legacy,
+    /// Five tap FIR filter with the specified weights. The weights are in
+    /// units of 1/256 and should sum to 256.
+    Custom([u8; 5]),
+}
+
+/// Physical arrangement of the subpixels of a display.
+#[cfg(feature = "render")]
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum SubpixelOrder {
+    /// Horizontal stripes ordered red, green, blue from left to right.
+    Rgb,
+    /// Horizontal stripes ordered blue, green, red from left to right.
+    Bgr,
+    /// Vertical stripes ordered red, green, blue from top to bottom.
+    Vrgb,
+    /// Vertical stripes ordered blue, green, red from top to bottom.
+    Vbgr,
+}
+
 /// Builder type for rendering a glyph into an image.
 ///
 /// See the module level [documentation](index.html#rendering) for detail.
@@ -767,7 +1082,15 @@ pub struct Render<'a> {
     transform: Option<Transform>,
     embolden: f32,
     foreground: [u8; 4],
+    palette: Option<BasePalette>,
+    palette_overrides: &'a [(u16, [u8; 4])],
     style: Style<'a>,
+    distance_field: Option<DistanceField>,
+    lcd_filter: Option<LcdFilter>,
+    subpixel_order: SubpixelOrder,
+    gamma: f32,
+    contrast: f32,
+    stem_darkening: bool,
 }
 
 #[cfg(feature = "render")]
@@ -782,7 +1105,15 @@ impl<'a> Render<'a> {
             transform: None,
             embolden: 0.,
             foreground: [128, 128, 128, 255],
+            palette: None,
+            palette_overrides: &[],
             style: Style::default(),
+            distance_field: None,
+            lcd_filter: None,
+            subpixel_order: SubpixelOrder::Rgb,
+            gamma: 1.,
+            contrast: 0.,
+            stem_darkening: false,
         }
     }
 
@@ -829,6 +1160,71 @@ impl<'a> Render<'a> {
         self
     }
 
+    /// Specifies the base palette to use for color glyphs, taking precedence
+    /// over the palette index of [`Source::ColorOutline`]. The first palette
+    /// is used if the requested palette is not available. Default is `None`.
+    pub fn base_palette(&mut self, palette: Option<BasePalette>) -> &mut Self {
+        self.palette = palette;
+        self
+    }
+
+    /// Specifies a sparse list of RGBA colors that replace entries of the
+    /// base palette, keyed by entry index. Indices beyond the size of the
+    /// palette are ignored. Default is empty.
+    pub fn palette_overrides(&mut self, overrides: &'a [(u16, [u8; 4])]) -> &mut Self {
+        self.palette_overrides = overrides;
+        self
+    }
+
+    /// Specifies that outlines should be rendered as a signed distance field
+    /// with the specified parameters rather than a coverage mask. This takes
+    /// precedence over the [`format`](Render::format) and produces an image
+    /// with [`Content::DistanceField`]. Default is `None`.
+    pub fn distance_field(&mut self, field: Option<DistanceField>) -> &mut Self {
+        self.distance_field = field;
+        self
+    }
+
+    /// Specifies the filter to apply when rendering subpixel masks. When a
+    /// filter is set, outlines are rasterized at three times the resolution
+    /// along the subpixel axis and filtered down, replacing the unfiltered
+    /// output of [`Format::Subpixel`]. This has no effect when the format is
+    /// [`Format::Alpha`].
+    /// Default is `None`.
+    pub fn lcd_filter(&mut self, filter: Option<LcdFilter>) -> &mut Self {
+        self.lcd_filter = filter;
+        self
+    }
+
+    /// Specifies the order of the subpixels of the target display for
+    /// subpixel masks. Orders other than [`SubpixelOrder::Rgb`] always use
+    /// the oversampled rasterizer described in
+    /// [`lcd_filter`](Render::lcd_filter). Default is [`SubpixelOrder::Rgb`].
+    pub fn subpixel_order(&mut self, order: SubpixelOrder) -> &mut Self {
+        self.subpixel_order = order;
+        self
+    }
+
+    /// Specifies gamma and contrast adjustments to apply to the coverage of
+    /// rasterized outlines. Contrast is applied first, pushing coverage away
+    /// from the midpoint, and the result is raised to the power of
+    /// `1 / gamma`. Values above one for gamma darken antialiased edges.
+    /// Default is `(1, 0)`.
+    pub fn gamma(&mut self, gamma: f32, contrast: f32) -> &mut Self {
+        self.gamma = gamma;
+        self.contrast = contrast;
+        self
+    }
+
+    /// Specifies whether outlines should be emboldened by a small amount
+    /// that decreases with size to improve the legibility of thin stems at
+    /// small sizes. This is added to any strength specified with
+    /// [`embolden`](Render::embolden). Default is `false`.
+    pub fn stem_darkening(&mut self, enable: bool) -> &mut Self {
+        self.stem_darkening = enable;
+        self
+    }
+
     /// Renders the specified glyph using the current configuration into the
     /// provided image.
     pub fn render_into(&self, scaler: &mut Scaler, glyph_id: GlyphId, image: &mut Image) -> bool {
@@ -840,25 +1236,49 @@ impl<'a> Render<'a> {
                     }
                     scaler.state.outline.clear();
                     if scaler.scale_outline_impl(glyph_id, None, None) {
+                        let strength = self.embolden + self.darkening(scaler.size);
                         let state = &mut scaler.state;
                         let rcx = &mut state.rcx;
                         let outline = &mut state.outline;
-                        if self.embolden != 0. {
-                            outline.embolden(self.embolden, self.embolden);
+                        if strength != 0. {
+                            outline.embolden(strength, strength);
                         }
                         if let Some(transform) = &self.transform {
                             outline.transform(transform);
                         }
-                        let placement = Mask::with_scratch(outline.path(), rcx)
-                            .format(self.format)
-                            .origin(Origin::BottomLeft)
-                            .style(self.style)
-                            .render_offset(self.offset)
-                            .inspect(|fmt, w, h| {
-                                image.data.resize(fmt.buffer_size(w, h), 0);
-                            })
-                            .render_into(&mut image.data[..], None);
-                        image.placement = placement;
+                        if let Some(field) = &self.distance_field {
+                            let builder = &mut state.sdf;
+                            builder.commands.clear();
+                            let fill =
+                                rcx.apply(outline.path(), self.style, None, &mut builder.commands);
+                            image.placement = builder.build(
+                                fill,
+                                self.offset,
+                                field.spread,
+                                field.resolution,
+                                &mut image.data,
+                            );
+                            image.content = Content::DistanceField;
+                            image.source = Source::Outline;
+                            return true;
+                        }
+                        image.placement = if self.is_filtered() {
+                            let scratch = &mut state.scratch0;
+                            self.render_filtered(outline.path(), rcx, scratch, &mut image.data)
+                        } else {
+                            Mask::with_scratch(outline.path(), rcx)
+                                .format(self.format)
+                                .origin(Origin::BottomLeft)
+                                .style(self.style)
+                                .render_offset(self.offset)
+                                .inspect(|fmt, w, h| {
+                                    image.data.resize(fmt.buffer_size(w, h), 0);
+                                })
+                                .render_into(&mut image.data[..], None)
+                        };
+                        if let Some(table) = lcd::gamma_table(self.gamma, self.contrast) {
+                            lcd::apply_table(&table, &mut image.data);
+                        }
                         image.content = if self.format == Format::Alpha {
                             Content::Mask
                         } else {
@@ -872,23 +1292,37 @@ impl<'a> Render<'a> {
                     if !scaler.has_color_outlines() {
                         continue;
                     }
+                    if scaler.proxy.color.paint(scaler.font.data, glyph_id).is_some() {
+                        if self.render_paint(scaler, glyph_id, *palette_index, image) {
+                            return true;
+                        }
+                        continue;
+                    }
                     scaler.state.outline.clear();
                     if scaler.scale_color_outline_impl(glyph_id) {
+                        let darkening = self.darkening(scaler.size);
                         let font = &scaler.font;
                         let proxy = &scaler.proxy;
                         let state = &mut scaler.state;
                         let scratch = &mut state.scratch0;
+                        let scratch1 = &mut state.scratch1;
                         let rcx = &mut state.rcx;
                         let outline = &mut state.outline;
+                        let colors = &mut state.colors;
+                        let has_palette = self.load_palette(font, proxy, *palette_index, colors);
                         // Cool effect, but probably not generally desirable.
                         // Maybe expose a separate option?
                         // if self.embolden != 0. {
                         //     outline.embolden(self.embolden, self.embolden);
                         // }
+                        if darkening != 0. {
+                            outline.embolden(darkening, darkening);
+                        }
                         if let Some(transform) = &self.transform {
                             outline.transform(transform);
                         }
-                        let palette = proxy.color.palette(font, *palette_index);
+                        let gamma = lcd::gamma_table(self.gamma, self.contrast);
+                        let filtered = self.is_filtered();
                         let mut base_x = 0i32;
                         let mut base_y = 0i32;
                         let mut base_w = 0u32;
@@ -903,14 +1337,21 @@ impl<'a> Render<'a> {
                                 }
                             };
                             scratch.clear();
-                            let placement = Mask::with_scratch(layer.path(), rcx)
-                                .origin(Origin::BottomLeft)
-                                .style(self.style)
-                                .render_offset(self.offset)
-                                .inspect(|fmt, w, h| {
-                                    scratch.resize(fmt.buffer_size(w, h), 0);
-                                })
-                                .render_into(&mut scratch[..], None);
+                            let placement = if filtered {
+                                self.render_filtered(layer.path(), rcx, scratch1, scratch)
+                            } else {
+                                Mask::with_scratch(layer.path(), rcx)
+                                    .origin(Origin::BottomLeft)
+                                    .style(self.style)
+                                    .render_offset(self.offset)
+                                    .inspect(|fmt, w, h| {
+                                        scratch.resize(fmt.buffer_size(w, h), 0);
+                                    })
+                                    .render_into(&mut scratch[..], None)
+                            };
+                            if let Some(table) = &gamma {
+                                lcd::apply_table(table, scratch);
+                            }
                             if i == 0 {
                                 base_x = placement.left;
                                 base_y = placement.top;
@@ -921,10 +1362,15 @@ impl<'a> Render<'a> {
                             }
                             let color = layer
                                 .color_index()
-                                .map(|i| palette.map(|p| p.get(i)))
-                                .flatten()
+                                .filter(|_| has_palette)
+                                .map(|i| colors.get(i as usize).copied().unwrap_or([0; 4]))
                                 .unwrap_or(self.foreground);
-                            bitmap::blit(
+                            let blit = if filtered {
+                                bitmap::blit_subpixel
+                            } else {
+                                bitmap::blit
+                            };
+                            blit(
                                 &scratch[..],
                                 placement.width,
                                 placement.height,
@@ -959,11 +1405,190 @@ impl<'a> Render<'a> {
                         return true;
                     }
                 }
+                Source::Svg => {
+                    if !scaler.has_svg() {
+                        continue;
+                    }
+                    if self.render_svg(scaler, glyph_id, image) {
+                        return true;
+                    }
+                }
             }
         }
         false
     }
 
+    /// Returns true if subpixel masks should be rasterized with oversampling
+    /// and filtered.
+    fn is_filtered(&self) -> bool {
+        self.format != Format::Alpha
+            && (self.lcd_filter.is_some() || self.subpixel_order != SubpixelOrder::Rgb)
+    }
+
+    /// Returns the stem darkening strength for the specified size.
+    fn darkening(&self, size: f32) -> f32 {
+        if self.stem_darkening {
+            lcd::darkening(size)
+        } else {
+            0.
+        }
+    }
+
+    /// Rasterizes a path at three times the resolution along the subpixel
+    /// axis and filters the result into an RGBA subpixel mask.
+    fn render_filtered(
+        &self,
+        path: impl PathData,
+        rcx: &mut Scratch,
+        scratch: &mut Vec<u8>,
+        target: &mut Vec<u8>,
+    ) -> Placement {
+        let (x, y) = match self.subpixel_order {
+            SubpixelOrder::Rgb | SubpixelOrder::Bgr => (3., 1.),
+            SubpixelOrder::Vrgb | SubpixelOrder::Vbgr => (1., 3.),
+        };
+        scratch.clear();
+        let placement = Mask::with_scratch(path, rcx)
+            .origin(Origin::BottomLeft)
+            .style(self.style)
+            .transform(Some(Transform::scale(x, y)))
+            .render_offset(Vector::new(self.offset.x * x, self.offset.y * y))
+            .inspect(|fmt, w, h| {
+                scratch.resize(fmt.buffer_size(w, h), 0);
+            })
+            .render_into(&mut scratch[..], None);
+        lcd::filter(
+            scratch,
+            placement,
+            self.lcd_filter,
+            self.subpixel_order,
+            target,
+        )
+    }
+
+    /// Loads the colors of the selected palette with overrides applied.
+    /// Returns false if the palette is not available.
+    fn load_palette(
+        &self,
+        font: &FontRef,
+        proxy: &ScalerProxy,
+        palette_index: PaletteIndex,
+        colors: &mut Vec<[u8; 4]>,
+    ) -> bool {
+        colors.clear();
+        let palette = match self.palette {
+            Some(BasePalette::Index(index)) => proxy.color.palette(font, index),
+            Some(BasePalette::Usability(usability)) => proxy
+                .color
+                .palettes(font)
+                .find(|palette| match (palette.usability(), usability) {
+                    (Some(Usability::Both), _) => true,
+                    (Some(a), b) => a == b,
+                    _ => false,
+                }),
+            None => proxy.color.palette(font, palette_index),
+        };
+        let palette = match palette {
+            Some(palette) => palette,
+            _ if self.palette.is_some() => match proxy.color.palette(font, 0) {
+                Some(palette) => palette,
+                _ => return false,
+            },
+            _ => return false,
+        };
+        colors.extend((0..palette.len()).map(|i| palette.get(i)));
+        for (index, color) in self.palette_overrides {
+            if let Some(entry) = colors.get_mut(*index as usize) {
+                *entry = *color;
+            }
+        }
+        true
+    }
+
+    /// Renders a color glyph defined by a paint graph.
+    fn render_paint(
+        &self,
+        scaler: &mut Scaler,
+        glyph_id: GlyphId,
+        palette_index: PaletteIndex,
+        image: &mut Image,
+    ) -> bool {
+        let mut base = Transform::translation(self.offset.x, self.offset.y);
+        if let Some(transform) = &self.transform {
+            base = canvas::concat(&base, transform);
+        }
+        let mut bounds = canvas::BoundsPainter::new(base);
+        if !scaler.paint_color_glyph(glyph_id, &mut bounds) {
+            return false;
+        }
+        let placement = match bounds.placement() {
+            Some(placement) => placement,
+            _ => return false,
+        };
+        let state = &mut *scaler.state;
+        self.load_palette(&scaler.font, scaler.proxy, palette_index, &mut state.colors);
+        let mut canvas = core::mem::take(&mut state.canvas);
+        canvas.begin(placement, base, state.colors.iter().copied(), self.foreground);
+        canvas.gamma = lcd::gamma_table(self.gamma, self.contrast);
+        let ok = scaler.paint_color_glyph(glyph_id, &mut canvas);
+        canvas.finish(&mut image.data);
+        scaler.state.canvas = canvas;
+        image.placement = placement;
+        image.source = Source::ColorOutline(palette_index);
+        image.content = Content::Color;
+        ok
+    }
+
+    /// Renders a color glyph described by an SVG document.
+    fn render_svg(&self, scaler: &mut Scaler, glyph_id: GlyphId, image: &mut Image) -> bool {
+        let font = scaler.font;
+        let documents = SvgDocuments::from_font_and_offset(&font, scaler.proxy.svg);
+        let doc = match documents.for_glyph(glyph_id) {
+            Some(doc) => doc,
+            _ => return false,
+        };
+        let state = &mut *scaler.state;
+        let key = (scaler.id, doc.offset);
+        if !state.svg.is_loaded(key) {
+            if !doc.decompress_into(&mut state.scratch0) {
+                return false;
+            }
+            if !state.svg.load(key, &state.scratch0) {
+                return false;
+            }
+        }
+        let single = doc.first_glyph() == doc.last_glyph();
+        let node = match state.svg.glyph(glyph_id, single) {
+            Some(node) => node,
+            _ => return false,
+        };
+        // Documents are specified in font units with the y-axis pointing
+        // down.
+        let scale = scaler.scale;
+        let mut base = Transform::translation(self.offset.x, self.offset.y);
+        if let Some(transform) = &self.transform {
+            base = canvas::concat(&base, transform);
+        }
+        base = canvas::concat(&base, &Transform::scale(scale, -scale));
+        self.load_palette(&font, scaler.proxy, 0, &mut state.colors);
+        let palette_len = state.colors.len();
+        let mut bounds = canvas::BoundsPainter::new(base);
+        svg::SvgRenderer::new(&state.svg, &mut state.colors, palette_len).paint(node, &mut bounds);
+        let placement = match bounds.placement() {
+            Some(placement) => placement,
+            _ => return false,
+        };
+        let canvas = &mut state.canvas;
+        canvas.begin(placement, base, state.colors.iter().copied(), self.foreground);
+        canvas.gamma = lcd::gamma_table(self.gamma, self.contrast);
+        svg::SvgRenderer::new(&state.svg, &mut state.colors, palette_len).paint(node, canvas);
+        canvas.finish(&mut image.data);
+        image.placement = placement;
+        image.source = Source::Svg;
+        image.content = Content::Color;
+        true
+    }
+
     /// Renders the specified glyph using the current configuration.
     pub fn render(&self, scaler: &mut Scaler, glyph_id: GlyphId) -> Option<Image> {
         let mut image = Image::new();
@@ -975,6 +1600,21 @@ impl<'a> Render<'a> {
     }
 }
 
+/// Applies the automatic hinter to the most recently added layer of an
+/// outline.
+fn autohint_layer(
+    cache: &mut autohint::Cache,
+    index: usize,
+    glyph_id: GlyphId,
+    outline: &mut Outline,
+) {
+    let len = outline.len();
+    if let Some(mut layer) = len.checked_sub(1).and_then(|i| outline.get_mut(i)) {
+        let verbs = layer.verbs();
+        cache.hint(index, glyph_id, layer.points_mut(), verbs);
+    }
+}
+
 fn fill_outline(
     outline: &mut Outline,
     points: &[glyf::Point],
@@ -0,0 +1,185 @@
+/*!
+Paint graphs for color glyphs.
+
+Color glyphs defined by version 1 of the `COLR` table are described by a
+directed acyclic graph of paint operations: fills with solid colors and
+gradients, transforms, clips and compositing. The [`ColorPainter`] trait
+receives a flattened traversal of this graph from
+[`Scaler::paint_color_glyph`](super::Scaler::paint_color_glyph) and can be
+implemented to drive a GPU renderer or vector backend. Layered glyphs from
+version 0 of the table are reported through the same interface as a
+sequence of solid color fills clipped to glyph outlines.
+
+All geometry is provided in the scaled coordinate space of the scaler with
+the y-axis pointing up, matching the space of the [outlines](super::Outline)
+produced by the scaler.
+*/
+
+use super::Outline;
+use crate::GlyphId;
+use zeno::{Bounds, Point, Transform};
+
+/// Receiver for the operations of a color glyph paint graph.
+///
+/// Operations are properly nested: each push is matched by a corresponding
+/// pop of the same kind.
+pub trait ColorPainter {
+    /// Applies the specified transform to subsequent operations, in addition
+    /// to any transforms currently in effect.
+    fn push_transform(&mut self, transform: Transform);
+
+    /// Removes the most recently pushed transform.
+    fn pop_transform(&mut self);
+
+    /// Restricts subsequent fills to the interior of the outline of the
+    /// specified glyph.
+    fn push_clip_glyph(&mut self, glyph_id: GlyphId, outline: &Outline);
+
+    /// Restricts subsequent fills to the specified rectangle.
+    fn push_clip_box(&mut self, bounds: Bounds);
+
+    /// Removes the most recently pushed clip.
+    fn pop_clip(&mut self);
+
+    /// Fills the current clip region with the specified brush.
+    fn fill(&mut self, brush: &Brush);
+
+    /// Begins a new transparent layer that will be composited onto the
+    /// current content with the specified mode when popped.
+    fn push_layer(&mut self, mode: CompositeMode);
+
+    /// Composites the most recently pushed layer.
+    fn pop_layer(&mut self);
+}
+
+/// Color of a fill or gradient stop.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct PaintColor {
+    /// Index of the color in the selected palette, or `None` for the
+    /// foreground color.
+    pub palette_index: Option<u16>,
+    /// Alpha multiplier in the range 0..=1.
+    pub alpha: f32,
+}
+
+/// Color and position of a stop on a gradient color line.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct ColorStop {
+    /// Position of the stop on the color line.
+    pub offset: f32,
+    /// Color of the stop.
+    pub color: PaintColor,
+}
+
+/// Behavior of a gradient outside of the range of its color line.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Extend {
+    /// Use the color of the nearest stop.
+    Pad,
+    /// Repeat the color line.
+    Repeat,
+    /// Repeat the color line, reversing it on every other repetition.
+    Reflect,
+}
+
+/// Source of color for a fill.
+#[derive(Copy, Clone, Debug)]
+pub enum Brush<'a> {
+    /// Solid color.
+    Solid(PaintColor),
+    /// Gradient along the line from `start` to `end`.
+    LinearGradient {
+        start: Point,
+        end: Point,
+        stops: &'a [ColorStop],
+        extend: Extend,
+    },
+    /// Gradient between two circles.
+    RadialGradient {
+        start_center: Point,
+        start_radius: f32,
+        end_center: Point,
+        end_radius: f32,
+        stops: &'a [ColorStop],
+        extend: Extend,
+    },
+    /// Gradient around a center point between two angles, in counter-clockwise
+    /// degrees.
+    SweepGradient {
+        center: Point,
+        start_angle: f32,
+        end_angle: f32,
+        stops: &'a [ColorStop],
+        extend: Extend,
+    },
+}
+
+/// Mode for compositing a layer onto the content beneath it.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum CompositeMode {
+    Clear,
+    Src,
+    Dest,
+    SrcOver,
+    DestOver,
+    SrcIn,
+    DestIn,
+    SrcOut,
+    DestOut,
+    SrcAtop,
+    DestAtop,
+    Xor,
+    Plus,
+    Screen,
+    Overlay,
+    Darken,
+    Lighten,
+    ColorDodge,
+    ColorBurn,
+    HardLight,
+    SoftLight,
+    Difference,
+    Exclusion,
+    Multiply,
+    Hue,
+    Saturation,
+    Color,
+    Luminosity,
+}
+
+impl CompositeMode {
+    pub(super) fn from_raw(value: u8) -> Option<Self> {
+        use CompositeMode::*;
+        Some(match value {
+            0 => Clear,
+            1 => Src,
+            2 => Dest,
+            3 => SrcOver,
+            4 => DestOver,
+            5 => SrcIn,
+            6 => DestIn,
+            7 => SrcOut,
+            8 => DestOut,
+            9 => SrcAtop,
+            10 => DestAtop,
+            11 => Xor,
+            12 => Plus,
+            13 => Screen,
+            14 => Overlay,
+            15 => Darken,
+            16 => Lighten,
+            17 => ColorDodge,
+            18 => ColorBurn,
+            19 => HardLight,
+            20 => SoftLight,
+            21 => Difference,
+            22 => Exclusion,
+            23 => Multiply,
+            24 => Hue,
+            25 => Saturation,
+            26 => Color,
+            27 => Luminosity,
+            _ => return None,
+        })
+    }
+}
@@ -0,0 +1,217 @@
+/*!
+Standalone PostScript font programs.
+
+Fonts embedded in PDF documents are frequently stored as bare font programs
+rather than complete OpenType fonts: compact font format data (`FontFile3`)
+or Type 1 programs (`FontFile`). The latter are also distributed as PFA and
+PFB files. The types in this module load these programs directly and
+produce scaled [outlines](Outline) without requiring an sfnt wrapper.
+
+Outlines are scaled relative to an em of 1000 units after applying the font
+matrix, which matches the glyph space of PDF text. A size of 0 produces
+outlines in this unscaled space.
+
+The resulting outlines can be rendered with [zeno]:
+```no_run
+# use swash::scale::postscript::Type1Font;
+# let data: &[u8] = &[];
+use swash::zeno::Mask;
+
+let font = Type1Font::new(data).unwrap();
+let glyph_id = font.glyph_by_name("a").unwrap();
+let outline = font.scale_outline(glyph_id, 16.).unwrap();
+let (mask, placement) = Mask::new(outline.path()).render();
+```
+*/
+
+use super::cff::{Cff, HinterState, Type1};
+use super::internal::cff::Charset;
+use super::{CffBuilder, Outline};
+use crate::GlyphId;
+use zeno::Transform;
+
+/// Units per em of the glyph space produced by the font matrix.
+const UNITS_PER_EM: f32 = 1000.;
+
+/// Bare compact font format (CFF or CFF2) program.
+#[derive(Copy, Clone)]
+pub struct CffFont<'a> {
+    cff: Cff<'a>,
+    charset: Option<Charset<'a>>,
+}
+
+impl<'a> CffFont<'a> {
+    /// Creates a new font from the specified CFF or CFF2 data.
+    pub fn new(data: &'a [u8]) -> Option<Self> {
+        let cff = Cff::new(data)?;
+        Some(Self {
+            cff,
+            charset: Charset::with_cids(data),
+        })
+    }
+
+    /// Returns the number of glyphs in the font.
+    pub fn glyph_count(&self) -> u16 {
+        self.cff.glyph_count()
+    }
+
+    /// Returns true if the font is CID-keyed.
+    pub fn is_cid(&self) -> bool {
+        self.charset.map(|charset| charset.is_cid()).unwrap_or(false)
+    }
+
+    /// Returns the name of the specified glyph. CID-keyed and CFF2 fonts do
+    /// not provide glyph names.
+    pub fn glyph_name(&self, glyph_id: GlyphId) -> Option<&'a str> {
+        self.charset?.name(glyph_id)
+    }
+
+    /// Returns the identifier of the first glyph with the specified name.
+    ///
+    /// This performs a linear search over the glyph names.
+    pub fn glyph_by_name(&self, name: &str) -> Option<GlyphId> {
+        let charset = self.charset?;
+        if charset.is_cid() {
+            return None;
+        }
+        charset.glyph_by_name(name)
+    }
+
+    /// Returns the glyph mapped to the specified character code by the
+    /// built-in encoding of a name-keyed font. The predefined expert
+    /// encoding is not supported.
+    pub fn glyph_by_code(&self, code: u8) -> Option<GlyphId> {
+        self.charset?.glyph_by_code(code)
+    }
+
+    /// Returns the glyph for the specified CID in a CID-keyed font.
+    pub fn glyph_by_cid(&self, cid: u16) -> Option<GlyphId> {
+        let charset = self.charset?;
+        if !charset.is_cid() {
+            return None;
+        }
+        charset.glyph_by_sid(cid)
+    }
+
+    /// Scales an outline for the specified glyph into the provided outline,
+    /// optionally applying hinting.
+    pub fn scale_outline_into(
+        &self,
+        glyph_id: GlyphId,
+        size: f32,
+        hint: bool,
+        outline: &mut Outline,
+    ) -> bool {
+        outline.clear();
+        let glyph = match self.cff.get(glyph_id) {
+            Some(glyph) => glyph,
+            _ => return false,
+        };
+        let scale = if size != 0. { size / UNITS_PER_EM } else { 1. };
+        outline.begin_layer(None);
+        let mut builder = CffBuilder { outline };
+        let ok = if hint {
+            let state = HinterState::new(&glyph, scale, &[]);
+            glyph.path(scale, &[], Some(&state), &mut builder)
+        } else {
+            glyph.path(scale, &[], None, &mut builder)
+        };
+        outline.maybe_close();
+        outline.finish();
+        ok
+    }
+
+    /// Scales an outline for the specified glyph, optionally applying
+    /// hinting.
+    pub fn scale_outline(&self, glyph_id: GlyphId, size: f32, hint: bool) -> Option<Outline> {
+        let mut outline = Outline::new();
+        if self.scale_outline_into(glyph_id, size, hint, &mut outline) {
+            Some(outline)
+        } else {
+            None
+        }
+    }
+}
+
+/// Type 1 font program.
+///
+/// Glyph identifiers are assigned in the order of the char strings in the
+/// program, except that `.notdef` is always glyph 0. Hints are ignored when
+/// scaling.
+#[derive(Clone)]
+pub struct Type1Font {
+    font: Type1,
+}
+
+impl Type1Font {
+    /// Creates a new font from the specified data, which may be in PFB
+    /// (segmented binary), PFA (hexadecimal) or raw form with a binary
+    /// encrypted portion as embedded in PDF documents.
+    pub fn new(data: &[u8]) -> Option<Self> {
+        Some(Self {
+            font: Type1::new(data)?,
+        })
+    }
+
+    /// Returns the PostScript name of the font.
+    pub fn name(&self) -> &str {
+        self.font.name()
+    }
+
+    /// Returns the number of glyphs in the font.
+    pub fn glyph_count(&self) -> u16 {
+        self.font.glyph_count()
+    }
+
+    /// Returns the name of the specified glyph.
+    pub fn glyph_name(&self, glyph_id: GlyphId) -> Option<&str> {
+        self.font.glyph_name(glyph_id)
+    }
+
+    /// Returns the identifier of the glyph with the specified name.
+    ///
+    /// This performs a linear search over the glyph names.
+    pub fn glyph_by_name(&self, name: &str) -> Option<GlyphId> {
+        self.font.glyph_by_name(name)
+    }
+
+    /// Returns the glyph mapped to the specified character code by the
+    /// built-in encoding.
+    pub fn glyph_by_code(&self, code: u8) -> Option<GlyphId> {
+        self.font.glyph_by_code(code)
+    }
+
+    /// Scales an outline for the specified glyph into the provided outline.
+    pub fn scale_outline_into(&self, glyph_id: GlyphId, size: f32, outline: &mut Outline) -> bool {
+        outline.clear();
+        outline.begin_layer(None);
+        let mut builder = CffBuilder { outline };
+        if !self.font.path(glyph_id, &mut builder) {
+            return false;
+        }
+        outline.maybe_close();
+        outline.finish();
+        let scale = if size != 0. { size / UNITS_PER_EM } else { 1. };
+        let m = self.font.font_matrix();
+        let s = UNITS_PER_EM * scale;
+        outline.transform(&Transform::new(
+            m[0] * s,
+            m[1] * s,
+            m[2] * s,
+            m[3] * s,
+            m[4] * s,
+            m[5] * s,
+        ));
+        true
+    }
+
+    /// Scales an outline for the specified glyph.
+    pub fn scale_outline(&self, glyph_id: GlyphId, size: f32) -> Option<Outline> {
+        let mut outline = Outline::new();
+        if self.scale_outline_into(glyph_id, size, &mut outline) {
+            Some(outline)
+        } else {
+            None
+        }
+    }
+}
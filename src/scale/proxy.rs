@@ -1,6 +1,7 @@
 use super::{
     super::{
         strike::BitmapStrikesProxy,
+        internal::{raw_tag, RawFont},
         metrics::MetricsProxy,
         FontRef,
     },
@@ -22,6 +23,7 @@ pub struct ScalerProxy {
     pub color: ColorProxy,
     pub outlines: OutlinesProxy,
     pub bitmaps: BitmapStrikesProxy,
+    pub svg: u32,
     pub coord_count: u16,
 }
 
@@ -39,6 +41,7 @@ impl ScalerProxy {
             color: ColorProxy::from_font(font),
             outlines,
             bitmaps: BitmapStrikesProxy::from_font(font),
+            svg: font.table_offset(raw_tag(b"SVG ")),
             coord_count: font.variations().len() as u16,
         }
     }
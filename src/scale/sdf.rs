@@ -0,0 +1,230 @@
+//! Signed distance field generation.
+
+use zeno::{Command, Fill, Placement, Point, Vector};
+
+/// Maximum distance in field pixels between a curve and its flattened
+/// approximation.
+const TOLERANCE: f32 = 0.05;
+
+/// Maximum number of field samples per pixel.
+const MAX_RESOLUTION: f32 = 16.;
+
+/// Maximum spread in field samples.
+const MAX_SPREAD: f32 = 256.;
+
+/// Maximum number of samples in a single field.
+const MAX_SAMPLES: i64 = 1 << 24;
+
+/// Line segment with cached bounds.
+#[derive(Copy, Clone)]
+struct Segment {
+    a: Point,
+    b: Point,
+    min: Point,
+    max: Point,
+}
+
+impl Segment {
+    fn new(a: Point, b: Point) -> Self {
+        Self {
+            a,
+            b,
+            min: Point::new(a.x.min(b.x), a.y.min(b.y)),
+            max: Point::new(a.x.max(b.x), a.y.max(b.y)),
+        }
+    }
+
+    /// Returns the squared distance from the specified point to the segment.
+    fn distance_squared(&self, p: Point) -> f32 {
+        let d = self.b - self.a;
+        let len = d.x * d.x + d.y * d.y;
+        let t = if len > 0. {
+            (((p.x - self.a.x) * d.x + (p.y - self.a.y) * d.y) / len).clamp(0., 1.)
+        } else {
+            0.
+        };
+        let x = self.a.x + d.x * t - p.x;
+        let y = self.a.y + d.y * t - p.y;
+        x * x + y * y
+    }
+}
+
+/// Builder for signed distance fields. Retains buffers between glyphs.
+#[derive(Default)]
+pub struct FieldBuilder {
+    pub commands: Vec<Command>,
+    segments: Vec<Segment>,
+    near: Vec<Segment>,
+    crossings: Vec<(f32, i32)>,
+}
+
+impl FieldBuilder {
+    /// Builds a field from the current commands, writing one byte per sample
+    /// to the target. The spread is in pixels at the scaled size and the
+    /// resolution is the number of samples per pixel. Returns an empty
+    /// placement if the field would be unreasonably large.
+    pub fn build(
+        &mut self,
+        fill: Fill,
+        offset: Vector,
+        spread: f32,
+        resolution: f32,
+        target: &mut Vec<u8>,
+    ) -> Placement {
+        target.clear();
+        if resolution.is_nan() || spread.is_nan() {
+            return Placement::default();
+        }
+        let scale = resolution.clamp(f32::EPSILON, MAX_RESOLUTION);
+        self.flatten(offset, scale);
+        if self.segments.is_empty() {
+            return Placement::default();
+        }
+        let spread = (spread * scale).clamp(f32::EPSILON, MAX_SPREAD);
+        let pad = spread.ceil();
+        let mut min = self.segments[0].min;
+        let mut max = self.segments[0].max;
+        for s in &self.segments {
+            min = Point::new(min.x.min(s.min.x), min.y.min(s.min.y));
+            max = Point::new(max.x.max(s.max.x), max.y.max(s.max.y));
+        }
+        let left = (min.x.floor() - pad) as i64;
+        let top = (max.y.ceil() + pad) as i64;
+        let width = ((max.x.ceil() + pad) as i64 - left).max(0);
+        let height = (top - (min.y.floor() - pad) as i64).max(0);
+        if width * height > MAX_SAMPLES || left < i32::MIN as i64 || top > i32::MAX as i64 {
+            return Placement::default();
+        }
+        target.resize((width * height) as usize, 0);
+        let limit = spread * spread;
+        for (row, line) in target.chunks_exact_mut(width as usize).enumerate() {
+            let y = top as f32 - row as f32 - 0.5;
+            self.intersect(y);
+            // Only segments within the spread of the row can affect it.
+            self.near.clear();
+            self.near.extend(
+                self.segments
+                    .iter()
+                    .filter(|s| s.min.y - y < spread && y - s.max.y < spread),
+            );
+            let mut crossing = 0;
+            let mut winding = 0;
+            for (col, value) in line.iter_mut().enumerate() {
+                let x = left as f32 + col as f32 + 0.5;
+                while crossing < self.crossings.len() && self.crossings[crossing].0 < x {
+                    winding += self.crossings[crossing].1;
+                    crossing += 1;
+                }
+                let inside = match fill {
+                    Fill::NonZero => winding != 0,
+                    Fill::EvenOdd => winding & 1 != 0,
+                };
+                let p = Point::new(x, y);
+                let mut best = limit;
+                for s in &self.near {
+                    let dx = (s.min.x - x).max(x - s.max.x).max(0.);
+                    let dy = (s.min.y - y).max(y - s.max.y).max(0.);
+                    if dx * dx + dy * dy >= best {
+                        continue;
+                    }
+                    best = best.min(s.distance_squared(p));
+                }
+                let d = best.sqrt() / spread;
+                let d = if inside { d } else { -d };
+                *value = (128. + d * 127.).round().clamp(0., 255.) as u8;
+            }
+        }
+        Placement {
+            left: left as i32,
+            top: top as i32,
+            width: width as u32,
+            height: height as u32,
+        }
+    }
+
+    /// Collects the sorted crossings of the outline with a horizontal line.
+    fn intersect(&mut self, y: f32) {
+        self.crossings.clear();
+        for s in &self.segments {
+            if (s.a.y <= y) != (s.b.y <= y) {
+                let x = s.a.x + (y - s.a.y) * (s.b.x - s.a.x) / (s.b.y - s.a.y);
+                let dir = if s.b.y > s.a.y { 1 } else { -1 };
+                self.crossings.push((x, dir));
+            }
+        }
+        self.crossings
+            .sort_unstable_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(core::cmp::Ordering::Equal));
+    }
+
+    /// Converts the commands into closed polylines in field coordinates.
+    fn flatten(&mut self, offset: Vector, scale: f32) {
+        self.segments.clear();
+        let map = |p: Point| Point::new((p.x + offset.x) * scale, (p.y + offset.y) * scale);
+        let segments = &mut self.segments;
+        let mut start = Point::ZERO;
+        let mut cur = Point::ZERO;
+        let mut line = |from: Point, to: Point| {
+            if from != to {
+                segments.push(Segment::new(from, to));
+            }
+        };
+        for cmd in &self.commands {
+            match *cmd {
+                Command::MoveTo(p) => {
+                    line(cur, start);
+                    start = map(p);
+                    cur = start;
+                }
+                Command::LineTo(p) => {
+                    let p = map(p);
+                    line(cur, p);
+                    cur = p;
+                }
+                Command::QuadTo(c, p) => {
+                    let (c, p) = (map(c), map(p));
+                    let dd = (cur - c * 2. + p).length();
+                    let n = steps(dd * 0.25);
+                    let mut prev = cur;
+                    for i in 1..=n {
+                        let t = i as f32 / n as f32;
+                        let u = 1. - t;
+                        let q = cur * (u * u) + c * (2. * u * t) + p * (t * t);
+                        line(prev, q);
+                        prev = q;
+                    }
+                    cur = p;
+                }
+                Command::CurveTo(c1, c2, p) => {
+                    let (c1, c2, p) = (map(c1), map(c2), map(p));
+                    let dd = (cur - c1 * 2. + c2)
+                        .length()
+                        .max((c1 - c2 * 2. + p).length());
+                    let n = steps(dd * 0.75);
+                    let mut prev = cur;
+                    for i in 1..=n {
+                        let t = i as f32 / n as f32;
+                        let u = 1. - t;
+                        let q = cur * (u * u * u)
+                            + c1 * (3. * u * u * t)
+                            + c2 * (3. * u * t * t)
+                            + p * (t * t * t);
+                        line(prev, q);
+                        prev = q;
+                    }
+                    cur = p;
+                }
+                Command::Close => {
+                    line(cur, start);
+                    cur = start;
+                }
+            }
+        }
+        line(cur, start);
+    }
+}
+
+/// Returns the number of line segments needed to approximate a curve with
+/// the specified weighted second difference.
+fn steps(dd: f32) -> usize {
+    ((dd / TOLERANCE).sqrt().ceil() as usize).clamp(1, 64)
+}
@@ -0,0 +1,1023 @@
+//! Renderer for the static subset of SVG used by color glyphs.
+
+use super::canvas::{concat, BoundsPainter, Canvas};
+use super::paint::{Brush, ColorPainter, ColorStop, Extend, PaintColor};
+use super::Outline;
+use zeno::{
+    apply, Bounds, Cap, Command, Fill, Join, PathBuilder, PathData, Point, Stroke, Transform,
+};
+
+const NONE: u32 = !0;
+
+/// Maximum nesting depth for elements and references.
+const MAX_DEPTH: u32 = 32;
+
+/// Maximum number of elements painted for a single glyph, including the
+/// repeated contents of references.
+const MAX_ELEMENTS: u32 = 10000;
+
+/// Painter that can select the fill rule for subsequent clips.
+pub trait SvgPainter: ColorPainter {
+    fn set_fill(&mut self, fill: Fill);
+}
+
+impl SvgPainter for BoundsPainter {
+    fn set_fill(&mut self, _fill: Fill) {}
+}
+
+impl SvgPainter for Canvas {
+    fn set_fill(&mut self, fill: Fill) {
+        self.even_odd = fill == Fill::EvenOdd;
+    }
+}
+
+#[derive(Copy, Clone)]
+struct Node {
+    name: (u32, u32),
+    attrs: (u32, u32),
+    first_child: u32,
+    next_sibling: u32,
+}
+
+#[derive(Copy, Clone)]
+struct Attr {
+    name: (u32, u32),
+    value: (u32, u32),
+}
+
+/// Parsed SVG document.
+#[derive(Default)]
+pub struct Document {
+    key: Option<(u64, u32)>,
+    text: String,
+    nodes: Vec<Node>,
+    attrs: Vec<Attr>,
+}
+
+impl Document {
+    /// Returns true if the document was loaded with the specified key.
+    pub fn is_loaded(&self, key: (u64, u32)) -> bool {
+        self.key == Some(key)
+    }
+
+    /// Parses the specified text, associating the result with a key for
+    /// later reuse.
+    pub fn load(&mut self, key: (u64, u32), text: &[u8]) -> bool {
+        self.key = None;
+        self.text.clear();
+        self.nodes.clear();
+        self.attrs.clear();
+        match core::str::from_utf8(text) {
+            Ok(text) => self.text.push_str(text),
+            _ => return false,
+        }
+        if self.parse().is_none() {
+            self.nodes.clear();
+            return false;
+        }
+        self.key = Some(key);
+        true
+    }
+
+    /// Returns the element with the specified id.
+    pub fn find(&self, id: &str) -> Option<u32> {
+        (0..self.nodes.len() as u32).find(|&node| self.attr(node, "id") == Some(id))
+    }
+
+    /// Returns the element for the specified glyph. Documents describing a
+    /// single glyph may omit the identifier.
+    pub fn glyph(&self, glyph_id: u16, single: bool) -> Option<u32> {
+        let mut buf = [0u8; 16];
+        let id = format_glyph_id(&mut buf, glyph_id);
+        self.find(id).or(if single && !self.nodes.is_empty() {
+            Some(0)
+        } else {
+            None
+        })
+    }
+
+    fn str(&self, range: (u32, u32)) -> &str {
+        self.text
+            .get(range.0 as usize..range.1 as usize)
+            .unwrap_or("")
+    }
+
+    fn name(&self, node: u32) -> &str {
+        let name = self.str(self.nodes[node as usize].name);
+        // Ignore namespace prefixes.
+        name.rsplit(':').next().unwrap_or(name)
+    }
+
+    fn attr(&self, node: u32, name: &str) -> Option<&str> {
+        let (start, end) = self.nodes[node as usize].attrs;
+        self.attrs[start as usize..end as usize]
+            .iter()
+            .find(|attr| self.str(attr.name) == name)
+            .map(|attr| self.str(attr.value))
+    }
+
+    /// Returns the value of a presentation property, preferring the `style`
+    /// attribute.
+    fn property(&self, node: u32, name: &str) -> Option<&str> {
+        if let Some(style) = self.attr(node, "style") {
+            for decl in style.split(';') {
+                let mut parts = decl.splitn(2, ':');
+                if let (Some(key), Some(value)) = (parts.next(), parts.next()) {
+                    if key.trim() == name {
+                        return Some(value.trim());
+                    }
+                }
+            }
+        }
+        self.attr(node, name)
+    }
+
+    fn href(&self, node: u32) -> Option<u32> {
+        let href = self
+            .attr(node, "xlink:href")
+            .or_else(|| self.attr(node, "href"))?;
+        self.find(href.trim().strip_prefix('#')?)
+    }
+
+    fn children(&self, node: u32) -> Children<'_> {
+        Children {
+            doc: self,
+            node: self.nodes[node as usize].first_child,
+        }
+    }
+
+    fn parse(&mut self) -> Option<()> {
+        let bytes = self.text.as_bytes();
+        let len = bytes.len();
+        let mut pos = 0;
+        // Stack of open elements with their last child.
+        let mut stack: Vec<(u32, u32)> = Vec::new();
+        let find = |pos: usize, pat: &str| -> Option<usize> {
+            self.text.get(pos..)?.find(pat).map(|i| pos + i)
+        };
+        let mut nodes = Vec::new();
+        let mut attrs = Vec::new();
+        while pos < len {
+            pos = match find(pos, "<") {
+                Some(p) => p,
+                _ => break,
+            };
+            let rest = &bytes[pos..];
+            if rest.starts_with(b"<!--") {
+                pos = find(pos + 4, "-->")? + 3;
+            } else if rest.starts_with(b"<![CDATA[") {
+                pos = find(pos + 9, "]]>")? + 3;
+            } else if rest.starts_with(b"<?") {
+                pos = find(pos + 2, "?>")? + 2;
+            } else if rest.starts_with(b"<!") {
+                let end = find(pos, ">")?;
+                pos = match find(pos, "[") {
+                    Some(open) if open < end => find(open, "]>")? + 2,
+                    _ => end + 1,
+                };
+            } else if rest.starts_with(b"</") {
+                stack.pop()?;
+                pos = find(pos, ">")? + 1;
+            } else {
+                pos += 1;
+                let start = pos;
+                while pos < len && !is_space(bytes[pos]) && bytes[pos] != b'/' && bytes[pos] != b'>'
+                {
+                    pos += 1;
+                }
+                let name = (start as u32, pos as u32);
+                let first_attr = attrs.len() as u32;
+                let mut closed = false;
+                loop {
+                    while pos < len && is_space(bytes[pos]) {
+                        pos += 1;
+                    }
+                    match *bytes.get(pos)? {
+                        b'>' => {
+                            pos += 1;
+                            break;
+                        }
+                        b'/' => {
+                            if *bytes.get(pos + 1)? != b'>' {
+                                return None;
+                            }
+                            pos += 2;
+                            closed = true;
+                            break;
+                        }
+                        _ => {}
+                    }
+                    let start = pos;
+                    while pos < len && !is_space(bytes[pos]) && bytes[pos] != b'=' {
+                        pos += 1;
+                    }
+                    let name = (start as u32, pos as u32);
+                    while pos < len && is_space(bytes[pos]) {
+                        pos += 1;
+                    }
+                    if *bytes.get(pos)? != b'=' {
+                        return None;
+                    }
+                    pos += 1;
+                    while pos < len && is_space(bytes[pos]) {
+                        pos += 1;
+                    }
+                    let quote = *bytes.get(pos)?;
+                    if quote != b'"' && quote != b'\'' {
+                        return None;
+                    }
+                    let start = pos + 1;
+                    pos = start + bytes.get(start..)?.iter().position(|&b| b == quote)?;
+                    attrs.push(Attr {
+                        name,
+                        value: (start as u32, pos as u32),
+                    });
+                    pos += 1;
+                }
+                let index = nodes.len() as u32;
+                if let Some(top) = stack.last_mut() {
+                    if top.1 == NONE {
+                        let parent: &mut Node = &mut nodes[top.0 as usize];
+                        parent.first_child = index;
+                    } else {
+                        let prev: &mut Node = &mut nodes[top.1 as usize];
+                        prev.next_sibling = index;
+                    }
+                    top.1 = index;
+                } else if !nodes.is_empty() {
+                    // Only a single root element is permitted.
+                    break;
+                }
+                nodes.push(Node {
+                    name,
+                    attrs: (first_attr, attrs.len() as u32),
+                    first_child: NONE,
+                    next_sibling: NONE,
+                });
+                if !closed {
+                    stack.push((index, NONE));
+                }
+            }
+        }
+        self.nodes = nodes;
+        self.attrs = attrs;
+        Some(())
+    }
+}
+
+struct Children<'a> {
+    doc: &'a Document,
+    node: u32,
+}
+
+impl<'a> Iterator for Children<'a> {
+    type Item = u32;
+
+    fn next(&mut self) -> Option<u32> {
+        if self.node == NONE {
+            return None;
+        }
+        let node = self.node;
+        self.node = self.doc.nodes[node as usize].next_sibling;
+        Some(node)
+    }
+}
+
+/// Source of color for a fill or stroke.
+#[derive(Copy, Clone)]
+enum Paint {
+    None,
+    Color([u8; 4]),
+    CurrentColor,
+    Palette(u16, [u8; 4]),
+    Server(u32, Option<[u8; 4]>),
+}
+
+/// Inherited presentation properties.
+#[derive(Copy, Clone)]
+struct Style {
+    fill: Paint,
+    fill_opacity: f32,
+    fill_rule: Fill,
+    stroke: Paint,
+    stroke_opacity: f32,
+    stroke_width: f32,
+    join: Join,
+    cap: Cap,
+    miter_limit: f32,
+    opacity: f32,
+}
+
+impl Default for Style {
+    fn default() -> Self {
+        Self {
+            fill: Paint::Color([0, 0, 0, 255]),
+            fill_opacity: 1.,
+            fill_rule: Fill::NonZero,
+            stroke: Paint::None,
+            stroke_opacity: 1.,
+            stroke_width: 1.,
+            join: Join::Miter,
+            cap: Cap::Butt,
+            miter_limit: 4.,
+            opacity: 1.,
+        }
+    }
+}
+
+/// Paints the elements of a document.
+pub struct SvgRenderer<'a> {
+    doc: &'a Document,
+    /// Palette colors followed by colors referenced by the document.
+    colors: &'a mut Vec<[u8; 4]>,
+    palette_len: usize,
+    stops: Vec<ColorStop>,
+    commands: Vec<Command>,
+    stroke: Vec<Command>,
+    outline: Outline,
+    /// Remaining number of elements that may be painted.
+    budget: u32,
+}
+
+impl<'a> SvgRenderer<'a> {
+    /// Creates a new renderer for a document. The colors buffer should begin
+    /// with the specified number of palette colors and will receive the
+    /// colors used by the document.
+    pub fn new(doc: &'a Document, colors: &'a mut Vec<[u8; 4]>, palette_len: usize) -> Self {
+        Self {
+            doc,
+            colors,
+            palette_len,
+            stops: Vec::new(),
+            commands: Vec::new(),
+            stroke: Vec::new(),
+            outline: Outline::new(),
+            budget: MAX_ELEMENTS,
+        }
+    }
+
+    /// Paints the specified element and its descendants.
+    pub fn paint(&mut self, node: u32, painter: &mut impl SvgPainter) {
+        self.colors.truncate(self.palette_len);
+        self.budget = MAX_ELEMENTS;
+        self.paint_node(node, Style::default(), 0, painter);
+    }
+
+    fn paint_node(&mut self, node: u32, parent: Style, depth: u32, painter: &mut impl SvgPainter) {
+        if depth > MAX_DEPTH || self.budget == 0 {
+            return;
+        }
+        self.budget -= 1;
+        let doc = self.doc;
+        let name = doc.name(node);
+        match name {
+            "defs" | "linearGradient" | "radialGradient" | "clipPath" | "mask" | "symbol"
+            | "style" | "title" | "desc" | "metadata" | "pattern" | "filter" | "marker" => return,
+            _ => {}
+        }
+        if doc.property(node, "display") == Some("none") {
+            return;
+        }
+        let style = self.style(node, &parent);
+        let transform = doc.attr(node, "transform").map(parse_transform);
+        if let Some(transform) = transform {
+            painter.push_transform(transform);
+        }
+        let clip = match doc.property(node, "clip-path").and_then(|v| self.url(v)) {
+            Some(clip) if doc.name(clip) == "clipPath" => {
+                self.clip_path(clip, painter);
+                true
+            }
+            _ => false,
+        };
+        match name {
+            "svg" | "g" | "a" | "switch" => {
+                for child in doc.children(node) {
+                    self.paint_node(child, style, depth + 1, painter);
+                }
+            }
+            "use" => {
+                if let Some(target) = doc.href(node) {
+                    let x = length(doc.attr(node, "x"), 0.);
+                    let y = length(doc.attr(node, "y"), 0.);
+                    painter.push_transform(Transform::translation(x, y));
+                    if doc.name(target) == "symbol" {
+                        for child in doc.children(target) {
+                            self.paint_node(child, style, depth + 1, painter);
+                        }
+                    } else {
+                        self.paint_node(target, style, depth + 1, painter);
+                    }
+                    painter.pop_transform();
+                }
+            }
+            _ => {
+                self.commands.clear();
+                if shape(doc, node, &mut self.commands) {
+                    self.paint_shape(&style, painter);
+                }
+            }
+        }
+        if clip {
+            painter.pop_clip();
+        }
+        if transform.is_some() {
+            painter.pop_transform();
+        }
+    }
+
+    /// Pushes a clip for the union of the shapes in a clip path.
+    fn clip_path(&mut self, clip: u32, painter: &mut impl SvgPainter) {
+        let doc = self.doc;
+        self.commands.clear();
+        for child in doc.children(clip) {
+            let start = self.commands.len();
+            if shape(doc, child, &mut self.commands) {
+                if let Some(transform) = doc.attr(child, "transform").map(parse_transform) {
+                    for command in &mut self.commands[start..] {
+                        *command = command.transform(&transform);
+                    }
+                }
+            }
+        }
+        let fill = match doc.property(clip, "clip-rule") {
+            Some("evenodd") => Fill::EvenOdd,
+            _ => Fill::NonZero,
+        };
+        build_outline(&self.commands, &mut self.outline);
+        painter.set_fill(fill);
+        painter.push_clip_glyph(0, &self.outline);
+        painter.set_fill(Fill::NonZero);
+    }
+
+    fn paint_shape(&mut self, style: &Style, painter: &mut impl SvgPainter) {
+        let bounds = bounds(&self.commands);
+        if !matches!(style.fill, Paint::None) {
+            build_outline(&self.commands, &mut self.outline);
+            let alpha = style.opacity * style.fill_opacity;
+            self.fill(style.fill, style.fill_rule, alpha, &bounds, painter);
+        }
+        if !matches!(style.stroke, Paint::None) && style.stroke_width > 0. {
+            let mut stroke = Stroke::new(style.stroke_width);
+            stroke
+                .join(style.join)
+                .cap(style.cap)
+                .miter_limit(style.miter_limit);
+            self.stroke.clear();
+            apply(&self.commands, stroke, None, &mut self.stroke);
+            build_outline(&self.stroke, &mut self.outline);
+            let alpha = style.opacity * style.stroke_opacity;
+            self.fill(style.stroke, Fill::NonZero, alpha, &bounds, painter);
+        }
+    }
+
+    /// Fills the current outline with the specified paint.
+    fn fill(
+        &mut self,
+        paint: Paint,
+        fill: Fill,
+        alpha: f32,
+        bounds: &Bounds,
+        painter: &mut impl SvgPainter,
+    ) {
+        painter.set_fill(fill);
+        painter.push_clip_glyph(0, &self.outline);
+        painter.set_fill(Fill::NonZero);
+        match paint {
+            Paint::Server(server, fallback) => {
+                if !self.fill_gradient(server, alpha, bounds, painter) {
+                    if let Some(color) = fallback {
+                        let color = self.color(Paint::Color(color), alpha);
+                        painter.fill(&Brush::Solid(color));
+                    }
+                }
+            }
+            _ => {
+                let color = self.color(paint, alpha);
+                painter.fill(&Brush::Solid(color));
+            }
+        }
+        painter.pop_clip();
+    }
+
+    fn fill_gradient(
+        &mut self,
+        server: u32,
+        alpha: f32,
+        bounds: &Bounds,
+        painter: &mut impl SvgPainter,
+    ) -> bool {
+        let doc = self.doc;
+        let name = doc.name(server);
+        let radial = match name {
+            "linearGradient" => false,
+            "radialGradient" => true,
+            _ => return false,
+        };
+        // Gradients inherit attributes and stops through references.
+        let mut chain = [NONE; 8];
+        let mut len = 0;
+        let mut node = Some(server);
+        while let Some(n) = node {
+            if len == chain.len() || chain[..len].contains(&n) {
+                break;
+            }
+            chain[len] = n;
+            len += 1;
+            node = doc.href(n);
+        }
+        let chain = &chain[..len];
+        let attr = |name: &str| chain.iter().find_map(|&n| doc.attr(n, name));
+        self.stops.clear();
+        if let Some(&node) = chain
+            .iter()
+            .find(|&&n| doc.children(n).any(|c| doc.name(c) == "stop"))
+        {
+            let mut last = 0f32;
+            for stop in doc.children(node).filter(|&c| doc.name(c) == "stop") {
+                let offset = fraction(doc.attr(stop, "offset"), 0.).max(last).min(1.);
+                last = offset;
+                let color = match doc.property(stop, "stop-color").map(parse_paint) {
+                    Some(Some(paint)) => paint,
+                    _ => Paint::Color([0, 0, 0, 255]),
+                };
+                let opacity = number(doc.property(stop, "stop-opacity")).unwrap_or(1.);
+                let color = self.color(color, alpha * opacity.clamp(0., 1.));
+                self.stops.push(ColorStop { offset, color });
+            }
+        }
+        if self.stops.is_empty() {
+            return false;
+        }
+        let extend = match attr("spreadMethod") {
+            Some("reflect") => Extend::Reflect,
+            Some("repeat") => Extend::Repeat,
+            _ => Extend::Pad,
+        };
+        let user_space = attr("gradientUnits") == Some("userSpaceOnUse");
+        let mut transform = if user_space {
+            Transform::IDENTITY
+        } else {
+            let size = bounds.max - bounds.min;
+            if size.x <= 0. || size.y <= 0. {
+                return false;
+            }
+            Transform::new(size.x, 0., 0., size.y, bounds.min.x, bounds.min.y)
+        };
+        if let Some(t) = attr("gradientTransform") {
+            transform = concat(&transform, &parse_transform(t));
+        }
+        let coord = |name: &str, default: f32| fraction(attr(name), default);
+        painter.push_transform(transform);
+        if radial {
+            let cx = coord("cx", 0.5);
+            let cy = coord("cy", 0.5);
+            let r = coord("r", 0.5);
+            let fx = fraction(attr("fx"), cx);
+            let fy = fraction(attr("fy"), cy);
+            let fr = coord("fr", 0.);
+            painter.fill(&Brush::RadialGradient {
+                start_center: Point::new(fx, fy),
+                start_radius: fr,
+                end_center: Point::new(cx, cy),
+                end_radius: r,
+                stops: &self.stops,
+                extend,
+            });
+        } else {
+            painter.fill(&Brush::LinearGradient {
+                start: Point::new(coord("x1", 0.), coord("y1", 0.)),
+                end: Point::new(coord("x2", 1.), coord("y2", 0.)),
+                stops: &self.stops,
+                extend,
+            });
+        }
+        painter.pop_transform();
+        true
+    }
+
+    /// Returns a paint color for a solid paint, adding the color to the
+    /// palette if necessary.
+    fn color(&mut self, paint: Paint, alpha: f32) -> PaintColor {
+        let color = match paint {
+            Paint::CurrentColor => None,
+            Paint::Palette(index, _) if (index as usize) < self.palette_len => Some(index),
+            Paint::Color(color) | Paint::Palette(_, color) => {
+                if self.colors.len() >= u16::MAX as usize {
+                    None
+                } else {
+                    self.colors.push(color);
+                    Some(self.colors.len() as u16 - 1)
+                }
+            }
+            _ => None,
+        };
+        PaintColor {
+            palette_index: color,
+            alpha,
+        }
+    }
+
+    /// Returns the element referenced by a `url(#id)` value.
+    fn url(&self, value: &str) -> Option<u32> {
+        let id = value.trim().strip_prefix("url(")?.trim_start();
+        let id = id.strip_prefix('#')?;
+        let end = id.find(')')?;
+        self.doc
+            .find(id[..end].trim().trim_matches(|c| c == '"' || c == '\''))
+    }
+
+    /// Computes the style of an element from its properties and the style
+    /// of its parent.
+    fn style(&self, node: u32, parent: &Style) -> Style {
+        let doc = self.doc;
+        let mut style = *parent;
+        let paint = |name: &str, current: Paint| match doc.property(node, name) {
+            Some(value) if value.trim().starts_with("url(") => match self.url(value) {
+                Some(server) => {
+                    let fallback = value.find(')').and_then(|i| parse_paint(&value[i + 1..]));
+                    let fallback = match fallback {
+                        Some(Paint::Color(color)) => Some(color),
+                        _ => None,
+                    };
+                    Paint::Server(server, fallback)
+                }
+                _ => Paint::None,
+            },
+            Some("inherit") | None => current,
+            Some(value) => parse_paint(value).unwrap_or(current),
+        };
+        style.fill = paint("fill", style.fill);
+        style.stroke = paint("stroke", style.stroke);
+        if let Some(v) = number(doc.property(node, "fill-opacity")) {
+            style.fill_opacity = v.clamp(0., 1.);
+        }
+        if let Some(v) = number(doc.property(node, "stroke-opacity")) {
+            style.stroke_opacity = v.clamp(0., 1.);
+        }
+        if let Some(v) = number(doc.property(node, "opacity")) {
+            // Group opacity is approximated by applying it to each
+            // descendant.
+            style.opacity *= v.clamp(0., 1.);
+        }
+        match doc.property(node, "fill-rule") {
+            Some("evenodd") => style.fill_rule = Fill::EvenOdd,
+            Some("nonzero") => style.fill_rule = Fill::NonZero,
+            _ => {}
+        }
+        if let Some(v) = number(doc.property(node, "stroke-width")) {
+            style.stroke_width = v;
+        }
+        if let Some(v) = number(doc.property(node, "stroke-miterlimit")) {
+            style.miter_limit = v;
+        }
+        match doc.property(node, "stroke-linejoin") {
+            Some("round") => style.join = Join::Round,
+            Some("bevel") => style.join = Join::Bevel,
+            Some("miter") => style.join = Join::Miter,
+            _ => {}
+        }
+        match doc.property(node, "stroke-linecap") {
+            Some("round") => style.cap = Cap::Round,
+            Some("square") => style.cap = Cap::Square,
+            Some("butt") => style.cap = Cap::Butt,
+            _ => {}
+        }
+        style
+    }
+}
+
+/// Appends the geometry of a basic shape or path element.
+fn shape(doc: &Document, node: u32, commands: &mut Vec<Command>) -> bool {
+    let attr = |name: &str| length(doc.attr(node, name), 0.);
+    let start = commands.len();
+    match doc.name(node) {
+        "path" => {
+            if let Some(data) = doc.attr(node, "d") {
+                data.copy_to(commands);
+            }
+        }
+        "rect" => {
+            let (x, y, w, h) = (attr("x"), attr("y"), attr("width"), attr("height"));
+            if w <= 0. || h <= 0. {
+                return false;
+            }
+            let rx = doc.attr(node, "rx").map(|v| length(Some(v), 0.));
+            let ry = doc.attr(node, "ry").map(|v| length(Some(v), 0.));
+            let (rx, ry) = match (rx, ry) {
+                (Some(rx), Some(ry)) => (rx, ry),
+                (Some(r), None) | (None, Some(r)) => (r, r),
+                _ => (0., 0.),
+            };
+            if rx > 0. && ry > 0. {
+                commands.add_round_rect([x, y], w, h, rx.min(w / 2.), ry.min(h / 2.));
+            } else {
+                commands.add_rect([x, y], w, h);
+            }
+        }
+        "circle" => {
+            let r = attr("r");
+            if r <= 0. {
+                return false;
+            }
+            commands.add_circle([attr("cx"), attr("cy")], r);
+        }
+        "ellipse" => {
+            let (rx, ry) = (attr("rx"), attr("ry"));
+            if rx <= 0. || ry <= 0. {
+                return false;
+            }
+            commands.add_ellipse([attr("cx"), attr("cy")], rx, ry);
+        }
+        "line" => {
+            commands.move_to([attr("x1"), attr("y1")]);
+            commands.line_to([attr("x2"), attr("y2")]);
+        }
+        name @ "polyline" | name @ "polygon" => {
+            let points = doc.attr(node, "points").unwrap_or("");
+            let mut values = numbers(points);
+            let mut first = true;
+            while let (Some(x), Some(y)) = (values.next(), values.next()) {
+                if first {
+                    commands.move_to([x, y]);
+                    first = false;
+                } else {
+                    commands.line_to([x, y]);
+                }
+            }
+            if name == "polygon" && !first {
+                commands.close();
+            }
+        }
+        _ => return false,
+    }
+    commands.len() > start
+}
+
+/// Returns the bounds of the points of a sequence of commands.
+fn bounds(commands: &[Command]) -> Bounds {
+    let mut min = Point::new(f32::MAX, f32::MAX);
+    let mut max = Point::new(f32::MIN, f32::MIN);
+    let mut add = |p: Point| {
+        min = Point::new(min.x.min(p.x), min.y.min(p.y));
+        max = Point::new(max.x.max(p.x), max.y.max(p.y));
+    };
+    for command in commands {
+        match *command {
+            Command::MoveTo(p) | Command::LineTo(p) => add(p),
+            Command::QuadTo(p0, p1) => {
+                add(p0);
+                add(p1);
+            }
+            Command::CurveTo(p0, p1, p2) => {
+                add(p0);
+                add(p1);
+                add(p2);
+            }
+            Command::Close => {}
+        }
+    }
+    if min.x > max.x {
+        return Bounds::default();
+    }
+    Bounds::new(min, max)
+}
+
+fn build_outline(commands: &[Command], outline: &mut Outline) {
+    outline.clear();
+    outline.begin_layer(None);
+    for command in commands {
+        match *command {
+            Command::MoveTo(p) => {
+                outline.maybe_close();
+                outline.move_to(p);
+            }
+            Command::LineTo(p) => outline.line_to(p),
+            Command::QuadTo(p0, p1) => outline.quad_to(p0, p1),
+            Command::CurveTo(p0, p1, p2) => outline.curve_to(p0, p1, p2),
+            Command::Close => outline.close(),
+        }
+    }
+    outline.maybe_close();
+    outline.finish();
+}
+
+/// Parses a list of transform functions.
+fn parse_transform(value: &str) -> Transform {
+    let mut result = Transform::IDENTITY;
+    let mut rest = value;
+    while let Some(open) = rest.find('(') {
+        let name = rest[..open].trim_matches(|c: char| c.is_whitespace() || c == ',');
+        let close = match rest[open..].find(')') {
+            Some(close) => open + close,
+            _ => break,
+        };
+        let mut args = [0f32; 6];
+        let mut count = 0;
+        for (arg, value) in args.iter_mut().zip(numbers(&rest[open + 1..close])) {
+            *arg = value;
+            count += 1;
+        }
+        let [a, b, c, d, e, f] = args;
+        let t = match (name, count) {
+            ("matrix", 6) => Transform::new(a, b, c, d, e, f),
+            ("translate", 1) => Transform::translation(a, 0.),
+            ("translate", 2) => Transform::translation(a, b),
+            ("scale", 1) => Transform::scale(a, a),
+            ("scale", 2) => Transform::scale(a, b),
+            ("rotate", 1) | ("rotate", 3) => {
+                let (sin, cos) = a.to_radians().sin_cos();
+                let (cx, cy) = if count == 3 { (b, c) } else { (0., 0.) };
+                let x = cx - (cx * cos - cy * sin);
+                let y = cy - (cx * sin + cy * cos);
+                Transform::new(cos, sin, -sin, cos, x, y)
+            }
+            ("skewX", 1) => Transform::new(1., 0., a.to_radians().tan(), 1., 0., 0.),
+            ("skewY", 1) => Transform::new(1., a.to_radians().tan(), 0., 1., 0., 0.),
+            _ => Transform::IDENTITY,
+        };
+        result = concat(&result, &t);
+        rest = &rest[close + 1..];
+    }
+    result
+}
+
+/// Parses a paint value, returning `None` for unsupported values.
+fn parse_paint(value: &str) -> Option<Paint> {
+    let value = value.trim();
+    Some(match value {
+        "none" => Paint::None,
+        "currentColor" => Paint::CurrentColor,
+        _ => {
+            if let Some(var) = value.strip_prefix("var(") {
+                // Palette entries are exposed as custom properties named
+                // `--color<index>`.
+                let var = var.strip_suffix(')')?;
+                let mut parts = var.splitn(2, ',');
+                let name = parts.next()?.trim();
+                let fallback = match parts.next().map(parse_paint) {
+                    Some(Some(Paint::Color(color))) => color,
+                    _ => [0, 0, 0, 255],
+                };
+                let index = name.strip_prefix("--color")?.parse::<u16>().ok()?;
+                Paint::Palette(index, fallback)
+            } else {
+                Paint::Color(parse_color(value)?)
+            }
+        }
+    })
+}
+
+fn parse_color(value: &str) -> Option<[u8; 4]> {
+    if let Some(hex) = value.strip_prefix('#') {
+        let digit = |i: usize| u8::from_str_radix(hex.get(i..i + 1)?, 16).ok();
+        let byte = |i: usize| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok();
+        return match hex.len() {
+            3 | 4 => {
+                let a = if hex.len() == 4 { digit(3)? * 17 } else { 255 };
+                Some([digit(0)? * 17, digit(1)? * 17, digit(2)? * 17, a])
+            }
+            6 | 8 => {
+                let a = if hex.len() == 8 { byte(6)? } else { 255 };
+                Some([byte(0)?, byte(2)?, byte(4)?, a])
+            }
+            _ => None,
+        };
+    }
+    let func = value
+        .strip_prefix("rgba(")
+        .or_else(|| value.strip_prefix("rgb("));
+    if let Some(args) = func {
+        let args = args.strip_suffix(')')?;
+        let mut color = [0, 0, 0, 255];
+        for (i, arg) in args.split([',', '/']).enumerate().take(4) {
+            let arg = arg.trim();
+            let v = if let Some(percent) = arg.strip_suffix('%') {
+                percent.parse::<f32>().ok()? / 100.
+            } else if i == 3 {
+                arg.parse::<f32>().ok()?
+            } else {
+                arg.parse::<f32>().ok()? / 255.
+            };
+            color[i] = (v.clamp(0., 1.) * 255. + 0.5) as u8;
+        }
+        return Some(color);
+    }
+    if value.eq_ignore_ascii_case("transparent") {
+        return Some([0; 4]);
+    }
+    let lower = value.to_ascii_lowercase();
+    let index = NAMED_COLORS
+        .binary_search_by(|entry| entry.0.cmp(lower.as_str()))
+        .ok()?;
+    let c = NAMED_COLORS[index].1;
+    Some([(c >> 16) as u8, (c >> 8) as u8, c as u8, 255])
+}
+
+/// Parses a number, ignoring any trailing unit.
+fn number(value: Option<&str>) -> Option<f32> {
+    let value = value?.trim();
+    let end = value
+        .find(|c: char| !(c.is_ascii_digit() || c == '.' || c == '-' || c == '+' || c == 'e'))
+        .unwrap_or(value.len());
+    value[..end].parse().ok()
+}
+
+fn length(value: Option<&str>, default: f32) -> f32 {
+    number(value).unwrap_or(default)
+}
+
+/// Parses a number or percentage as a fraction.
+fn fraction(value: Option<&str>, default: f32) -> f32 {
+    match value.map(str::trim) {
+        Some(v) if v.ends_with('%') => number(Some(v)).map(|v| v / 100.).unwrap_or(default),
+        v => number(v).unwrap_or(default),
+    }
+}
+
+/// Returns an iterator over the numbers in a whitespace or comma separated
+/// list.
+fn numbers(value: &str) -> impl Iterator<Item = f32> + '_ {
+    value
+        .split(|c: char| c.is_whitespace() || c == ',')
+        .filter(|s| !s.is_empty())
+        .filter_map(|s| s.parse().ok())
+}
+
+fn is_space(b: u8) -> bool {
+    matches!(b, b' ' | b'\t' | b'\r' | b'\n')
+}
+
+fn format_glyph_id(buf: &mut [u8; 16], glyph_id: u16) -> &str {
+    buf[..5].copy_from_slice(b"glyph");
+    let mut digits = [0u8; 5];
+    let mut len = 0;
+    let mut n = glyph_id;
+    loop {
+        digits[len] = b'0' + (n % 10) as u8;
+        len += 1;
+        n /= 10;
+        if n == 0 {
+            break;
+        }
+    }
+    for i in 0..len {
+        buf[5 + i] = digits[len - 1 - i];
+    }
+    core::str::from_utf8(&buf[..5 + len]).unwrap_or("")
+}
+
+/// CSS named colors, sorted by name.
+#[rustfmt::skip]
+const NAMED_COLORS: [(&str, u32); 148] = [
+    ("aliceblue", 0xF0F8FF), ("antiquewhite", 0xFAEBD7), ("aqua", 0x00FFFF),
+    ("aquamarine", 0x7FFFD4), ("azure", 0xF0FFFF), ("beige", 0xF5F5DC),
+    ("bisque", 0xFFE4C4), ("black", 0x000000), ("blanchedalmond", 0xFFEBCD),
+    ("blue", 0x0000FF), ("blueviolet", 0x8A2BE2), ("brown", 0xA52A2A),
+    ("burlywood", 0xDEB887), ("cadetblue", 0x5F9EA0), ("chartreuse", 0x7FFF00),
+    ("chocolate", 0xD2691E), ("coral", 0xFF7F50), ("cornflowerblue", 0x6495ED),
+    ("cornsilk", 0xFFF8DC), ("crimson", 0xDC143C), ("cyan", 0x00FFFF),
+    ("darkblue", 0x00008B), ("darkcyan", 0x008B8B), ("darkgoldenrod", 0xB8860B),
+    ("darkgray", 0xA9A9A9), ("darkgreen", 0x006400), ("darkgrey", 0xA9A9A9),
+    ("darkkhaki", 0xBDB76B), ("darkmagenta", 0x8B008B), ("darkolivegreen", 0x556B2F),
+    ("darkorange", 0xFF8C00), ("darkorchid", 0x9932CC), ("darkred", 0x8B0000),
+    ("darksalmon", 0xE9967A), ("darkseagreen", 0x8FBC8F), ("darkslateblue", 0x483D8B),
+    ("darkslategray", 0x2F4F4F), ("darkslategrey", 0x2F4F4F), ("darkturquoise", 0x00CED1),
+    ("darkviolet", 0x9400D3), ("deeppink", 0xFF1493), ("deepskyblue", 0x00BFFF),
+    ("dimgray", 0x696969), ("dimgrey", 0x696969), ("dodgerblue", 0x1E90FF),
+    ("firebrick", 0xB22222), ("floralwhite", 0xFFFAF0), ("forestgreen", 0x228B22),
+    ("fuchsia", 0xFF00FF), ("gainsboro", 0xDCDCDC), ("ghostwhite", 0xF8F8FF),
+    ("gold", 0xFFD700), ("goldenrod", 0xDAA520), ("gray", 0x808080),
+    ("green", 0x008000), ("greenyellow", 0xADFF2F), ("grey", 0x808080),
+    ("honeydew", 0xF0FFF0), ("hotpink", 0xFF69B4), ("indianred", 0xCD5C5C),
+    ("indigo", 0x4B0082), ("ivory", 0xFFFFF0), ("khaki", 0xF0E68C),
+    ("lavender", 0xE6E6FA), ("lavenderblush", 0xFFF0F5), ("lawngreen", 0x7CFC00),
+    ("lemonchiffon", 0xFFFACD), ("lightblue", 0xADD8E6), ("lightcoral", 0xF08080),
+    ("lightcyan", 0xE0FFFF), ("lightgoldenrodyellow", 0xFAFAD2), ("lightgray", 0xD3D3D3),
+    ("lightgreen", 0x90EE90), ("lightgrey", 0xD3D3D3), ("lightpink", 0xFFB6C1),
+    ("lightsalmon", 0xFFA07A), ("lightseagreen", 0x20B2AA), ("lightskyblue", 0x87CEFA),
+    ("lightslategray", 0x778899), ("lightslategrey", 0x778899), ("lightsteelblue", 0xB0C4DE),
+    ("lightyellow", 0xFFFFE0), ("lime", 0x00FF00), ("limegreen", 0x32CD32),
+    ("linen", 0xFAF0E6), ("magenta", 0xFF00FF), ("maroon", 0x800000),
+    ("mediumaquamarine", 0x66CDAA), ("mediumblue", 0x0000CD), ("mediumorchid", 0xBA55D3),
+    ("mediumpurple", 0x9370DB), ("mediumseagreen", 0x3CB371), ("mediumslateblue", 0x7B68EE),
+    ("mediumspringgreen", 0x00FA9A), ("mediumturquoise", 0x48D1CC), ("mediumvioletred", 0xC71585),
+    ("midnightblue", 0x191970), ("mintcream", 0xF5FFFA), ("mistyrose", 0xFFE4E1),
+    ("moccasin", 0xFFE4B5), ("navajowhite", 0xFFDEAD), ("navy", 0x000080),
+    ("oldlace", 0xFDF5E6), ("olive", 0x808000), ("olivedrab", 0x6B8E23),
+    ("orange", 0xFFA500), ("orangered", 0xFF4500), ("orchid", 0xDA70D6),
+    ("palegoldenrod", 0xEEE8AA), ("palegreen", 0x98FB98), ("paleturquoise", 0xAFEEEE),
+    ("palevioletred", 0xDB7093), ("papayawhip", 0xFFEFD5), ("peachpuff", 0xFFDAB9),
+    ("peru", 0xCD853F), ("pink", 0xFFC0CB), ("plum", 0xDDA0DD),
+    ("powderblue", 0xB0E0E6), ("purple", 0x800080), ("rebeccapurple", 0x663399),
+    ("red", 0xFF0000), ("rosybrown", 0xBC8F8F), ("royalblue", 0x4169E1),
+    ("saddlebrown", 0x8B4513), ("salmon", 0xFA8072), ("sandybrown", 0xF4A460),
+    ("seagreen", 0x2E8B57), ("seashell", 0xFFF5EE), ("sienna", 0xA0522D),
+    ("silver", 0xC0C0C0), ("skyblue", 0x87CEEB), ("slateblue", 0x6A5ACD),
+    ("slategray", 0x708090), ("slategrey", 0x708090), ("snow", 0xFFFAFA),
+    ("springgreen", 0x00FF7F), ("steelblue", 0x4682B4), ("tan", 0xD2B48C),
+    ("teal", 0x008080), ("thistle", 0xD8BFD8), ("tomato", 0xFF6347),
+    ("turquoise", 0x40E0D0), ("violet", 0xEE82EE), ("wheat", 0xF5DEB3),
+    ("white", 0xFFFFFF), ("whitesmoke", 0xF5F5F5), ("yellow", 0xFFFF00),
+    ("yellowgreen", 0x9ACD32),
+];
@@ -3,7 +3,7 @@ Glyph cluster modeling-- output from the shaper.
 */
 
 use crate::text::cluster::{ClusterInfo, SourceRange, UserData};
-use crate::GlyphId;
+use crate::{GlyphId, ReverseCharmap};
 
 /// Glyph identifier and positioning information as a result of shaping.
 #[derive(Copy, Clone, Default, Debug)]
@@ -77,3 +77,152 @@ impl<'a> GlyphCluster<'a> {
         advance
     }
 }
+
+/// Table mapping glyph identifiers to the text they represent, suitable for
+/// generating a ToUnicode CMap when embedding shaped text in a PDF.
+///
+/// The table is built incrementally from shaped glyph clusters along with
+/// the source text that was supplied to the shaper. Clusters containing a
+/// single glyph map that glyph to the full text of the cluster which
+/// handles ligatures and other many-to-one substitutions. For clusters
+/// containing multiple glyphs, each glyph that nominally maps to a
+/// character in the cluster (according to the reverse character map) is
+/// assigned that character and the remaining text is assigned to the
+/// ligature components, if the counts match, or otherwise to the first
+/// unassigned glyph.
+///
+/// Since a glyph can only have a single entry, the first mapping
+/// encountered for each glyph is retained.
+#[derive(Clone, Default, Debug)]
+pub struct ToUnicode {
+    reverse: ReverseCharmap,
+    entries: Vec<(GlyphId, u32, u32)>,
+    text: String,
+    chars: Vec<(usize, char, bool)>,
+    unassigned: Vec<GlyphId>,
+}
+
+impl ToUnicode {
+    /// Creates a new table that uses the specified reverse character map to
+    /// resolve the glyphs of complex clusters.
+    pub fn new(reverse: ReverseCharmap) -> Self {
+        Self {
+            reverse,
+            ..Default::default()
+        }
+    }
+
+    /// Adds the mappings for a glyph cluster. The text must be the source
+    /// text that was supplied to the shaper as the source ranges of the
+    /// cluster are used to extract the associated characters.
+    pub fn add(&mut self, cluster: &GlyphCluster, text: &str) {
+        let source = match text.get(cluster.source.to_range()) {
+            Some(source) if !source.is_empty() => source,
+            _ => return,
+        };
+        match cluster.glyphs.len() {
+            0 => return,
+            1 => {
+                self.insert(cluster.glyphs[0].id, source);
+                return;
+            }
+            _ => {}
+        }
+        self.chars.clear();
+        self.chars
+            .extend(source.char_indices().map(|(i, ch)| (i, ch, false)));
+        self.unassigned.clear();
+        let mut assigned = false;
+        for glyph in cluster.glyphs {
+            let reverse = &self.reverse;
+            let found = self.chars.iter().position(|(_, ch, used)| {
+                !*used && reverse.get_all(glyph.id).any(|c| c == *ch)
+            });
+            if let Some(index) = found {
+                let (offset, ch, _) = self.chars[index];
+                self.chars[index].2 = true;
+                assigned = true;
+                let start = cluster.source.start as usize + offset;
+                if let Some(s) = text.get(start..start + ch.len_utf8()) {
+                    self.insert(glyph.id, s);
+                }
+            } else {
+                self.unassigned.push(glyph.id);
+            }
+        }
+        if self.unassigned.is_empty() {
+            return;
+        }
+        if !assigned && cluster.components.len() == self.unassigned.len() {
+            for i in 0..self.unassigned.len() {
+                if let Some(s) = text.get(cluster.components[i].to_range()) {
+                    self.insert(self.unassigned[i], s);
+                }
+            }
+            return;
+        }
+        let glyph_id = self.unassigned[0];
+        if self.find(glyph_id).is_ok() {
+            return;
+        }
+        let start = self.text.len();
+        for (_, ch, used) in &self.chars {
+            if !*used {
+                self.text.push(*ch);
+            }
+        }
+        if self.text.len() > start {
+            let index = self.find(glyph_id).unwrap_err();
+            self.entries
+                .insert(index, (glyph_id, start as u32, self.text.len() as u32));
+        }
+    }
+
+    /// Returns the text associated with the specified glyph identifier.
+    pub fn get(&self, glyph_id: GlyphId) -> Option<&str> {
+        let index = self.find(glyph_id).ok()?;
+        let (_, start, end) = self.entries[index];
+        self.text.get(start as usize..end as usize)
+    }
+
+    /// Returns the number of glyphs in the table.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns true if the table is empty.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Returns an iterator over the glyph identifier/text pairs in the
+    /// table, ordered by glyph identifier.
+    pub fn iter(&self) -> impl Iterator<Item = (GlyphId, &str)> + '_ {
+        self.entries
+            .iter()
+            .map(move |e| (e.0, &self.text[e.1 as usize..e.2 as usize]))
+    }
+
+    /// Removes all mappings from the table, retaining the reverse character
+    /// map.
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.text.clear();
+    }
+
+    fn find(&self, glyph_id: GlyphId) -> Result<usize, usize> {
+        self.entries.binary_search_by(|e| e.0.cmp(&glyph_id))
+    }
+
+    fn insert(&mut self, glyph_id: GlyphId, s: &str) {
+        if s.is_empty() {
+            return;
+        }
+        if let Err(index) = self.find(glyph_id) {
+            let start = self.text.len() as u32;
+            self.text.push_str(s);
+            self.entries
+                .insert(index, (glyph_id, start, self.text.len() as u32));
+        }
+    }
+}
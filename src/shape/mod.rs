@@ -145,8 +145,9 @@ let mut parser = Parser::new(
 // Loop over all of the clusters
 while parser.next(&mut cluster) {
     // Map all of the characters in the cluster
-    // to nominal glyph identifiers
-    cluster.map(|ch| charmap.map(ch));
+    // to nominal glyph identifiers (or variation
+    // sequences) using the character map
+    cluster.map(charmap);
     // Add the cluster to the shaper
     shaper.add_cluster(&cluster);
 }
@@ -201,7 +202,7 @@ fn select_font<'a>(fonts: &[FontRef<'a>], cluster: &mut CharCluster) -> Option<u
     let mut best = None;
     for (i, font) in fonts.iter().enumerate() {
         let charmap = font.charmap();
-        match cluster.map(|ch| charmap.map(ch)) {
+        match cluster.map(charmap) {
             // This font provided a glyph for every character
             Status::Complete => return Some(i),
             // This font provided the most complete mapping so far
@@ -639,7 +640,7 @@ impl<'a> Shaper<'a> {
         );
         let charmap = self.charmap;
         while parser.next(&mut cluster) {
-            cluster.map(|ch| charmap.map(ch));
+            cluster.map(charmap);
             self.add_cluster(&cluster);
         }
     }
@@ -4,7 +4,8 @@ Shaper that partitions by font using trait based per-cluster selection.
 */
 
 use super::{Direction, ShapeContext, Shaper};
-use crate::text::cluster::{CharCluster, Parser, Token};
+use crate::proxy::CharmapProxy;
+use crate::text::cluster::{CharCluster, Emoji, Parser, Status, Token};
 use crate::text::{Codepoint as _, Language, Script};
 use crate::{FontRef, Setting, Synthesis};
 use core::iter::Copied;
@@ -30,6 +31,276 @@ pub trait SelectedFont: PartialEq {
     }
 }
 
+/// Font selected by a [`FallbackSelector`].
+#[derive(Copy, Clone)]
+pub struct FallbackFont<'a> {
+    index: u16,
+    font: FontRef<'a>,
+    synthesis: Option<Synthesis>,
+}
+
+impl<'a> FallbackFont<'a> {
+    /// Returns the index of the font in the selector.
+    pub fn index(&self) -> usize {
+        self.index as usize
+    }
+}
+
+impl PartialEq for FallbackFont<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        self.index == other.index
+    }
+}
+
+impl SelectedFont for FallbackFont<'_> {
+    fn font(&self) -> FontRef<'_> {
+        self.font
+    }
+
+    fn synthesis(&self) -> Option<Synthesis> {
+        self.synthesis
+    }
+}
+
+/// Number of entries in the selection cache.
+const CACHE_SIZE: usize = 256;
+
+/// Font selector that walks an ordered list of fonts with optional script,
+/// language and emoji specific overrides.
+///
+/// For each cluster, fonts are considered in the following order: the
+/// emoji fonts (for clusters with color emoji presentation), the fonts
+/// registered for the current language, the fonts registered for the
+/// script of the cluster and finally all fonts in the order they were
+/// added. The first font that completely maps the cluster is selected.
+/// Otherwise, the font that mapped the largest portion of the cluster is
+/// chosen, falling back to the first font if none mapped anything.
+///
+/// Recent selections for single character clusters are cached, so
+/// repeatedly shaping text with the same character repertoire avoids
+/// searching the fallback chain.
+pub struct FallbackSelector<'a> {
+    fonts: Vec<(FontRef<'a>, CharmapProxy, Option<Synthesis>)>,
+    scripts: Vec<(Script, Vec<u16>)>,
+    languages: Vec<(Language, Vec<u16>)>,
+    emoji: Vec<u16>,
+    language: Option<Language>,
+    presentation: Emoji,
+    cache: Vec<(u32, u16)>,
+}
+
+impl<'a> FallbackSelector<'a> {
+    /// Creates a new empty fallback selector.
+    pub fn new() -> Self {
+        Self {
+            fonts: Vec::new(),
+            scripts: Vec::new(),
+            languages: Vec::new(),
+            emoji: Vec::new(),
+            language: None,
+            presentation: Emoji::Color,
+            cache: vec![(!0, 0); CACHE_SIZE],
+        }
+    }
+
+    /// Appends a font to the fallback chain and returns its index.
+    pub fn add_font(&mut self, font: FontRef<'a>) -> usize {
+        self.add_font_with_synthesis(font, None)
+    }
+
+    /// Appends a font with the specified synthesis state to the fallback
+    /// chain and returns its index.
+    pub fn add_font_with_synthesis(
+        &mut self,
+        font: FontRef<'a>,
+        synthesis: Option<Synthesis>,
+    ) -> usize {
+        let index = self.fonts.len();
+        self.fonts
+            .push((font, CharmapProxy::from_font(&font), synthesis));
+        self.clear_cache();
+        index
+    }
+
+    /// Sets the fonts, specified by index, that are preferred for clusters
+    /// in the specified script.
+    pub fn set_script_fonts(&mut self, script: Script, fonts: &[usize]) {
+        let fonts = self.indices(fonts);
+        if let Some(entry) = self.scripts.iter_mut().find(|e| e.0 == script) {
+            entry.1 = fonts;
+        } else {
+            self.scripts.push((script, fonts));
+        }
+        self.clear_cache();
+    }
+
+    /// Sets the fonts, specified by index, that are preferred when the
+    /// current language matches the specified language. A language matches
+    /// if the language subtags are equal and the script and region
+    /// subtags are either unspecified or equal.
+    pub fn set_language_fonts(&mut self, language: Language, fonts: &[usize]) {
+        let fonts = self.indices(fonts);
+        if let Some(entry) = self
+            .languages
+            .iter_mut()
+            .find(|e| e.0 == language)
+        {
+            entry.1 = fonts;
+        } else {
+            self.languages.push((language, fonts));
+        }
+        self.clear_cache();
+    }
+
+    /// Sets the fonts, specified by index, that are preferred for clusters
+    /// with color emoji presentation.
+    pub fn set_emoji_fonts(&mut self, fonts: &[usize]) {
+        self.emoji = self.indices(fonts);
+        self.clear_cache();
+    }
+
+    /// Sets the language of the text being shaped. This should generally
+    /// match the language provided in the shape options.
+    pub fn set_language(&mut self, language: Option<Language>) {
+        if self.language != language {
+            self.language = language;
+            self.clear_cache();
+        }
+    }
+
+    /// Sets the presentation used for emoji clusters that are not followed
+    /// by a variation selector. This should be either [`Emoji::Color`] (the
+    /// default) or [`Emoji::Text`].
+    pub fn set_emoji_presentation(&mut self, presentation: Emoji) {
+        let presentation = match presentation {
+            Emoji::Text => Emoji::Text,
+            _ => Emoji::Color,
+        };
+        if presentation != self.presentation {
+            self.presentation = presentation;
+            self.clear_cache();
+        }
+    }
+
+    /// Clears the cache of recent selections.
+    pub fn clear_cache(&mut self) {
+        for entry in &mut self.cache {
+            *entry = (!0, 0);
+        }
+    }
+
+    fn indices(&self, fonts: &[usize]) -> Vec<u16> {
+        fonts
+            .iter()
+            .filter(|&&i| i < self.fonts.len())
+            .map(|&i| i as u16)
+            .collect()
+    }
+
+    fn selected(&self, index: u16) -> FallbackFont<'a> {
+        let (font, _, synthesis) = self.fonts[index as usize];
+        FallbackFont {
+            index,
+            font,
+            synthesis,
+        }
+    }
+
+    fn map(&self, index: u16, cluster: &mut CharCluster) -> Status {
+        let (font, proxy, _) = &self.fonts[index as usize];
+        cluster.map(proxy.materialize(font))
+    }
+
+    fn search(&self, cluster: &mut CharCluster) -> u16 {
+        let emoji = match cluster.info().emoji() {
+            Emoji::Default => self.presentation,
+            emoji => emoji,
+        };
+        let script = cluster
+            .chars()
+            .iter()
+            .map(|c| c.ch.script())
+            .find(|s| !matches!(s, Script::Common | Script::Inherited | Script::Unknown));
+        let empty: &[u16] = &[];
+        let emoji_fonts = if emoji == Emoji::Color {
+            &self.emoji[..]
+        } else {
+            empty
+        };
+        let language_fonts = self
+            .language
+            .as_ref()
+            .and_then(|lang| {
+                self.languages
+                    .iter()
+                    .find(|(l, _)| language_matches(l, lang))
+            })
+            .map(|e| &e.1[..])
+            .unwrap_or(empty);
+        let script_fonts = script
+            .and_then(|script| self.scripts.iter().find(|e| e.0 == script))
+            .map(|e| &e.1[..])
+            .unwrap_or(empty);
+        let all = 0..self.fonts.len() as u16;
+        let mut best = None;
+        for index in emoji_fonts
+            .iter()
+            .chain(language_fonts)
+            .chain(script_fonts)
+            .copied()
+            .chain(all)
+        {
+            match self.map(index, cluster) {
+                Status::Complete => return index,
+                Status::Keep => best = Some(index),
+                Status::Discard => {}
+            }
+        }
+        best.unwrap_or(0)
+    }
+}
+
+impl Default for FallbackSelector<'_> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'a> Selector for FallbackSelector<'a> {
+    type SelectedFont = FallbackFont<'a>;
+
+    fn select_font(&mut self, cluster: &mut CharCluster) -> Option<Self::SelectedFont> {
+        if self.fonts.is_empty() {
+            return None;
+        }
+        let chars = cluster.chars();
+        let key = if chars.len() == 1 {
+            Some(chars[0].ch as u32 | (cluster.info().emoji() as u32) << 21)
+        } else {
+            None
+        };
+        if let Some(key) = key {
+            let slot = key as usize % CACHE_SIZE;
+            let (cached_key, index) = self.cache[slot];
+            if cached_key == key {
+                self.map(index, cluster);
+                return Some(self.selected(index));
+            }
+            let index = self.search(cluster);
+            self.cache[slot] = (key, index);
+            return Some(self.selected(index));
+        }
+        // The search leaves the cluster mapped with the selected font.
+        Some(self.selected(self.search(cluster)))
+    }
+}
+
+fn language_matches(a: &Language, b: &Language) -> bool {
+    a.language() == b.language()
+        && (a.script().is_none() || a.script() == b.script())
+        && (a.region().is_none() || a.region() == b.region())
+}
+
 /// Trait for types that specify shaping options.
 pub trait ShapeOptions {
     /// Iterator over the feature settings for a fragment.
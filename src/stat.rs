@@ -0,0 +1,318 @@
+use super::internal::{fixed::Fixed, stat::*};
+use super::{
+    string::{LocalizedString, StringId},
+    text::Language,
+    FontRef, NormalizedCoord, Tag,
+};
+
+/// Maximum number of design axes considered when composing style names.
+const MAX_AXES: usize = 32;
+
+/// Iterator over a collection of style axis values.
+///
+/// Axis values are defined by the `STAT` table and describe named positions
+/// or ranges along the design axes of a font family.
+#[derive(Copy, Clone)]
+pub struct AxisValues<'a> {
+    font: FontRef<'a>,
+    stat: Stat<'a>,
+    len: usize,
+    pos: usize,
+}
+
+impl<'a> AxisValues<'a> {
+    pub(crate) fn from_font(font: &FontRef<'a>) -> Self {
+        let stat = Stat::from_font(font).unwrap_or_else(|| Stat::new(&[]));
+        Self {
+            font: *font,
+            stat,
+            len: stat.value_count() as usize,
+            pos: 0,
+        }
+    }
+
+    fn get(&self, index: usize) -> Option<AxisValue<'a>> {
+        let inner = self.stat.get_value(index as u16)?;
+        Some(AxisValue {
+            font: self.font,
+            stat: self.stat,
+            inner,
+        })
+    }
+
+    /// Returns the name identifier to use for the subfamily when all
+    /// axis value names are elided.
+    pub fn elided_fallback_name_id(&self) -> StringId {
+        self.stat
+            .elided_fallback_name_id()
+            .map(StringId::from_raw)
+            .unwrap_or(StringId::SubFamily)
+    }
+
+    /// Returns an iterator over the identifiers of the names that compose
+    /// the subfamily name for the specified normalized variation
+    /// coordinates.
+    ///
+    /// For each design axis, in the order specified by the font, this
+    /// selects the axis value that matches the coordinates, preferring
+    /// values that cover multiple axes. When no value or range matches an
+    /// axis exactly, the nearest single value is selected, favoring the
+    /// lower value when two are equally near. Elidable names are omitted
+    /// and the elided fallback name is produced if nothing remains. Design
+    /// axes that are not present in the `fvar` table are ignored.
+    ///
+    /// ## Iteration behavior
+    /// This function searches the entire axis value collection without regard
+    /// for the current state of the iterator.
+    pub fn subfamily_name_ids(
+        &self,
+        coords: &[NormalizedCoord],
+    ) -> impl Iterator<Item = StringId> + Clone {
+        let axis_count = (self.stat.axis_count() as usize).min(MAX_AXES);
+        // Selected axis value for each design axis.
+        let mut selected = [None; MAX_AXES];
+        let max_records = (0..self.len)
+            .filter_map(|i| self.get(i))
+            .map(|v| v.inner.record_count())
+            .max()
+            .unwrap_or(0);
+        // Values covering multiple axes take precedence.
+        for count in (2..=max_records).rev() {
+            for value in (0..self.len).filter_map(|i| self.get(i)) {
+                if value.inner.format != 4 || value.inner.record_count() != count {
+                    continue;
+                }
+                let covers = |selected: &[Option<u16>]| {
+                    (0..count).all(|i| match value.inner.record(i) {
+                        Some((axis, v)) => {
+                            (axis as usize) < axis_count
+                                && selected[axis as usize].is_none()
+                                && value.matches(axis, v, v, coords)
+                        }
+                        _ => false,
+                    })
+                };
+                if !value.is_older_sibling() && covers(&selected) {
+                    for (axis, _) in (0..count).filter_map(|i| value.inner.record(i)) {
+                        selected[axis as usize] = Some(value.inner.index);
+                    }
+                }
+            }
+        }
+        for axis in 0..axis_count {
+            if selected[axis].is_some() {
+                continue;
+            }
+            let axis = axis as u16;
+            // Nearest single value keyed by distance and whether it lies
+            // above the coordinate.
+            let mut nearest: Option<((i32, bool), u16)> = None;
+            let mut range = None;
+            for value in (0..self.len).filter_map(|i| self.get(i)) {
+                let inner = &value.inner;
+                if inner.format == 4 || inner.axis_index != axis || value.is_older_sibling() {
+                    continue;
+                }
+                if inner.format == 2 {
+                    if range.is_none() && value.matches(axis, inner.min, inner.max, coords) {
+                        range = Some(inner.index);
+                    }
+                } else if let Some(delta) = value.delta(axis, inner.value, coords) {
+                    let key = (delta.abs(), delta > 0);
+                    if nearest.map(|n| key < n.0).unwrap_or(true) {
+                        nearest = Some((key, inner.index));
+                    }
+                }
+            }
+            selected[axis as usize] = match nearest {
+                Some(((0, _), index)) => Some(index),
+                _ => range.or(nearest.map(|n| n.1)),
+            };
+        }
+        // Order the selected values by the ordering of their first design
+        // axis and remove duplicates produced by multiple axis values.
+        let mut order = [(0u16, 0u16); MAX_AXES];
+        let mut len = 0;
+        for (axis, value) in selected.iter().enumerate().take(axis_count) {
+            let value = match value {
+                Some(value) => *value,
+                _ => continue,
+            };
+            if order[..len].iter().any(|o| o.1 == value) {
+                continue;
+            }
+            let ordering = self
+                .stat
+                .get_axis(axis as u16)
+                .map(|a| a.ordering)
+                .unwrap_or(0);
+            order[len] = (ordering, value);
+            len += 1;
+        }
+        order[..len].sort_unstable();
+        let mut ids = [0u16; MAX_AXES];
+        let mut count = 0;
+        for (_, value) in &order[..len] {
+            if let Some(value) = self.get(*value as usize) {
+                if !value.is_elidable() {
+                    ids[count] = value.inner.name_id;
+                    count += 1;
+                }
+            }
+        }
+        if count == 0 {
+            ids[0] = self.elided_fallback_name_id().to_raw();
+            count = 1;
+        }
+        (0..count).map(move |i| StringId::from_raw(ids[i]))
+    }
+
+    /// Returns the subfamily name for the specified normalized variation
+    /// coordinates, optionally for a particular language.
+    ///
+    /// This joins the names produced by
+    /// [`subfamily_name_ids`](Self::subfamily_name_ids) with spaces,
+    /// falling back to English and then any decodable string for names
+    /// that are not available in the requested language.
+    pub fn subfamily_name(
+        &self,
+        coords: &[NormalizedCoord],
+        language: Option<&Language>,
+    ) -> String {
+        let strings = self.font.localized_strings();
+        let mut name = String::new();
+        for id in self.subfamily_name_ids(coords) {
+            if let Some(string) = strings.find_best(&[id], language) {
+                if !name.is_empty() {
+                    name.push(' ');
+                }
+                name.extend(string.chars());
+            }
+        }
+        name
+    }
+}
+
+impl_iter!(AxisValues, AxisValue);
+
+/// Named position or range along one or more design axes.
+#[derive(Copy, Clone)]
+pub struct AxisValue<'a> {
+    font: FontRef<'a>,
+    stat: Stat<'a>,
+    inner: StatValue<'a>,
+}
+
+impl<'a> AxisValue<'a> {
+    /// Returns the index of the axis value.
+    pub fn index(&self) -> usize {
+        self.inner.index as usize
+    }
+
+    /// Returns the format of the axis value: 1 for a single value, 2 for a
+    /// range, 3 for a value with a linked style and 4 for a combination of
+    /// values on multiple axes.
+    pub fn format(&self) -> u16 {
+        self.inner.format
+    }
+
+    /// Returns the tag of the design axis for single axis values.
+    pub fn tag(&self) -> Option<Tag> {
+        if self.inner.format == 4 {
+            return None;
+        }
+        Some(self.stat.get_axis(self.inner.axis_index)?.tag)
+    }
+
+    /// Returns the nominal value for single axis values.
+    pub fn value(&self) -> Option<f32> {
+        if self.inner.format == 4 {
+            return None;
+        }
+        Some(self.inner.value.to_f32())
+    }
+
+    /// Returns the minimum and maximum values of the range covered by the
+    /// axis value (format 2).
+    pub fn range(&self) -> Option<(f32, f32)> {
+        if self.inner.format == 2 {
+            Some((self.inner.min.to_f32(), self.inner.max.to_f32()))
+        } else {
+            None
+        }
+    }
+
+    /// Returns the value of the style linked to this axis value, such as
+    /// the corresponding bold weight (format 3).
+    pub fn linked_value(&self) -> Option<f32> {
+        self.inner.linked_value.map(|v| v.to_f32())
+    }
+
+    /// Returns an iterator over the design axis tag and value pairs covered
+    /// by the axis value.
+    pub fn values(&self) -> impl Iterator<Item = (Tag, f32)> + 'a {
+        let inner = self.inner;
+        let stat = self.stat;
+        (0..inner.record_count()).filter_map(move |i| {
+            let (axis, value) = inner.record(i)?;
+            Some((stat.get_axis(axis)?.tag, value.to_f32()))
+        })
+    }
+
+    /// Returns true if the name may be omitted when composing style names.
+    pub fn is_elidable(&self) -> bool {
+        self.inner.flags & ELIDABLE_AXIS_VALUE_NAME != 0
+    }
+
+    /// Returns true if the axis value applies to older versions of the
+    /// font family rather than the current font.
+    pub fn is_older_sibling(&self) -> bool {
+        self.inner.flags & OLDER_SIBLING_FONT_ATTRIBUTE != 0
+    }
+
+    /// Returns the name identifier for the axis value.
+    pub fn name_id(&self) -> StringId {
+        StringId::from_raw(self.inner.name_id)
+    }
+
+    /// Returns the name for the axis value, optionally for a particular
+    /// language, falling back to English and then any decodable string.
+    pub fn name(&self, language: Option<&Language>) -> Option<LocalizedString<'a>> {
+        self.font
+            .localized_strings()
+            .find_best(&[self.name_id()], language)
+    }
+
+    /// Returns true if the normalized coordinate for the specified design
+    /// axis lies within the specified range of user values.
+    fn matches(&self, axis: u16, min: Fixed, max: Fixed, coords: &[NormalizedCoord]) -> bool {
+        if min == max {
+            return self.delta(axis, min, coords) == Some(0);
+        }
+        let tag = match self.stat.get_axis(axis) {
+            Some(axis) => axis.tag,
+            _ => return false,
+        };
+        let var = match self.font.variations().find_by_tag(tag) {
+            Some(var) => var,
+            _ => return false,
+        };
+        let coord = coords.get(var.index()).copied().unwrap_or(0);
+        var.normalize(min.to_f32()) <= coord && coord <= var.normalize(max.to_f32())
+    }
+
+    /// Returns the signed distance in normalized units from the coordinate
+    /// for the specified design axis to the specified user value.
+    fn delta(&self, axis: u16, value: Fixed, coords: &[NormalizedCoord]) -> Option<i32> {
+        let tag = self.stat.get_axis(axis)?.tag;
+        let var = self.font.variations().find_by_tag(tag)?;
+        let coord = coords.get(var.index()).copied().unwrap_or(0);
+        let value = value.to_f32();
+        // Values outside the axis range would be clamped by normalization
+        // and must not match the extremes.
+        if value < var.min_value() || value > var.max_value() {
+            return None;
+        }
+        Some(var.normalize(value) as i32 - coord as i32)
+    }
+}
@@ -262,7 +262,7 @@ impl<'a> BitmapStrike<'a> {
     #[cfg(feature = "scale")]
     pub(crate) fn get(&self, glyph_id: GlyphId) -> Option<Bitmap<'a>> {
         let loc = get_location(&self.data, self.offset, self.is_sbix, glyph_id)?;
-        loc.get(self.bitmap_data, self.upem, self.is_apple)
+        loc.get(self)
     }
 }
 
@@ -278,6 +278,10 @@ pub enum BitmapFormat {
     Color,
     /// PNG encoded.
     Png,
+    /// JPEG encoded.
+    Jpeg,
+    /// TIFF encoded.
+    Tiff,
 }
 
 #[cfg(feature = "scale")]
@@ -310,6 +314,8 @@ pub struct Bitmap<'a> {
     pub top: i32,
     pub advance: u32,
     pub data: &'a [u8],
+    /// Strike containing the components of a composite bitmap.
+    composite: Option<BitmapStrike<'a>>,
 }
 
 #[cfg(feature = "scale")]
@@ -319,15 +325,25 @@ impl<'a> Bitmap<'a> {
             BitmapFormat::Packed(data.bit_depth)
         } else if data.is_png {
             BitmapFormat::Png
+        } else if data.is_jpeg {
+            BitmapFormat::Jpeg
+        } else if data.is_tiff {
+            BitmapFormat::Tiff
         } else if data.bit_depth == 32 {
             BitmapFormat::Color
         } else {
             BitmapFormat::Alpha(data.bit_depth)
         };
-        let (width, height, left, top, advance) = if data.is_png {
-            let png = Bytes::new(data.data);
-            let width = png.read::<u32>(16)?;
-            let height = png.read::<u32>(20)?;
+        let size = match format {
+            BitmapFormat::Png => {
+                let png = Bytes::new(data.data);
+                Some((png.read::<u32>(16)?, png.read::<u32>(20)?))
+            }
+            BitmapFormat::Jpeg => Some(super::scale::jpeg_size(data.data)?),
+            BitmapFormat::Tiff => Some(super::scale::tiff_size(data.data)?),
+            _ => None,
+        };
+        let (width, height, left, top, advance) = if let Some((width, height)) = size {
             let (left, mut top) = (data.metrics.x as i32, data.metrics.y as i32);
             let advance = if data.is_sbix {
                 if top == 0 && is_apple {
@@ -359,6 +375,7 @@ impl<'a> Bitmap<'a> {
             top,
             advance,
             data: data.data,
+            composite: None,
         })
     }
 
@@ -391,8 +408,17 @@ impl<'a> Bitmap<'a> {
         if target.len() < size {
             return false;
         }
+        if let Some(strike) = &self.composite {
+            let mut budget = MAX_COMPONENTS;
+            return self.decode_composite(strike, &mut target[..size], 0, &mut budget);
+        }
         let w = self.width as usize;
         let h = self.height as usize;
+        if (w == 0 || h == 0)
+            && matches!(self.format, BitmapFormat::Packed(_) | BitmapFormat::Alpha(_))
+        {
+            return true;
+        }
         let src = self.data;
         let dst = &mut *target;
         match self.format {
@@ -461,6 +487,94 @@ impl<'a> Bitmap<'a> {
                     return false;
                 }
             }
+            BitmapFormat::Jpeg => {
+                use super::scale::decode_jpeg;
+                if decode_jpeg(src, scratch, target).is_none() {
+                    return false;
+                }
+            }
+            BitmapFormat::Tiff => {
+                use super::scale::decode_tiff;
+                if decode_tiff(src, scratch, target).is_none() {
+                    return false;
+                }
+            }
+        }
+        true
+    }
+
+    /// Assembles a composite bitmap by drawing each component glyph at its
+    /// offset from the top left corner.
+    ///
+    /// The budget limits the total number of components that are drawn
+    /// across all levels of nesting.
+    fn decode_composite(
+        &self,
+        strike: &BitmapStrike<'a>,
+        target: &mut [u8],
+        depth: u32,
+        budget: &mut u32,
+    ) -> bool {
+        const MAX_DEPTH: u32 = 8;
+        if depth > MAX_DEPTH {
+            return false;
+        }
+        let channels = self.format.channels() as usize;
+        let pitch = self.width as usize * channels;
+        for x in target.iter_mut() {
+            *x = 0;
+        }
+        let d = Bytes::new(self.data);
+        let mut buf = Vec::new();
+        for i in 0..self.data.len() / 4 {
+            let offset = i * 4;
+            let component = match strike.get(d.read_or_default::<u16>(offset)) {
+                Some(component) if component.format.channels() as usize == channels => component,
+                _ => return false,
+            };
+            if *budget == 0 {
+                return false;
+            }
+            *budget -= 1;
+            let w = component.width as usize;
+            if w == 0 || component.height == 0 {
+                continue;
+            }
+            let x = d.read_or_default::<i8>(offset + 2) as isize;
+            let y = d.read_or_default::<i8>(offset + 3) as isize;
+            buf.clear();
+            buf.resize(component.decoded_size(), 0);
+            let ok = if let Some(strike) = &component.composite {
+                component.decode_composite(strike, &mut buf, depth + 1, budget)
+            } else {
+                component.decode(None, &mut buf)
+            };
+            if !ok {
+                return false;
+            }
+            for (row, src) in buf.chunks_exact(w * channels).enumerate() {
+                let ty = y + row as isize;
+                if ty < 0 || ty >= self.height as isize {
+                    continue;
+                }
+                for (col, src) in src.chunks_exact(channels).enumerate() {
+                    let tx = x + col as isize;
+                    if tx < 0 || tx >= self.width as isize {
+                        continue;
+                    }
+                    let start = ty as usize * pitch + tx as usize * channels;
+                    let dst = &mut target[start..start + channels];
+                    if channels == 1 {
+                        dst[0] = dst[0].max(src[0]);
+                    } else {
+                        // Source over for premultiplied color.
+                        let a = 255 - src[3] as u32;
+                        for (d, s) in dst.iter_mut().zip(src) {
+                            *d = (*s as u32 + (*d as u32 * a + 127) / 255).min(255) as u8;
+                        }
+                    }
+                }
+            }
         }
         true
     }
@@ -485,9 +599,14 @@ struct Location {
 
 #[cfg(feature = "scale")]
 impl Location {
-    /// Gets a bitmap from this location in the specified data source.
-    pub fn get<'a>(&self, data: &'a [u8], upem: u16, is_apple: bool) -> Option<Bitmap<'a>> {
-        Bitmap::new(&get_data(data, self)?, upem, is_apple)
+    /// Gets a bitmap from this location in the specified strike.
+    pub fn get<'a>(&self, strike: &BitmapStrike<'a>) -> Option<Bitmap<'a>> {
+        let data = get_data(strike.bitmap_data, self)?;
+        let mut bitmap = Bitmap::new(&data, strike.upem, strike.is_apple)?;
+        if data.is_composite {
+            bitmap.composite = Some(*strike);
+        }
+        Some(bitmap)
     }
 }
 
@@ -657,7 +776,10 @@ fn get_data<'a>(table: &'a [u8], loc: &Location) -> Option<BitmapData<'a>> {
         vertical_metrics: loc.vertical_metrics,
         is_packed: false,
         is_png: false,
+        is_jpeg: false,
+        is_tiff: false,
         is_sbix: false,
+        is_composite: false,
     };
     let d = &Bytes::new(table);
     let offset = loc.offset as usize;
@@ -666,7 +788,11 @@ fn get_data<'a>(table: &'a [u8], loc: &Location) -> Option<BitmapData<'a>> {
     match loc.format {
         0xFF => {
             bitmap.data = d.read_bytes(offset, size)?;
-            bitmap.is_png = true;
+            match d.read::<u32>(offset - 4)? {
+                SBIX_JPG => bitmap.is_jpeg = true,
+                SBIX_TIFF => bitmap.is_tiff = true,
+                _ => bitmap.is_png = true,
+            }
             bitmap.is_sbix = true;
             Some(bitmap)
         }
@@ -705,6 +831,20 @@ fn get_data<'a>(table: &'a [u8], loc: &Location) -> Option<BitmapData<'a>> {
             bitmap.is_packed = true;
             Some(bitmap)
         }
+        8 => {
+            bitmap.read_metrics(d, offset, flags, false)?;
+            let count = d.read::<u16>(offset + 6)? as usize;
+            bitmap.data = d.read_bytes(offset + 8, count * 4)?;
+            bitmap.is_composite = true;
+            Some(bitmap)
+        }
+        9 => {
+            bitmap.read_metrics(d, offset, flags, true)?;
+            let count = d.read::<u16>(offset + 8)? as usize;
+            bitmap.data = d.read_bytes(offset + 10, count * 4)?;
+            bitmap.is_composite = true;
+            Some(bitmap)
+        }
         17 => {
             bitmap.read_metrics(d, offset, flags, false)?;
             let size = d.read::<u32>(offset + 5)? as usize;
@@ -729,6 +869,15 @@ fn get_data<'a>(table: &'a [u8], loc: &Location) -> Option<BitmapData<'a>> {
     }
 }
 
+const SBIX_PNG: RawTag = raw_tag(b"png ");
+const SBIX_JPG: RawTag = raw_tag(b"jpg ");
+const SBIX_TIFF: RawTag = raw_tag(b"tiff");
+
+/// Maximum number of components drawn for a single composite bitmap,
+/// including those of nested composites.
+#[cfg(feature = "scale")]
+const MAX_COMPONENTS: u32 = 256;
+
 fn sbix_range(
     table: &[u8],
     strike_base: usize,
@@ -736,7 +885,6 @@ fn sbix_range(
     recurse: i32,
 ) -> Option<(u32, u32)> {
     const DUPE: RawTag = raw_tag(b"dupe");
-    const PNG: RawTag = raw_tag(b"png ");
     if recurse > 1 {
         return None;
     }
@@ -754,7 +902,7 @@ fn sbix_range(
     if tag == DUPE {
         let dupe = b.read::<u16>(start as usize + 8)?;
         sbix_range(table, strike_base, dupe, recurse + 1)
-    } else if tag == PNG {
+    } else if tag == SBIX_PNG || tag == SBIX_JPG || tag == SBIX_TIFF {
         Some((start, end))
     } else {
         None
@@ -774,7 +922,10 @@ struct BitmapData<'a> {
     pub vertical_metrics: Metrics,
     pub is_packed: bool,
     pub is_png: bool,
+    pub is_jpeg: bool,
+    pub is_tiff: bool,
     pub is_sbix: bool,
+    pub is_composite: bool,
 }
 
 #[cfg(feature = "scale")]
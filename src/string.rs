@@ -5,6 +5,7 @@ Localized names and other metadata.
 use std::fmt::Write;
 
 use super::internal::*;
+use super::text::Language;
 use super::FontRef;
 
 const NAME: RawTag = raw_tag(b"name");
@@ -176,6 +177,52 @@ impl<'a> LocalizedStrings<'a> {
         }
     }
 
+    /// Searches for the most appropriate decodable string with one of the
+    /// specified identifiers for the specified language.
+    ///
+    /// The identifiers are considered in the order specified. For each
+    /// identifier, strings are preferred in the following order: those that
+    /// match the full language tag, those that match the primary language
+    /// subtag, English strings and finally the first decodable string.
+    /// Within each group, Unicode strings are preferred over other encodings.
+    ///
+    /// ## Iteration behavior
+    /// This function searches the entire string collection without regard
+    /// for the current state of the iterator.
+    pub fn find_best(
+        &self,
+        ids: &[StringId],
+        language: Option<&Language>,
+    ) -> Option<LocalizedString<'a>> {
+        let mut best = None;
+        let mut best_rank = (usize::MAX, u8::MAX, true);
+        for i in 0..self.len() {
+            let rec = match self.get(i) {
+                Some(rec) => rec,
+                _ => continue,
+            };
+            let raw_id = rec.raw_id();
+            let id_rank = match ids.iter().position(|id| id.to_raw() == raw_id) {
+                Some(rank) => rank,
+                _ => continue,
+            };
+            let encoding = rec.encoding();
+            if !encoding.is_decodable() {
+                continue;
+            }
+            let rank = (
+                id_rank,
+                language_rank(rec.language(), language),
+                encoding != Encoding::Unicode,
+            );
+            if rank < best_rank {
+                best_rank = rank;
+                best = Some(rec);
+            }
+        }
+        best
+    }
+
     /// Returns the string at the specified index.
     fn get(&self, index: usize) -> Option<LocalizedString<'a>> {
         if index >= self.len {
@@ -249,7 +296,7 @@ impl<'a> LocalizedString<'a> {
         let offset = self.data.read_or_default::<u16>(self.offset + 10) as usize + self.storage;
         Chars {
             record: *self,
-            bytes: if encoding == Encoding::MacRoman {
+            bytes: if encoding != Encoding::Unicode {
                 self.bytes().unwrap_or(&[])
             } else {
                 &[]
@@ -334,11 +381,24 @@ impl<'a> Iterator for Chars<'a> {
                 self.cur += 1;
                 if c > 127 {
                     let idx = c as usize - 128;
-                    Some(from_u32(MAC_ROMAN[idx] as u32).unwrap_or(rep))
+                    Some(from_u32(encoding::MAC_ROMAN[idx] as u32).unwrap_or(rep))
                 } else {
                     Some(from_u32(c).unwrap_or(rep))
                 }
             }
+            #[cfg(feature = "legacy-encodings")]
+            _ => {
+                // Legacy double byte strings on the Windows platform are
+                // commonly stored as 16-bit units, so skip the padding.
+                let bytes = self.bytes.get(..self.len)?;
+                while bytes.get(self.cur) == Some(&0) {
+                    self.cur += 1;
+                }
+                let (ch, len) = self.encoding.decode_legacy(bytes.get(self.cur..)?)?;
+                self.cur += len;
+                Some(ch)
+            }
+            #[cfg(not(feature = "legacy-encodings"))]
             _ => None,
         }
     }
@@ -353,14 +413,75 @@ impl<'a> IntoIterator for LocalizedString<'a> {
     }
 }
 
+/// Ranks a string language tag with respect to a requested language: 0 for
+/// a full match, 1 for a primary language match, 2 for English and 3
+/// otherwise.
+fn language_rank(tag: &str, language: Option<&Language>) -> u8 {
+    let mut parts = tag.split('-');
+    let primary = parts.next().unwrap_or("");
+    let rest = parts.next();
+    if let Some(language) = language {
+        if primary.eq_ignore_ascii_case(language.language()) {
+            let exact = match rest {
+                Some(rest) => {
+                    let eq = |s: Option<&str>| s.map(|s| s.eq_ignore_ascii_case(rest)) == Some(true);
+                    let script = language.script();
+                    eq(language.region())
+                        || eq(script)
+                        || matches!(
+                            (implied_script(primary, rest), script),
+                            (Some(a), Some(b)) if a.eq_ignore_ascii_case(b)
+                        )
+                }
+                _ => language.region().is_none() && language.script().is_none(),
+            };
+            return if exact { 0 } else { 1 };
+        }
+    }
+    if primary.eq_ignore_ascii_case("en") {
+        2
+    } else {
+        3
+    }
+}
+
+/// Returns the script implied by the region for Chinese language tags.
+fn implied_script(primary: &str, region: &str) -> Option<&'static str> {
+    if !primary.eq_ignore_ascii_case("zh") {
+        return None;
+    }
+    match region {
+        "cn" | "CN" | "sg" | "SG" => Some("Hans"),
+        "tw" | "TW" | "hk" | "HK" | "mo" | "MO" => Some("Hant"),
+        _ => None,
+    }
+}
+
 /// Encoding of a localized string.
 ///
 /// Fonts can contain a variety of platform specific and legacy encodings.
-/// Only the ones we decode are listed here.
+/// Only the ones we decode are listed here. The legacy encodings are only
+/// decodable when the `legacy-encodings` feature is enabled.
 #[derive(Copy, Clone, PartialEq, Debug)]
+#[non_exhaustive]
 pub enum Encoding {
     Unicode,
     MacRoman,
+    /// Shift-JIS (Windows) or Mac OS Japanese.
+    ShiftJis,
+    /// GB2312/GBK (Windows PRC) or Mac OS Simplified Chinese.
+    Prc,
+    /// Big5 (Windows) or Mac OS Traditional Chinese.
+    Big5,
+    /// Wansung (Windows) or Mac OS Korean.
+    Wansung,
+    /// Johab (Windows).
+    Johab,
+    MacArabic,
+    MacHebrew,
+    MacGreek,
+    MacCyrillic,
+    MacCentralEuropean,
     Other { platform_id: u16, encoding_id: u16 },
 }
 
@@ -369,8 +490,22 @@ impl Encoding {
         match (platform_id, encoding_id) {
             (0, _) => Self::Unicode,
             (1, 0) => Self::MacRoman,
+            (1, 1) => Self::ShiftJis,
+            (1, 2) => Self::Big5,
+            (1, 3) => Self::Wansung,
+            (1, 4) => Self::MacArabic,
+            (1, 5) => Self::MacHebrew,
+            (1, 6) => Self::MacGreek,
+            (1, 7) => Self::MacCyrillic,
+            (1, 25) => Self::Prc,
+            (1, 29) => Self::MacCentralEuropean,
             (3, 0) => Self::Unicode,
             (3, 1) => Self::Unicode,
+            (3, 2) => Self::ShiftJis,
+            (3, 3) => Self::Prc,
+            (3, 4) => Self::Big5,
+            (3, 5) => Self::Wansung,
+            (3, 6) => Self::Johab,
             (3, 10) => Self::Unicode,
             _ => Self::Other {
                 platform_id,
@@ -381,23 +516,43 @@ impl Encoding {
 
     /// Returns true if this encoding is can be turned into a string.
     pub fn is_decodable(&self) -> bool {
-        !matches!(self, Self::Other { .. })
+        match self {
+            Self::Unicode | Self::MacRoman => true,
+            Self::Other { .. } => false,
+            _ => cfg!(feature = "legacy-encodings"),
+        }
     }
-}
 
-#[rustfmt::skip]
-const MAC_ROMAN: [u16; 128] = [
-    196, 197, 199, 201, 209, 214, 220, 225, 224, 226, 228, 227, 229, 231, 233,
-    232, 234, 235, 237, 236, 238, 239, 241, 243, 242, 244, 246, 245, 250, 249,
-    251, 252, 8224, 176, 162, 163, 167, 8226, 182, 223, 174, 169, 8482, 180,
-    168, 8800, 198, 216, 8734, 177, 8804, 8805, 165, 181, 8706, 8721, 8719,
-    960, 8747, 170, 186, 937, 230, 248, 191, 161, 172, 8730, 402, 8776, 8710,
-    171, 187, 8230, 160, 192, 195, 213, 338, 339, 8211, 8212, 8220, 8221, 8216,
-    8217, 247, 9674, 255, 376, 8260, 8364, 8249, 8250, 64257, 64258, 8225, 183,
-    8218, 8222, 8240, 194, 202, 193, 203, 200, 205, 206, 207, 204, 211, 212,
-    63743, 210, 218, 219, 217, 305, 710, 732, 175, 728, 729, 730, 184, 733,
-    731, 711,
-];
+    /// Decodes the character at the start of the specified bytes for
+    /// legacy encodings, returning the character and the number of bytes
+    /// consumed.
+    #[cfg(feature = "legacy-encodings")]
+    fn decode_legacy(&self, bytes: &[u8]) -> Option<(char, usize)> {
+        let single_byte = |table: &[u16; 128]| {
+            let c = *bytes.first()?;
+            let ch = if c < 0x80 {
+                c as char
+            } else {
+                core::char::from_u32(table[c as usize - 0x80] as u32)
+                    .unwrap_or(core::char::REPLACEMENT_CHARACTER)
+            };
+            Some((ch, 1))
+        };
+        match self {
+            Self::ShiftJis => encoding::decode_double_byte(encoding_rs::SHIFT_JIS, bytes),
+            Self::Prc => encoding::decode_double_byte(encoding_rs::GBK, bytes),
+            Self::Big5 => encoding::decode_double_byte(encoding_rs::BIG5, bytes),
+            Self::Wansung => encoding::decode_double_byte(encoding_rs::EUC_KR, bytes),
+            Self::Johab => encoding::decode_johab(bytes),
+            Self::MacArabic => single_byte(&encoding::MAC_ARABIC),
+            Self::MacHebrew => single_byte(&encoding::MAC_HEBREW),
+            Self::MacGreek => single_byte(&encoding::MAC_GREEK),
+            Self::MacCyrillic => single_byte(&encoding::MAC_CYRILLIC),
+            Self::MacCentralEuropean => single_byte(&encoding::MAC_CENTRAL_EUROPEAN),
+            _ => None,
+        }
+    }
+}
 
 #[rustfmt::skip]
 const LANGUAGES: [(u32, &'static str); 334] = [
@@ -0,0 +1,173 @@
+//! SVG documents for color glyphs.
+
+use super::internal::*;
+use super::{FontRef, GlyphId};
+
+const SVG: RawTag = raw_tag(b"SVG ");
+
+/// Iterator over the SVG documents in a font.
+#[derive(Copy, Clone)]
+pub struct SvgDocuments<'a> {
+    data: Bytes<'a>,
+    base: u32,
+    list: usize,
+    len: usize,
+    pos: usize,
+}
+
+impl<'a> SvgDocuments<'a> {
+    pub(crate) fn new(data: &'a [u8], base: u32) -> Self {
+        let data = Bytes::new(data);
+        let list = data.read_or_default::<u32>(2) as usize;
+        let len = if list != 0 {
+            data.read_or_default::<u16>(list) as usize
+        } else {
+            0
+        };
+        Self {
+            data,
+            base,
+            list,
+            len,
+            pos: 0,
+        }
+    }
+
+    pub(crate) fn from_font(font: &FontRef<'a>) -> Self {
+        let offset = font.table_offset(SVG);
+        Self::from_font_and_offset(font, offset)
+    }
+
+    pub(crate) fn from_font_and_offset(font: &FontRef<'a>, offset: u32) -> Self {
+        if offset == 0 {
+            return Self::new(&[], 0);
+        }
+        let data = font.data.get(offset as usize..).unwrap_or(&[]);
+        Self::new(data, offset)
+    }
+
+    /// Returns the document that contains the specified glyph.
+    pub fn for_glyph(&self, glyph_id: GlyphId) -> Option<SvgDocument<'a>> {
+        let b = &self.data;
+        let mut l = 0;
+        let mut h = self.len;
+        while l < h {
+            let i = l + (h - l) / 2;
+            let rec = self.list + 2 + i * 12;
+            let first = b.read::<u16>(rec)?;
+            let last = b.read::<u16>(rec + 2)?;
+            if glyph_id < first {
+                h = i;
+            } else if glyph_id > last {
+                l = i + 1;
+            } else {
+                return self.get(i);
+            }
+        }
+        None
+    }
+
+    fn get(&self, index: usize) -> Option<SvgDocument<'a>> {
+        let b = &self.data;
+        let rec = self.list + 2 + index * 12;
+        let first_glyph = b.read::<u16>(rec)?;
+        let last_glyph = b.read::<u16>(rec + 2)?;
+        let offset = self.list + b.read::<u32>(rec + 4)? as usize;
+        let len = b.read::<u32>(rec + 8)? as usize;
+        let data = b.read_bytes(offset, len)?;
+        Some(SvgDocument {
+            data,
+            offset: self.base + offset as u32,
+            first_glyph,
+            last_glyph,
+        })
+    }
+}
+
+impl_iter!(SvgDocuments, SvgDocument);
+
+/// SVG document describing one or more color glyphs.
+///
+/// The element describing a particular glyph is identified by an `id`
+/// attribute of the form `glyph<id>`, for example `glyph42`. Glyphs are
+/// drawn in font units with the y-axis pointing down.
+#[derive(Copy, Clone)]
+pub struct SvgDocument<'a> {
+    data: &'a [u8],
+    pub(crate) offset: u32,
+    first_glyph: GlyphId,
+    last_glyph: GlyphId,
+}
+
+impl<'a> SvgDocument<'a> {
+    /// Returns the first glyph described by the document.
+    pub fn first_glyph(&self) -> GlyphId {
+        self.first_glyph
+    }
+
+    /// Returns the last glyph described by the document.
+    pub fn last_glyph(&self) -> GlyphId {
+        self.last_glyph
+    }
+
+    /// Returns true if the document describes the specified glyph.
+    pub fn contains(&self, glyph_id: GlyphId) -> bool {
+        glyph_id >= self.first_glyph && glyph_id <= self.last_glyph
+    }
+
+    /// Returns true if the document is compressed with gzip.
+    pub fn is_compressed(&self) -> bool {
+        self.data.starts_with(&[0x1F, 0x8B])
+    }
+
+    /// Returns the raw data of the document, which may be compressed.
+    pub fn data(&self) -> &'a [u8] {
+        self.data
+    }
+
+    /// Writes the uncompressed text of the document to the specified
+    /// buffer. Returns false if decompression fails.
+    #[cfg(feature = "scale")]
+    pub fn decompress_into(&self, target: &mut Vec<u8>) -> bool {
+        target.clear();
+        if !self.is_compressed() {
+            target.extend_from_slice(self.data);
+            return true;
+        }
+        let data = match gzip_body(self.data) {
+            Some(data) => data,
+            _ => return false,
+        };
+        let mut decoder = yazi::Decoder::new();
+        decoder.set_format(yazi::Format::Raw);
+        let mut stream = decoder.stream_into_vec(target);
+        stream.write(data).is_ok() && stream.finish().is_ok()
+    }
+}
+
+/// Returns the compressed portion of a gzip member.
+#[cfg(feature = "scale")]
+fn gzip_body(data: &[u8]) -> Option<&[u8]> {
+    const FHCRC: u8 = 2;
+    const FEXTRA: u8 = 4;
+    const FNAME: u8 = 8;
+    const FCOMMENT: u8 = 16;
+    if *data.get(2)? != 8 {
+        return None;
+    }
+    let flags = *data.get(3)?;
+    let mut offset = 10;
+    if flags & FEXTRA != 0 {
+        let len = *data.get(offset)? as usize | (*data.get(offset + 1)? as usize) << 8;
+        offset += 2 + len;
+    }
+    for flag in &[FNAME, FCOMMENT] {
+        if flags & flag != 0 {
+            offset += data.get(offset..)?.iter().position(|&b| b == 0)? + 1;
+        }
+    }
+    if flags & FHCRC != 0 {
+        offset += 2;
+    }
+    data.get(offset..)
+}
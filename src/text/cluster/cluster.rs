@@ -10,6 +10,33 @@ use core::ops::Range;
 /// The maximum number of characters in a single cluster.
 pub const MAX_CLUSTER_SIZE: usize = 32;
 
+/// The maximum number of dropped variation selectors recorded per cluster.
+const MAX_SELECTORS: usize = 8;
+
+/// Source of glyph identifiers for mapping character clusters.
+///
+/// This is implemented for closures that map characters to nominal glyph
+/// identifiers and for [`Charmap`](crate::Charmap), which additionally
+/// supports Unicode variation sequences.
+pub trait GlyphMapper {
+    /// Returns the nominal glyph identifier for the specified character.
+    fn map(&self, ch: char) -> GlyphId;
+
+    /// Returns the glyph identifier for the variation sequence composed of
+    /// the specified character and variation selector, or `None` if the
+    /// nominal glyph should be used.
+    fn map_variant(&self, ch: char, selector: char) -> Option<GlyphId> {
+        let _ = (ch, selector);
+        None
+    }
+}
+
+impl<F: Fn(char) -> GlyphId> GlyphMapper for F {
+    fn map(&self, ch: char) -> GlyphId {
+        self(ch)
+    }
+}
+
 /// Character cluster; output from the parser and input to the shaper.
 #[derive(Copy, Clone)]
 pub struct CharCluster {
@@ -24,6 +51,8 @@ pub struct CharCluster {
     decomp: Form,
     form: FormKind,
     best_ratio: f32,
+    selectors: [(u32, char); MAX_SELECTORS],
+    selector_count: u8,
 }
 
 impl CharCluster {
@@ -41,6 +70,8 @@ impl CharCluster {
             decomp: Form::new(),
             form: FormKind::Original,
             best_ratio: 0.,
+            selectors: [(0, ' '); MAX_SELECTORS],
+            selector_count: 0,
         }
     }
 
@@ -83,16 +114,23 @@ impl CharCluster {
 
     /// Applies a nominal glyph identifier mapping to the cluster, returning
     /// a result indicating the status of the mapping.
-    pub fn map(&mut self, f: impl Fn(char) -> GlyphId) -> Status {
+    ///
+    /// Characters followed by a variation selector are mapped with
+    /// [`GlyphMapper::map_variant`] when the mapper supports the sequence.
+    /// This includes emoji presentation selectors which are otherwise
+    /// dropped from the cluster.
+    pub fn map(&mut self, f: impl GlyphMapper) -> Status {
         let len = self.len;
         if len == 0 {
             return Status::Complete;
         }
         let mut glyph_ids = [0u16; MAX_CLUSTER_SIZE];
         let prev_ratio = self.best_ratio;
+        let selectors = self.selectors;
+        let selectors = &selectors[..self.selector_count as usize];
         let mut ratio;
         if self.force_normalize && self.composed().is_some() {
-            ratio = self.comp.map(&f, &mut glyph_ids, self.best_ratio);
+            ratio = self.comp.map(&f, selectors, &mut glyph_ids, self.best_ratio);
             if ratio > self.best_ratio {
                 self.best_ratio = ratio;
                 self.form = FormKind::NFC;
@@ -104,6 +142,7 @@ impl CharCluster {
         ratio = Mapper {
             chars: &mut self.chars[..self.len as usize],
             map_len: self.map_len,
+            selectors,
         }
         .map(&f, &mut glyph_ids, self.best_ratio);
         if ratio > self.best_ratio {
@@ -114,7 +153,7 @@ impl CharCluster {
             }
         }
         if len > 1 && self.decomposed().is_some() {
-            ratio = self.decomp.map(&f, &mut glyph_ids, self.best_ratio);
+            ratio = self.decomp.map(&f, selectors, &mut glyph_ids, self.best_ratio);
             if ratio > self.best_ratio {
                 self.best_ratio = ratio;
                 self.form = FormKind::NFD;
@@ -123,7 +162,7 @@ impl CharCluster {
                 }
             }
             if !self.force_normalize && self.composed().is_some() {
-                ratio = self.comp.map(&f, &mut glyph_ids, self.best_ratio);
+                ratio = self.comp.map(&f, selectors, &mut glyph_ids, self.best_ratio);
                 if ratio > self.best_ratio {
                     self.best_ratio = ratio;
                     self.form = FormKind::NFC;
@@ -152,6 +191,7 @@ impl CharCluster {
         self.decomp.clear();
         self.form = FormKind::Original;
         self.best_ratio = 0.;
+        self.selector_count = 0;
     }
 
     /// Returns the sequence of decomposed characters for the cluster.
@@ -262,10 +302,18 @@ impl CharCluster {
     /// This function records the attributes and range information for
     /// a character but does not add it to the cluster. It is used when
     /// characters such as emoji variation selectors are dropped from
-    /// shaping but should still be included in the cluster range.
+    /// shaping but should still be included in the cluster range. Dropped
+    /// variation selectors are remembered for mapping the preceding
+    /// character.
     pub(super) fn note_char(&mut self, input: &Token) {
         if self.len == 0 {
             self.start = input.offset;
+        } else if is_variation_selector(input.ch)
+            && (self.selector_count as usize) < MAX_SELECTORS
+        {
+            let offset = self.chars[self.len as usize - 1].offset;
+            self.selectors[self.selector_count as usize] = (offset, input.ch);
+            self.selector_count += 1;
         }
         self.info.merge_boundary(input.info.boundary() as u16);
         self.end = input.offset + input.len as u32;
@@ -368,13 +416,15 @@ impl Form {
 
     fn map(
         &mut self,
-        f: &impl Fn(char) -> u16,
+        f: &impl GlyphMapper,
+        selectors: &[(u32, char)],
         glyphs: &mut [u16; MAX_CLUSTER_SIZE],
         best_ratio: f32,
     ) -> f32 {
         Mapper {
             chars: &mut self.chars[..self.len as usize],
             map_len: self.map_len,
+            selectors,
         }
         .map(f, glyphs, best_ratio)
     }
@@ -383,12 +433,15 @@ impl Form {
 struct Mapper<'a> {
     chars: &'a mut [Char],
     map_len: u8,
+    /// Dropped variation selectors keyed by the offset of the character
+    /// they follow.
+    selectors: &'a [(u32, char)],
 }
 
 impl<'a> Mapper<'a> {
     fn map(
         &mut self,
-        f: &impl Fn(char) -> u16,
+        f: &impl GlyphMapper,
         glyphs: &mut [u16; MAX_CLUSTER_SIZE],
         best_ratio: f32,
     ) -> f32 {
@@ -396,11 +449,21 @@ impl<'a> Mapper<'a> {
             return 1.;
         }
         let mut mapped = 0;
-        for (c, g) in self.chars.iter().zip(glyphs.iter_mut()) {
+        for (i, (c, g)) in self.chars.iter().zip(glyphs.iter_mut()).enumerate() {
             if !c.contributes_to_shaping {
                 *g = 0;
             } else {
-                let gid = f(c.ch);
+                let selector = match self.chars.get(i + 1) {
+                    Some(next) if next.shape_class == ShapeClass::Vs => Some(next.ch),
+                    _ => self
+                        .selectors
+                        .iter()
+                        .find(|(offset, _)| *offset == c.offset)
+                        .map(|(_, selector)| *selector),
+                };
+                let gid = selector
+                    .and_then(|selector| f.map_variant(c.ch, selector))
+                    .unwrap_or_else(|| f.map(c.ch));
                 *g = gid;
                 if gid != 0 {
                     mapped += 1;
@@ -417,6 +480,10 @@ impl<'a> Mapper<'a> {
     }
 }
 
+fn is_variation_selector(ch: char) -> bool {
+    matches!(ch as u32, 0xFE00..=0xFE0F | 0xE0100..=0xE01EF)
+}
+
 const DEFAULT_CHAR: Char = Char {
     ch: ' ',
     shape_class: ShapeClass::Base,
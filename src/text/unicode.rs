@@ -11,8 +11,8 @@ pub use super::unicode_data::{
 
 use super::compose::{compose_pair, decompose, decompose_compat};
 use super::unicode_data::{
-    get_record_index, MyanmarClass, UseClass, BRACKETS, MIRRORS, RECORDS, SCRIPTS_BY_TAG,
-    SCRIPT_COMPLEXITY, SCRIPT_NAMES, SCRIPT_TAGS, Record,
+    get_record_index, MyanmarClass, UseClass, BLOCK_STARTS, BRACKETS, MIRRORS, RECORDS,
+    SCRIPTS_BY_TAG, SCRIPT_COMPLEXITY, SCRIPT_NAMES, SCRIPT_TAGS, Record,
 };
 use crate::Tag;
 
@@ -380,3 +380,23 @@ impl BidiClass {
         self.mask() & BIDI_MASK != 0
     }
 }
+
+impl Block {
+    /// Invokes the specified closure with each block that intersects the
+    /// inclusive range of codepoints and the number of codepoints in the
+    /// intersection.
+    pub(crate) fn for_each_in_range(start: u32, end: u32, mut f: impl FnMut(Block, usize)) {
+        let mut i = match BLOCK_STARTS.binary_search_by(|b| b.0.cmp(&start)) {
+            Ok(i) => i,
+            Err(i) => i.saturating_sub(1),
+        };
+        let mut cur = start;
+        while i < BLOCK_STARTS.len() && cur <= end {
+            let block_end = BLOCK_STARTS.get(i + 1).map_or(0x10FFFF, |b| b.0 - 1);
+            let last = block_end.min(end);
+            f(BLOCK_STARTS[i].1, (last - cur + 1) as usize);
+            cur = last + 1;
+            i += 1;
+        }
+    }
+}
@@ -1125,6 +1125,372 @@ pub const MIRRORS: [(u16, u16); 420] = [
     (0xFF5D, 0xFF5B), (0xFF5F, 0xFF60), (0xFF60, 0xFF5F), (0xFF62, 0xFF63), (0xFF63, 0xFF62),
 ];
 
+#[rustfmt::skip]
+pub const BLOCK_STARTS: [(u32, Block); 362] = [
+    (0x0, Block::BasicLatin),
+    (0x80, Block::Latin1Supplement),
+    (0x100, Block::LatinExtendedA),
+    (0x180, Block::LatinExtendedB),
+    (0x250, Block::IPAExtensions),
+    (0x2B0, Block::SpacingModifierLetters),
+    (0x300, Block::CombiningDiacriticalMarks),
+    (0x370, Block::GreekandCoptic),
+    (0x400, Block::Cyrillic),
+    (0x500, Block::CyrillicSupplement),
+    (0x530, Block::Armenian),
+    (0x590, Block::Hebrew),
+    (0x600, Block::Arabic),
+    (0x700, Block::Syriac),
+    (0x750, Block::ArabicSupplement),
+    (0x780, Block::Thaana),
+    (0x7C0, Block::NKo),
+    (0x800, Block::Samaritan),
+    (0x840, Block::Mandaic),
+    (0x860, Block::SyriacSupplement),
+    (0x870, Block::NoBlock),
+    (0x8A0, Block::ArabicExtendedA),
+    (0x900, Block::Devanagari),
+    (0x980, Block::Bengali),
+    (0xA00, Block::Gurmukhi),
+    (0xA80, Block::Gujarati),
+    (0xB00, Block::Oriya),
+    (0xB80, Block::Tamil),
+    (0xC00, Block::Telugu),
+    (0xC80, Block::Kannada),
+    (0xD00, Block::Malayalam),
+    (0xD80, Block::Sinhala),
+    (0xE00, Block::Thai),
+    (0xE80, Block::Lao),
+    (0xF00, Block::Tibetan),
+    (0x1000, Block::Myanmar),
+    (0x10A0, Block::Georgian),
+    (0x1100, Block::HangulJamo),
+    (0x1200, Block::Ethiopic),
+    (0x1380, Block::EthiopicSupplement),
+    (0x13A0, Block::Cherokee),
+    (0x1400, Block::UnifiedCanadianAboriginalSyllabics),
+    (0x1680, Block::Ogham),
+    (0x16A0, Block::Runic),
+    (0x1700, Block::Tagalog),
+    (0x1720, Block::Hanunoo),
+    (0x1740, Block::Buhid),
+    (0x1760, Block::Tagbanwa),
+    (0x1780, Block::Khmer),
+    (0x1800, Block::Mongolian),
+    (0x18B0, Block::UnifiedCanadianAboriginalSyllabicsExtended),
+    (0x1900, Block::Limbu),
+    (0x1950, Block::TaiLe),
+    (0x1980, Block::NewTaiLue),
+    (0x19E0, Block::KhmerSymbols),
+    (0x1A00, Block::Buginese),
+    (0x1A20, Block::TaiTham),
+    (0x1AB0, Block::CombiningDiacriticalMarksExtended),
+    (0x1B00, Block::Balinese),
+    (0x1B80, Block::Sundanese),
+    (0x1BC0, Block::Batak),
+    (0x1C00, Block::Lepcha),
+    (0x1C50, Block::OlChiki),
+    (0x1C80, Block::CyrillicExtendedC),
+    (0x1C90, Block::GeorgianExtended),
+    (0x1CC0, Block::SundaneseSupplement),
+    (0x1CD0, Block::VedicExtensions),
+    (0x1D00, Block::PhoneticExtensions),
+    (0x1D80, Block::PhoneticExtensionsSupplement),
+    (0x1DC0, Block::CombiningDiacriticalMarksSupplement),
+    (0x1E00, Block::LatinExtendedAdditional),
+    (0x1F00, Block::GreekExtended),
+    (0x2000, Block::GeneralPunctuation),
+    (0x2070, Block::SuperscriptsandSubscripts),
+    (0x20A0, Block::CurrencySymbols),
+    (0x20D0, Block::CombiningDiacriticalMarksforSymbols),
+    (0x2100, Block::LetterlikeSymbols),
+    (0x2150, Block::NumberForms),
+    (0x2190, Block::Arrows),
+    (0x2200, Block::MathematicalOperators),
+    (0x2300, Block::MiscellaneousTechnical),
+    (0x2400, Block::ControlPictures),
+    (0x2440, Block::OpticalCharacterRecognition),
+    (0x2460, Block::EnclosedAlphanumerics),
+    (0x2500, Block::BoxDrawing),
+    (0x2580, Block::BlockElements),
+    (0x25A0, Block::GeometricShapes),
+    (0x2600, Block::MiscellaneousSymbols),
+    (0x2700, Block::Dingbats),
+    (0x27C0, Block::MiscellaneousMathematicalSymbolsA),
+    (0x27F0, Block::SupplementalArrowsA),
+    (0x2800, Block::BraillePatterns),
+    (0x2900, Block::SupplementalArrowsB),
+    (0x2980, Block::MiscellaneousMathematicalSymbolsB),
+    (0x2A00, Block::SupplementalMathematicalOperators),
+    (0x2B00, Block::MiscellaneousSymbolsandArrows),
+    (0x2C00, Block::Glagolitic),
+    (0x2C60, Block::LatinExtendedC),
+    (0x2C80, Block::Coptic),
+    (0x2D00, Block::GeorgianSupplement),
+    (0x2D30, Block::Tifinagh),
+    (0x2D80, Block::EthiopicExtended),
+    (0x2DE0, Block::CyrillicExtendedA),
+    (0x2E00, Block::SupplementalPunctuation),
+    (0x2E80, Block::CJKRadicalsSupplement),
+    (0x2F00, Block::KangxiRadicals),
+    (0x2FE0, Block::NoBlock),
+    (0x2FF0, Block::IdeographicDescriptionCharacters),
+    (0x3000, Block::CJKSymbolsandPunctuation),
+    (0x3040, Block::Hiragana),
+    (0x30A0, Block::Katakana),
+    (0x3100, Block::Bopomofo),
+    (0x3130, Block::HangulCompatibilityJamo),
+    (0x3190, Block::Kanbun),
+    (0x31A0, Block::BopomofoExtended),
+    (0x31C0, Block::CJKStrokes),
+    (0x31F0, Block::KatakanaPhoneticExtensions),
+    (0x3200, Block::EnclosedCJKLettersandMonths),
+    (0x3300, Block::CJKCompatibility),
+    (0x3400, Block::CJKUnifiedIdeographsExtensionA),
+    (0x4DC0, Block::YijingHexagramSymbols),
+    (0x4E00, Block::CJKUnifiedIdeographs),
+    (0xA000, Block::YiSyllables),
+    (0xA490, Block::YiRadicals),
+    (0xA4D0, Block::Lisu),
+    (0xA500, Block::Vai),
+    (0xA640, Block::CyrillicExtendedB),
+    (0xA6A0, Block::Bamum),
+    (0xA700, Block::ModifierToneLetters),
+    (0xA720, Block::LatinExtendedD),
+    (0xA800, Block::SylotiNagri),
+    (0xA830, Block::CommonIndicNumberForms),
+    (0xA840, Block::Phagspa),
+    (0xA880, Block::Saurashtra),
+    (0xA8E0, Block::DevanagariExtended),
+    (0xA900, Block::KayahLi),
+    (0xA930, Block::Rejang),
+    (0xA960, Block::HangulJamoExtendedA),
+    (0xA980, Block::Javanese),
+    (0xA9E0, Block::MyanmarExtendedB),
+    (0xAA00, Block::Cham),
+    (0xAA60, Block::MyanmarExtendedA),
+    (0xAA80, Block::TaiViet),
+    (0xAAE0, Block::MeeteiMayekExtensions),
+    (0xAB00, Block::EthiopicExtendedA),
+    (0xAB30, Block::LatinExtendedE),
+    (0xAB70, Block::CherokeeSupplement),
+    (0xABC0, Block::MeeteiMayek),
+    (0xAC00, Block::HangulSyllables),
+    (0xD7B0, Block::HangulJamoExtendedB),
+    (0xD800, Block::HighSurrogates),
+    (0xDB80, Block::HighPrivateUseSurrogates),
+    (0xDC00, Block::LowSurrogates),
+    (0xE000, Block::PrivateUseArea),
+    (0xF900, Block::CJKCompatibilityIdeographs),
+    (0xFB00, Block::AlphabeticPresentationForms),
+    (0xFB50, Block::ArabicPresentationFormsA),
+    (0xFE00, Block::VariationSelectors),
+    (0xFE10, Block::VerticalForms),
+    (0xFE20, Block::CombiningHalfMarks),
+    (0xFE30, Block::CJKCompatibilityForms),
+    (0xFE50, Block::SmallFormVariants),
+    (0xFE70, Block::ArabicPresentationFormsB),
+    (0xFF00, Block::HalfwidthandFullwidthForms),
+    (0xFFF0, Block::Specials),
+    (0x10000, Block::LinearBSyllabary),
+    (0x10080, Block::LinearBIdeograms),
+    (0x10100, Block::AegeanNumbers),
+    (0x10140, Block::AncientGreekNumbers),
+    (0x10190, Block::AncientSymbols),
+    (0x101D0, Block::PhaistosDisc),
+    (0x10200, Block::NoBlock),
+    (0x10280, Block::Lycian),
+    (0x102A0, Block::Carian),
+    (0x102E0, Block::CopticEpactNumbers),
+    (0x10300, Block::OldItalic),
+    (0x10330, Block::Gothic),
+    (0x10350, Block::OldPermic),
+    (0x10380, Block::Ugaritic),
+    (0x103A0, Block::OldPersian),
+    (0x103E0, Block::NoBlock),
+    (0x10400, Block::Deseret),
+    (0x10450, Block::Shavian),
+    (0x10480, Block::Osmanya),
+    (0x104B0, Block::Osage),
+    (0x10500, Block::Elbasan),
+    (0x10530, Block::CaucasianAlbanian),
+    (0x10570, Block::NoBlock),
+    (0x10600, Block::LinearA),
+    (0x10780, Block::NoBlock),
+    (0x10800, Block::CypriotSyllabary),
+    (0x10840, Block::ImperialAramaic),
+    (0x10860, Block::Palmyrene),
+    (0x10880, Block::Nabataean),
+    (0x108B0, Block::NoBlock),
+    (0x108E0, Block::Hatran),
+    (0x10900, Block::Phoenician),
+    (0x10920, Block::Lydian),
+    (0x10940, Block::NoBlock),
+    (0x10980, Block::MeroiticHieroglyphs),
+    (0x109A0, Block::MeroiticCursive),
+    (0x10A00, Block::Kharoshthi),
+    (0x10A60, Block::OldSouthArabian),
+    (0x10A80, Block::OldNorthArabian),
+    (0x10AA0, Block::NoBlock),
+    (0x10AC0, Block::Manichaean),
+    (0x10B00, Block::Avestan),
+    (0x10B40, Block::InscriptionalParthian),
+    (0x10B60, Block::InscriptionalPahlavi),
+    (0x10B80, Block::PsalterPahlavi),
+    (0x10BB0, Block::NoBlock),
+    (0x10C00, Block::OldTurkic),
+    (0x10C50, Block::NoBlock),
+    (0x10C80, Block::OldHungarian),
+    (0x10D00, Block::HanifiRohingya),
+    (0x10D40, Block::NoBlock),
+    (0x10E60, Block::RumiNumeralSymbols),
+    (0x10E80, Block::Yezidi),
+    (0x10EC0, Block::NoBlock),
+    (0x10F00, Block::OldSogdian),
+    (0x10F30, Block::Sogdian),
+    (0x10F70, Block::NoBlock),
+    (0x10FB0, Block::Chorasmian),
+    (0x10FE0, Block::Elymaic),
+    (0x11000, Block::Brahmi),
+    (0x11080, Block::Kaithi),
+    (0x110D0, Block::SoraSompeng),
+    (0x11100, Block::Chakma),
+    (0x11150, Block::Mahajani),
+    (0x11180, Block::Sharada),
+    (0x111E0, Block::SinhalaArchaicNumbers),
+    (0x11200, Block::Khojki),
+    (0x11250, Block::NoBlock),
+    (0x11280, Block::Multani),
+    (0x112B0, Block::Khudawadi),
+    (0x11300, Block::Grantha),
+    (0x11380, Block::NoBlock),
+    (0x11400, Block::Newa),
+    (0x11480, Block::Tirhuta),
+    (0x114E0, Block::NoBlock),
+    (0x11580, Block::Siddham),
+    (0x11600, Block::Modi),
+    (0x11660, Block::MongolianSupplement),
+    (0x11680, Block::Takri),
+    (0x116D0, Block::NoBlock),
+    (0x11700, Block::Ahom),
+    (0x11740, Block::NoBlock),
+    (0x11800, Block::Dogra),
+    (0x11850, Block::NoBlock),
+    (0x118A0, Block::WarangCiti),
+    (0x11900, Block::DivesAkuru),
+    (0x11960, Block::NoBlock),
+    (0x119A0, Block::Nandinagari),
+    (0x11A00, Block::ZanabazarSquare),
+    (0x11A50, Block::Soyombo),
+    (0x11AB0, Block::NoBlock),
+    (0x11AC0, Block::PauCinHau),
+    (0x11B00, Block::NoBlock),
+    (0x11C00, Block::Bhaiksuki),
+    (0x11C70, Block::Marchen),
+    (0x11CC0, Block::NoBlock),
+    (0x11D00, Block::MasaramGondi),
+    (0x11D60, Block::GunjalaGondi),
+    (0x11DB0, Block::NoBlock),
+    (0x11EE0, Block::Makasar),
+    (0x11F00, Block::NoBlock),
+    (0x11FB0, Block::LisuSupplement),
+    (0x11FC0, Block::TamilSupplement),
+    (0x12000, Block::Cuneiform),
+    (0x12400, Block::CuneiformNumbersandPunctuation),
+    (0x12480, Block::EarlyDynasticCuneiform),
+    (0x12550, Block::NoBlock),
+    (0x13000, Block::EgyptianHieroglyphs),
+    (0x13430, Block::EgyptianHieroglyphFormatControls),
+    (0x13440, Block::NoBlock),
+    (0x14400, Block::AnatolianHieroglyphs),
+    (0x14680, Block::NoBlock),
+    (0x16800, Block::BamumSupplement),
+    (0x16A40, Block::Mro),
+    (0x16A70, Block::NoBlock),
+    (0x16AD0, Block::BassaVah),
+    (0x16B00, Block::PahawhHmong),
+    (0x16B90, Block::NoBlock),
+    (0x16E40, Block::Medefaidrin),
+    (0x16EA0, Block::NoBlock),
+    (0x16F00, Block::Miao),
+    (0x16FA0, Block::NoBlock),
+    (0x16FE0, Block::IdeographicSymbolsandPunctuation),
+    (0x17000, Block::Tangut),
+    (0x18800, Block::TangutComponents),
+    (0x18B00, Block::KhitanSmallScript),
+    (0x18D00, Block::TangutSupplement),
+    (0x18D90, Block::NoBlock),
+    (0x1B000, Block::KanaSupplement),
+    (0x1B100, Block::KanaExtendedA),
+    (0x1B130, Block::SmallKanaExtension),
+    (0x1B170, Block::Nushu),
+    (0x1B300, Block::NoBlock),
+    (0x1BC00, Block::Duployan),
+    (0x1BCA0, Block::ShorthandFormatControls),
+    (0x1BCB0, Block::NoBlock),
+    (0x1D000, Block::ByzantineMusicalSymbols),
+    (0x1D100, Block::MusicalSymbols),
+    (0x1D200, Block::AncientGreekMusicalNotation),
+    (0x1D250, Block::NoBlock),
+    (0x1D2E0, Block::MayanNumerals),
+    (0x1D300, Block::TaiXuanJingSymbols),
+    (0x1D360, Block::CountingRodNumerals),
+    (0x1D380, Block::NoBlock),
+    (0x1D400, Block::MathematicalAlphanumericSymbols),
+    (0x1D800, Block::SuttonSignWriting),
+    (0x1DAB0, Block::NoBlock),
+    (0x1E000, Block::GlagoliticSupplement),
+    (0x1E030, Block::NoBlock),
+    (0x1E100, Block::NyiakengPuachueHmong),
+    (0x1E150, Block::NoBlock),
+    (0x1E2C0, Block::Wancho),
+    (0x1E300, Block::NoBlock),
+    (0x1E800, Block::MendeKikakui),
+    (0x1E8E0, Block::NoBlock),
+    (0x1E900, Block::Adlam),
+    (0x1E960, Block::NoBlock),
+    (0x1EC70, Block::IndicSiyaqNumbers),
+    (0x1ECC0, Block::NoBlock),
+    (0x1ED00, Block::OttomanSiyaqNumbers),
+    (0x1ED50, Block::NoBlock),
+    (0x1EE00, Block::ArabicMathematicalAlphabeticSymbols),
+    (0x1EF00, Block::NoBlock),
+    (0x1F000, Block::MahjongTiles),
+    (0x1F030, Block::DominoTiles),
+    (0x1F0A0, Block::PlayingCards),
+    (0x1F100, Block::EnclosedAlphanumericSupplement),
+    (0x1F200, Block::EnclosedIdeographicSupplement),
+    (0x1F300, Block::MiscellaneousSymbolsandPictographs),
+    (0x1F600, Block::Emoticons),
+    (0x1F650, Block::OrnamentalDingbats),
+    (0x1F680, Block::TransportandMapSymbols),
+    (0x1F700, Block::AlchemicalSymbols),
+    (0x1F780, Block::GeometricShapesExtended),
+    (0x1F800, Block::SupplementalArrowsC),
+    (0x1F900, Block::SupplementalSymbolsandPictographs),
+    (0x1FA00, Block::ChessSymbols),
+    (0x1FA70, Block::SymbolsandPictographsExtendedA),
+    (0x1FB00, Block::SymbolsforLegacyComputing),
+    (0x1FC00, Block::NoBlock),
+    (0x20000, Block::CJKUnifiedIdeographsExtensionB),
+    (0x2A6E0, Block::NoBlock),
+    (0x2A700, Block::CJKUnifiedIdeographsExtensionC),
+    (0x2B740, Block::CJKUnifiedIdeographsExtensionD),
+    (0x2B820, Block::CJKUnifiedIdeographsExtensionE),
+    (0x2CEB0, Block::CJKUnifiedIdeographsExtensionF),
+    (0x2EBF0, Block::NoBlock),
+    (0x2F800, Block::CJKCompatibilityIdeographsSupplement),
+    (0x2FA20, Block::NoBlock),
+    (0x30000, Block::CJKUnifiedIdeographsExtensionG),
+    (0x31350, Block::NoBlock),
+    (0xE0000, Block::Tags),
+    (0xE0080, Block::NoBlock),
+    (0xE0100, Block::VariationSelectorsSupplement),
+    (0xE01F0, Block::NoBlock),
+    (0xF0000, Block::SupplementaryPrivateUseAreaA),
+    (0x100000, Block::SupplementaryPrivateUseAreaB),
+];
+
 #[rustfmt::skip]
 const RECORD_INDEX0: [u8; 4352] = [
     0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19, 20, 21, 22, 23, 24, 25,